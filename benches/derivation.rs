@@ -0,0 +1,30 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use crypto_key_manager::{seed, DerivationPath};
+
+const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+fn bench_derivation(c: &mut Criterion) {
+    let master = seed::generate_master_key_from_mnemonic(TEST_MNEMONIC, "").unwrap();
+    let paths: Vec<DerivationPath> = (0..10_000)
+        .map(|i| format!("m/84'/0'/0'/{}/{}", i % 2, i / 2).parse().unwrap())
+        .collect();
+    let keys: Vec<_> = paths[..1000].iter().map(|path| master.derive(path).unwrap()).collect();
+
+    let mut group = c.benchmark_group("derivation");
+    group.sample_size(10);
+    group.bench_function("derive_10k_paths", |b| {
+        b.iter(|| paths.iter().map(|path| master.derive(path).unwrap()).collect::<Vec<_>>())
+    });
+    // Non-hardened siblings reuse the parent's cached public key
+    let parent = master.derive_path("m/84'/0'/0'/0").unwrap();
+    group.bench_function("derive_1k_siblings", |b| {
+        b.iter(|| (0..1000).map(|index| parent.derive_normal(index).unwrap()).collect::<Vec<_>>())
+    });
+    group.bench_function("to_string_1k", |b| {
+        b.iter(|| keys.iter().map(|key| key.to_string()).collect::<Vec<_>>())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_derivation);
+criterion_main!(benches);
@@ -0,0 +1,21 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use crypto_key_manager::utils;
+
+/// The previous `bytes_to_hex`, kept as a baseline
+fn bytes_to_hex_format(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn bench_hex(c: &mut Criterion) {
+    let buffer: Vec<u8> = (0..1024u32).map(|i| (i * 31) as u8).collect();
+    let encoded = utils::bytes_to_hex(&buffer);
+
+    let mut group = c.benchmark_group("hex_1kb");
+    group.bench_function("encode_format", |b| b.iter(|| bytes_to_hex_format(black_box(&buffer))));
+    group.bench_function("encode_table", |b| b.iter(|| utils::bytes_to_hex(black_box(&buffer))));
+    group.bench_function("decode", |b| b.iter(|| utils::hex_to_bytes(black_box(&encoded)).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, bench_hex);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use crypto_key_manager::{seed, signing, DerivationPath};
+
+const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+fn digests(count: usize) -> Vec<[u8; 32]> {
+    (0..count as u32)
+        .map(|i| {
+            let mut digest = [0u8; 32];
+            digest[..4].copy_from_slice(&i.to_be_bytes());
+            digest
+        })
+        .collect()
+}
+
+fn bench_signing(c: &mut Criterion) {
+    let master = seed::generate_master_key_from_mnemonic(TEST_MNEMONIC, "").unwrap();
+    let key = master.derive_path("m/84'/0'/0'/0/0").unwrap();
+    let digests = digests(1000);
+
+    let mut group = c.benchmark_group("sign_1000");
+    group.sample_size(10);
+    group.bench_function("individual", |b| {
+        b.iter(|| {
+            digests
+                .iter()
+                .map(|d| signing::sign_ecdsa(&key, d).unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("batch", |b| b.iter(|| signing::sign_batch(&key, &digests).unwrap()));
+
+    let items: Vec<(DerivationPath, [u8; 32])> = (0..1000)
+        .map(|i| format!("m/84'/0'/0'/0/{}", i % 20).parse().unwrap())
+        .zip(digests.iter().copied())
+        .collect();
+    group.bench_function("batch_at_paths", |b| {
+        b.iter(|| signing::sign_batch_at_paths(&master, &items).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_signing);
+criterion_main!(benches);
@@ -0,0 +1,166 @@
+use crate::error::{KeyManagerError, Result};
+use crate::hd_key::ExtendedKey;
+use crate::network::Network;
+use crate::taproot;
+use crate::utils::{base58, bech32, ec, hash160};
+use crate::xpub::SlipVersion;
+use secp256k1::PublicKey;
+use zeroize::Zeroize;
+
+/// Single-key output script types and their BIP44-style purpose numbers
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScriptType {
+    /// Legacy pay-to-pubkey-hash (BIP44, `1...`)
+    P2pkh,
+    /// P2WPKH nested in P2SH (BIP49, `3...`)
+    P2shP2wpkh,
+    /// Native segwit v0 (BIP84, `bc1q...`)
+    P2wpkh,
+    /// Taproot key-path only (BIP86, `bc1p...`)
+    P2tr,
+}
+
+impl ScriptType {
+    /// All script types, in purpose order
+    pub const ALL: [ScriptType; 4] = [ScriptType::P2pkh, ScriptType::P2shP2wpkh, ScriptType::P2wpkh, ScriptType::P2tr];
+
+    /// BIP44-style purpose number (44, 49, 84 or 86)
+    pub fn purpose(self) -> u32 {
+        match self {
+            ScriptType::P2pkh => 44,
+            ScriptType::P2shP2wpkh => 49,
+            ScriptType::P2wpkh => 84,
+            ScriptType::P2tr => 86,
+        }
+    }
+
+    /// Script type for a purpose number, if it is one of the standard four
+    pub fn from_purpose(purpose: u32) -> Option<Self> {
+        ScriptType::ALL.into_iter().find(|script_type| script_type.purpose() == purpose)
+    }
+
+    /// Lowercase name used in CLI options and JSON output ("p2sh-p2wpkh")
+    pub fn name(self) -> &'static str {
+        match self {
+            ScriptType::P2pkh => "p2pkh",
+            ScriptType::P2shP2wpkh => "p2sh-p2wpkh",
+            ScriptType::P2wpkh => "p2wpkh",
+            ScriptType::P2tr => "p2tr",
+        }
+    }
+
+    /// SLIP-132 prefix wallets expect for account keys of this type
+    ///
+    /// Taproot has no SLIP-132 prefix, so it uses plain xpub/tpub.
+    pub fn slip_version(self, network: Network) -> SlipVersion {
+        match (self, network) {
+            (ScriptType::P2shP2wpkh, Network::Mainnet) => SlipVersion::Ypub,
+            (ScriptType::P2shP2wpkh, Network::Testnet | Network::Regtest) => SlipVersion::Upub,
+            (ScriptType::P2wpkh, Network::Mainnet) => SlipVersion::Zpub,
+            (ScriptType::P2wpkh, Network::Testnet | Network::Regtest) => SlipVersion::Vpub,
+            (ScriptType::P2pkh | ScriptType::P2tr, network) => network.xpub_version(),
+        }
+    }
+
+    /// Address of this type for a compressed public key
+    pub fn address_from_pubkey(self, public_key: &[u8; 33], network: Network) -> Result<String> {
+        match self {
+            ScriptType::P2pkh => Ok(p2pkh_from_pubkey(public_key, network)),
+            ScriptType::P2shP2wpkh => Ok(p2sh_p2wpkh_from_pubkey(public_key, network)),
+            ScriptType::P2wpkh => p2wpkh_from_pubkey(public_key, network),
+            ScriptType::P2tr => p2tr_from_pubkey(public_key, network),
+        }
+    }
+}
+
+/// Address of the given script type for a derived key
+pub fn address(key: &ExtendedKey, script_type: ScriptType, network: Network) -> Result<String> {
+    script_type.address_from_pubkey(&key.public_key()?, network)
+}
+
+/// Legacy P2PKH address (`1...` on mainnet) for a derived key
+pub fn p2pkh_address(key: &ExtendedKey, network: Network) -> Result<String> {
+    Ok(p2pkh_from_pubkey(&key.public_key()?, network))
+}
+
+/// Native segwit P2WPKH address (`bc1q...` on mainnet) for a derived key
+pub fn p2wpkh_address(key: &ExtendedKey, network: Network) -> Result<String> {
+    p2wpkh_from_pubkey(&key.public_key()?, network)
+}
+
+/// WIF private key and matching address for a derived key, as `(wif, address)`
+///
+/// `compressed` picks both the WIF flavour (trailing `0x01`) and the public
+/// key encoding hashed into the address, so importing the WIF into a wallet
+/// yields exactly this address. Only P2PKH has an uncompressed form; segwit
+/// and taproot outputs require compressed keys.
+pub fn wif_and_address(key: &ExtendedKey, script_type: ScriptType, network: Network, compressed: bool) -> Result<(String, String)> {
+    let address = address_for_encoding(&key.public_key()?, script_type, network, compressed)?;
+    Ok((encode_wif(key.private_key(), network, compressed), address))
+}
+
+/// Address for a compressed public key, hashing its uncompressed form when `compressed` is false
+pub(crate) fn address_for_encoding(
+    public_key: &[u8; 33],
+    script_type: ScriptType,
+    network: Network,
+    compressed: bool,
+) -> Result<String> {
+    match (compressed, script_type) {
+        (true, _) => script_type.address_from_pubkey(public_key, network),
+        (false, ScriptType::P2pkh) => Ok(p2pkh_from_pubkey(&ec::pubkey_uncompress(public_key)?, network)),
+        (false, _) => Err(KeyManagerError::EncodingError(format!(
+            "{} addresses require a compressed public key",
+            script_type.name()
+        ))),
+    }
+}
+
+/// WIF for a raw private key: version byte, key, and 0x01 if the public key is compressed
+pub(crate) fn encode_wif(private_key: &[u8; 32], network: Network, compressed: bool) -> String {
+    let mut payload = Vec::with_capacity(34);
+    payload.push(network.wif_version());
+    payload.extend_from_slice(private_key);
+    if compressed {
+        payload.push(0x01);
+    }
+    let wif = base58::encode_check(&payload);
+    payload.zeroize();
+    wif
+}
+
+/// P2PKH address for a serialized public key
+pub fn p2pkh_from_pubkey(public_key: &[u8], network: Network) -> String {
+    let mut payload = vec![network.p2pkh_version()];
+    payload.extend_from_slice(&hash160(public_key));
+    base58::encode_check(&payload)
+}
+
+/// P2WPKH address for a compressed public key
+pub fn p2wpkh_from_pubkey(public_key: &[u8], network: Network) -> Result<String> {
+    bech32::encode_segwit(network.bech32_hrp(), 0, &hash160(public_key))
+}
+
+/// Nested segwit address: P2SH of the `0 <hash160(pubkey)>` redeem script
+pub fn p2sh_p2wpkh_from_pubkey(public_key: &[u8], network: Network) -> String {
+    let mut redeem_script = vec![0x00, 0x14];
+    redeem_script.extend_from_slice(&hash160(public_key));
+
+    let mut payload = vec![network.p2sh_version()];
+    payload.extend_from_slice(&hash160(&redeem_script));
+    base58::encode_check(&payload)
+}
+
+/// BIP86 key-path-only taproot address for a compressed public key
+///
+/// The output key is the internal x-only key tweaked by
+/// `tagged_hash("TapTweak", internal_key)` (no script tree).
+pub fn p2tr_from_pubkey(public_key: &[u8], network: Network) -> Result<String> {
+    let internal_key = PublicKey::from_slice(public_key)
+        .map_err(|e| KeyManagerError::Secp256k1Error(e.to_string()))?
+        .x_only_public_key()
+        .0;
+
+    let (output_key, _parity) = taproot::tweak_public_key(&internal_key.serialize(), None)?;
+    bech32::encode_segwit(network.bech32_hrp(), 1, &output_key)
+}
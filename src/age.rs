@@ -0,0 +1,89 @@
+//! Deterministic age (X25519) identities derived from the BIP32 master key
+//!
+//! Backups encrypted to an `age1...` recipient can be decrypted again after
+//! restoring the wallet, since the identity comes from the mnemonic:
+//!
+//! ```text
+//! m/83696968'/657169'/{index}'   X25519 secret for age identity `index`
+//! ```
+//!
+//! 657169 spells "AGE" in ASCII decimal, following the `pgp` and `ssh`
+//! application numbers. The secret is the `bip85` entropy for that path; age
+//! clamps it when it is used, so it is stored unclamped like `age-keygen` does.
+//!
+//! Both halves use original bech32 (not bech32m) as the age spec requires:
+//! the recipient with HRP `age`, the secret key with HRP `AGE-SECRET-KEY-`
+//! in uppercase.
+
+use crate::bip85::bip85_entropy;
+use crate::error::Result;
+use crate::hd_key::ExtendedKey;
+use crate::utils::bech32::{self, Variant};
+use curve25519_dalek::MontgomeryPoint;
+use zeroize::Zeroize;
+
+/// BIP85 application number used for age identities ("AGE")
+pub const AGE_APPLICATION: u32 = 657169;
+
+/// Bech32 HRP of `age1...` recipients
+const RECIPIENT_HRP: &str = "age";
+
+/// Bech32 HRP of secret keys, written in uppercase
+const SECRET_KEY_HRP: &str = "age-secret-key-";
+
+/// Deterministic age X25519 identity
+pub struct AgeIdentity {
+    secret: [u8; 32],
+}
+
+/// Derive age identity `index` (0, 1, 2, ...) from the master key
+///
+/// The same master key and index always give the same identity and so the
+/// same recipient.
+pub fn derive_identity(master: &ExtendedKey, index: u32) -> Result<AgeIdentity> {
+    Ok(AgeIdentity { secret: bip85_entropy(master, AGE_APPLICATION, index)? })
+}
+
+impl AgeIdentity {
+    /// The raw 32-byte X25519 secret, as encoded in the secret key string
+    pub fn secret_key(&self) -> &[u8; 32] {
+        &self.secret
+    }
+
+    /// The 32-byte X25519 public key
+    pub fn public_key(&self) -> [u8; 32] {
+        MontgomeryPoint::mul_base_clamped(self.secret).to_bytes()
+    }
+
+    /// `age1...` recipient to encrypt to, e.g. with `age -r`
+    pub fn recipient(&self) -> Result<String> {
+        bech32::encode(RECIPIENT_HRP, &bech32::convert_bits(&self.public_key(), 8, 5, true)?, Variant::Bech32)
+    }
+
+    /// `AGE-SECRET-KEY-1...` string that decrypts for `recipient`
+    pub fn to_secret_key_string(&self) -> Result<String> {
+        let mut data = bech32::convert_bits(&self.secret, 8, 5, true)?;
+        let encoded = bech32::encode(SECRET_KEY_HRP, &data, Variant::Bech32);
+        data.zeroize();
+        let mut encoded = encoded?;
+        let upper = encoded.to_ascii_uppercase();
+        encoded.zeroize();
+        Ok(upper)
+    }
+
+    /// Identity file contents for `age -d -i`, laid out as `age-keygen` writes it
+    ///
+    /// The `# created:` line is left out so the file is reproducible.
+    pub fn to_identity_file(&self) -> Result<String> {
+        let mut secret_key = self.to_secret_key_string()?;
+        let file = format!("# public key: {}\n{}\n", self.recipient()?, secret_key);
+        secret_key.zeroize();
+        Ok(file)
+    }
+}
+
+impl Drop for AgeIdentity {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
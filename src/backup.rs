@@ -0,0 +1,174 @@
+//! Password-encrypted backups of a mnemonic and its passphrase
+//!
+//! The key is stretched from the password with scrypt and the payload is
+//! sealed with AES-256-GCM. Blob layout:
+//!
+//! ```text
+//! magic "CKMB" | version (1) | log_n | r | p | salt (16) | nonce (12) | ciphertext + tag
+//! ```
+//!
+//! The whole header is authenticated as associated data, so editing the
+//! scrypt parameters or salt makes decryption fail rather than silently
+//! producing a different key.
+
+use crate::entropy::{EntropySource, SystemEntropy};
+use crate::error::{KeyManagerError, Result};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use zeroize::{Zeroize, Zeroizing};
+
+/// Leading bytes of every backup blob
+pub const BACKUP_MAGIC: &[u8; 4] = b"CKMB";
+
+/// Current blob format version
+pub const BACKUP_VERSION: u8 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = BACKUP_MAGIC.len() + 4 + SALT_LEN + NONCE_LEN;
+const TAG_LEN: usize = 16;
+
+/// scrypt cost used for new backups (N = 2^15, r = 8, p = 1)
+const DEFAULT_PARAMS: KdfParams = KdfParams { log_n: 15, r: 8, p: 1 };
+
+/// Most scrypt memory (128 * r * 2^log_n bytes) a blob may ask for when decrypting
+///
+/// The default parameters need 32 MiB. Capping the product rather than
+/// `log_n` alone stops a crafted header from demanding gigabytes through `r`.
+const MAX_KDF_MEMORY: u128 = 256 * 1024 * 1024;
+
+/// Highest scrypt parallelism accepted when decrypting
+const MAX_P: u8 = 4;
+
+/// scrypt cost parameters stored in the blob header
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct KdfParams {
+    pub(crate) log_n: u8,
+    pub(crate) r: u8,
+    pub(crate) p: u8,
+}
+
+/// Encrypt `mnemonic` and `passphrase` under `password`
+///
+/// Each call uses a fresh random salt and nonce, so encrypting the same
+/// wallet twice gives different blobs.
+pub fn encrypt_backup(mnemonic: &str, passphrase: &str, password: &str) -> Result<Vec<u8>> {
+    encrypt_backup_with_params(mnemonic, passphrase, password, DEFAULT_PARAMS)
+}
+
+pub(crate) fn encrypt_backup_with_params(
+    mnemonic: &str,
+    passphrase: &str,
+    password: &str,
+    params: KdfParams,
+) -> Result<Vec<u8>> {
+    let mnemonic_len = u32::try_from(mnemonic.len())
+        .map_err(|_| KeyManagerError::EncodingError("Mnemonic too long to back up".to_string()))?;
+
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    let mut rng = SystemEntropy::new();
+    rng.fill_bytes(&mut salt)?;
+    rng.fill_bytes(&mut nonce)?;
+
+    let mut blob = Vec::with_capacity(HEADER_LEN + 4 + mnemonic.len() + passphrase.len() + TAG_LEN);
+    blob.extend_from_slice(BACKUP_MAGIC);
+    blob.extend_from_slice(&[BACKUP_VERSION, params.log_n, params.r, params.p]);
+    blob.extend_from_slice(&salt);
+    blob.extend_from_slice(&nonce);
+
+    let mut plaintext = Vec::with_capacity(4 + mnemonic.len() + passphrase.len());
+    plaintext.extend_from_slice(&mnemonic_len.to_be_bytes());
+    plaintext.extend_from_slice(mnemonic.as_bytes());
+    plaintext.extend_from_slice(passphrase.as_bytes());
+
+    let cipher = cipher(password, &salt, params)?;
+    let sealed = cipher.encrypt(Nonce::from_slice(&nonce), Payload { msg: &plaintext, aad: &blob });
+    plaintext.zeroize();
+    let ciphertext = sealed.map_err(|_| KeyManagerError::EncodingError("Backup encryption failed".to_string()))?;
+
+    blob.extend_from_slice(&ciphertext);
+    Ok(blob)
+}
+
+/// Decrypt a blob from `encrypt_backup`, returning `(mnemonic, passphrase)`
+///
+/// A wrong password and a tampered blob are indistinguishable: both fail
+/// authentication.
+pub fn decrypt_backup(blob: &[u8], password: &str) -> Result<(String, String)> {
+    if blob.len() < HEADER_LEN + TAG_LEN || &blob[..BACKUP_MAGIC.len()] != BACKUP_MAGIC {
+        return Err(KeyManagerError::EncodingError("Not an encrypted backup".to_string()));
+    }
+    let (header, ciphertext) = blob.split_at(HEADER_LEN);
+    let version = header[4];
+    if version != BACKUP_VERSION {
+        return Err(KeyManagerError::EncodingError(format!("Unsupported backup version {}", version)));
+    }
+    let params = KdfParams { log_n: header[5], r: header[6], p: header[7] };
+    check_kdf_cost(params)?;
+    let salt = &header[8..8 + SALT_LEN];
+    let nonce = &header[8 + SALT_LEN..];
+
+    let cipher = cipher(password, salt, params)?;
+    // Wiped on every return path, including a malformed payload
+    let plaintext = Zeroizing::new(
+        cipher.decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: header }).map_err(|_| {
+            KeyManagerError::EncodingError("Backup authentication failed (wrong password or corrupted data)".to_string())
+        })?,
+    );
+
+    split_plaintext(&plaintext)
+}
+
+/// Reject header parameters that would make scrypt allocate or spin far beyond a real backup
+fn check_kdf_cost(params: KdfParams) -> Result<()> {
+    let memory = 1u128
+        .checked_shl(params.log_n.into())
+        .unwrap_or(u128::MAX)
+        .saturating_mul(128 * u128::from(params.r));
+    if memory > MAX_KDF_MEMORY {
+        return Err(KeyManagerError::EncodingError(format!(
+            "Backup scrypt cost (log_n {}, r {}) exceeds the limit of {} MiB",
+            params.log_n,
+            params.r,
+            MAX_KDF_MEMORY >> 20
+        )));
+    }
+    if params.p > MAX_P {
+        return Err(KeyManagerError::EncodingError(format!(
+            "Backup scrypt parallelism {} exceeds the limit of {}",
+            params.p, MAX_P
+        )));
+    }
+    Ok(())
+}
+
+/// Split the authenticated payload back into mnemonic and passphrase
+///
+/// The caller wipes `plaintext`; only the returned strings keep a copy.
+fn split_plaintext(plaintext: &[u8]) -> Result<(String, String)> {
+    let malformed = || KeyManagerError::EncodingError("Malformed backup payload".to_string());
+
+    let (len, rest) = plaintext.split_first_chunk::<4>().ok_or_else(malformed)?;
+    let len = u32::from_be_bytes(*len) as usize;
+    if len > rest.len() {
+        return Err(malformed());
+    }
+    let (mnemonic, passphrase) = rest.split_at(len);
+    let mnemonic = std::str::from_utf8(mnemonic).map_err(|_| malformed())?;
+    let passphrase = std::str::from_utf8(passphrase).map_err(|_| malformed())?;
+    Ok((mnemonic.to_string(), passphrase.to_string()))
+}
+
+/// AES-256-GCM keyed with scrypt(password, salt)
+fn cipher(password: &str, salt: &[u8], params: KdfParams) -> Result<Aes256Gcm> {
+    let scrypt_params = scrypt::Params::new(params.log_n, params.r.into(), params.p.into(), 32)
+        .map_err(|e| KeyManagerError::EncodingError(format!("Invalid backup scrypt parameters: {}", e)))?;
+
+    let mut key = [0u8; 32];
+    scrypt::scrypt(password.as_bytes(), salt, &scrypt_params, &mut key)
+        .map_err(|e| KeyManagerError::KeyGenerationError(e.to_string()))?;
+    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|e| KeyManagerError::KeyGenerationError(e.to_string()));
+    key.zeroize();
+    cipher
+}
@@ -0,0 +1,182 @@
+//! BIP21 `bitcoin:` payment URIs
+//!
+//! `uri` builds a URI for an address with an optional amount, label and
+//! message; `parse` reads one back. Amounts are handled in satoshis and
+//! written in BTC as plain decimals with at most 8 places ("0.001", never
+//! "1e-3"). Unknown parameters are kept as-is, except `req-` ones, which
+//! BIP21 says a reader must refuse if it does not understand them.
+
+use crate::error::{KeyManagerError, Result};
+
+/// URI scheme, matched case-insensitively when parsing
+pub const SCHEME: &str = "bitcoin";
+
+/// Satoshis per bitcoin
+pub const SATS_PER_BTC: u64 = 100_000_000;
+
+/// Largest amount accepted: the 21 million BTC supply cap
+pub const MAX_AMOUNT_SATS: u64 = 21_000_000 * SATS_PER_BTC;
+
+/// Optional parameters for `uri`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Options<'a> {
+    /// Requested amount in satoshis
+    pub amount: Option<u64>,
+    /// Label for the recipient ("invoice 42")
+    pub label: Option<&'a str>,
+    /// Note shown to the payer
+    pub message: Option<&'a str>,
+}
+
+/// A parsed payment URI
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bip21 {
+    pub address: String,
+    /// Amount in satoshis
+    pub amount: Option<u64>,
+    pub label: Option<String>,
+    pub message: Option<String>,
+    /// Other optional parameters (e.g. `lightning`), exactly as they appear in the URI
+    pub extras: Vec<(String, String)>,
+}
+
+/// `bitcoin:<address>` with the given parameters, percent-encoded
+pub fn uri(address: &str, options: Options) -> String {
+    let mut params = Vec::new();
+    if let Some(amount) = options.amount {
+        params.push(format!("amount={}", format_amount(amount)));
+    }
+    if let Some(label) = options.label {
+        params.push(format!("label={}", percent_encode(label)));
+    }
+    if let Some(message) = options.message {
+        params.push(format!("message={}", percent_encode(message)));
+    }
+
+    let mut uri = format!("{}:{}", SCHEME, address);
+    if !params.is_empty() {
+        uri.push('?');
+        uri.push_str(&params.join("&"));
+    }
+    uri
+}
+
+/// Parse a `bitcoin:` URI
+///
+/// The address is returned as written; check it with the address functions
+/// for the expected network before paying to it.
+pub fn parse(uri: &str) -> Result<Bip21> {
+    let invalid = |reason: String| KeyManagerError::EncodingError(format!("Invalid BIP21 URI: {}", reason));
+
+    let rest = uri
+        .split_once(':')
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case(SCHEME))
+        .map(|(_, rest)| rest)
+        .ok_or_else(|| invalid(format!("expected the '{}:' scheme", SCHEME)))?;
+    let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+    if address.is_empty() {
+        return Err(invalid("missing address".to_string()));
+    }
+
+    let mut parsed = Bip21 { address: address.to_string(), ..Bip21::default() };
+    for param in query.split('&').filter(|param| !param.is_empty()) {
+        let (key, value) = param.split_once('=').unwrap_or((param, ""));
+        let duplicate = || invalid(format!("duplicate '{}' parameter", key));
+        match key {
+            "amount" => {
+                if parsed.amount.replace(parse_amount(value)?).is_some() {
+                    return Err(duplicate());
+                }
+            }
+            "label" => {
+                if parsed.label.replace(percent_decode(value)?).is_some() {
+                    return Err(duplicate());
+                }
+            }
+            "message" => {
+                if parsed.message.replace(percent_decode(value)?).is_some() {
+                    return Err(duplicate());
+                }
+            }
+            _ if key.starts_with("req-") => {
+                return Err(invalid(format!("unsupported required parameter '{}'", key)));
+            }
+            _ => parsed.extras.push((key.to_string(), value.to_string())),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Satoshis as a BTC decimal without trailing zeros ("0.001", "50")
+pub fn format_amount(sats: u64) -> String {
+    let whole = sats / SATS_PER_BTC;
+    let fraction = sats % SATS_PER_BTC;
+    if fraction == 0 {
+        return whole.to_string();
+    }
+    let fraction = format!("{:08}", fraction);
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+/// A BTC decimal ("0.001", "20.3", ".5") as satoshis
+///
+/// Exponents, signs, more than 8 decimal places and amounts above the
+/// supply cap are rejected.
+pub fn parse_amount(amount: &str) -> Result<u64> {
+    let invalid = |reason: &str| KeyManagerError::EncodingError(format!("Invalid amount '{}': {}", amount, reason));
+
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if whole.is_empty() && fraction.is_empty() {
+        return Err(invalid("expected a decimal BTC amount"));
+    }
+    if !whole.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit()) {
+        return Err(invalid("expected a decimal BTC amount"));
+    }
+    if fraction.len() > 8 {
+        return Err(invalid("more than 8 decimal places"));
+    }
+
+    let whole: u64 = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| invalid("too large"))? };
+    let fraction: u64 = format!("{:0<8}", fraction).parse().map_err(|_| invalid("expected a decimal BTC amount"))?;
+    whole
+        .checked_mul(SATS_PER_BTC)
+        .and_then(|sats| sats.checked_add(fraction))
+        .filter(|&sats| sats <= MAX_AMOUNT_SATS)
+        .ok_or_else(|| invalid("exceeds 21 million BTC"))
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Decode `%XX` escapes; the result must be UTF-8
+fn percent_decode(value: &str) -> Result<String> {
+    let invalid = || KeyManagerError::EncodingError(format!("Invalid percent-encoding in '{}'", value));
+
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value
+                .get(i + 1..i + 3)
+                .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                .ok_or_else(invalid)?;
+            decoded.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| invalid())
+}
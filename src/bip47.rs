@@ -0,0 +1,74 @@
+//! BIP47 reusable payment codes (version 1)
+//!
+//! A payment code publishes the public key and chain code of the wallet's
+//! `m/47'/0'/0'` account so senders can derive fresh addresses for each
+//! payment. Only code generation and the notification address are
+//! implemented; the ECDH notification transaction is not.
+
+use crate::address::p2pkh_from_pubkey;
+use crate::error::{KeyManagerError, Result};
+use crate::hd_key::{ExtendedKey, HARDENED_OFFSET};
+use crate::network::Network;
+use crate::path::DerivationPath;
+use crate::utils::base58;
+use crate::xpub::{ExtendedPublicKey, SlipVersion};
+
+/// Base58Check version byte, which makes every code start with "PM8T"
+pub const PAYMENT_CODE_PREFIX: u8 = 0x47;
+
+/// BIP43 purpose of the payment code account
+pub const BIP47_PURPOSE: u32 = 47;
+
+/// Payload length: version, features, 33-byte key, chain code, 13 reserved bytes
+const PAYLOAD_LEN: usize = 80;
+
+/// Version-1 payment code for the wallet's first Bitcoin account
+///
+/// Derives `m/47'/0'/0'` from `master` and Base58Check-encodes its public key
+/// and chain code.
+pub fn payment_code(master: &ExtendedKey) -> Result<String> {
+    let path = DerivationPath::from_indices(vec![
+        BIP47_PURPOSE + HARDENED_OFFSET,
+        HARDENED_OFFSET,
+        HARDENED_OFFSET,
+    ]);
+    let account = master.derive(&path)?;
+
+    let mut data = [0u8; 1 + PAYLOAD_LEN];
+    data[0] = PAYMENT_CODE_PREFIX;
+    data[1] = 0x01; // version
+    data[2] = 0x00; // features: no bitmessage notification
+    data[3..36].copy_from_slice(&account.public_key()?);
+    data[36..68].copy_from_slice(account.chain_code());
+    Ok(base58::encode_check(&data))
+}
+
+/// P2PKH address senders notify before their first payment to `code`
+///
+/// This is the code's own key derived at child 0, on mainnet.
+pub fn notification_address(code: &str) -> Result<String> {
+    let notification_key = parse_payment_code(code)?.derive_child(0)?;
+    Ok(p2pkh_from_pubkey(notification_key.public_key(), Network::Mainnet))
+}
+
+/// Decode a version-1 payment code into its public key and chain code
+fn parse_payment_code(code: &str) -> Result<ExtendedPublicKey> {
+    let data = base58::decode_check(code.trim())?;
+    if data.len() != 1 + PAYLOAD_LEN || data[0] != PAYMENT_CODE_PREFIX {
+        return Err(KeyManagerError::EncodingError(
+            "Not a BIP47 payment code".to_string(),
+        ));
+    }
+    if data[1] != 0x01 {
+        return Err(KeyManagerError::EncodingError(format!(
+            "Unsupported payment code version {}",
+            data[1]
+        )));
+    }
+
+    let mut public_key = [0u8; 33];
+    let mut chain_code = [0u8; 32];
+    public_key.copy_from_slice(&data[3..36]);
+    chain_code.copy_from_slice(&data[36..68]);
+    ExtendedPublicKey::from_parts(public_key, chain_code, SlipVersion::Xpub)
+}
@@ -0,0 +1,50 @@
+//! BIP85-style deterministic entropy from the BIP32 master key
+//!
+//! Each application gets its own hardened branch below the "SEED" purpose:
+//!
+//! ```text
+//! m/83696968'/{application}'/{index}'
+//! ```
+//!
+//! The entropy is the first 32 bytes of HMAC-SHA512("bip-entropy-from-k", k)
+//! for the private key k at that path. The `pgp`, `ssh` and `age` modules
+//! all seed their keys this way, each with its own application number.
+
+use crate::error::{KeyManagerError, Result};
+use crate::hd_key::{ExtendedKey, HARDENED_OFFSET};
+use crate::path::DerivationPath;
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use zeroize::Zeroize;
+
+/// BIP85 root purpose ("SEED")
+pub const BIP85_PURPOSE: u32 = 83696968;
+
+/// 32 bytes of entropy for key `index` of `application`
+///
+/// Both numbers are used as hardened indices, so they must be below 2^31.
+pub fn bip85_entropy(master: &ExtendedKey, application: u32, index: u32) -> Result<[u8; 32]> {
+    if application >= HARDENED_OFFSET || index >= HARDENED_OFFSET {
+        return Err(KeyManagerError::InvalidDerivationPath(format!(
+            "BIP85 application and index must be below 2^31, got {}'/{}'",
+            application, index
+        )));
+    }
+
+    let path = DerivationPath::from_indices(vec![
+        BIP85_PURPOSE + HARDENED_OFFSET,
+        application + HARDENED_OFFSET,
+        index + HARDENED_OFFSET,
+    ]);
+    let derived = master.derive(&path)?;
+
+    let mut mac = Hmac::<Sha512>::new_from_slice(b"bip-entropy-from-k")
+        .map_err(|_| KeyManagerError::HmacError("HMAC init failed".to_string()))?;
+    mac.update(derived.private_key());
+    let mut output = mac.finalize().into_bytes();
+
+    let mut entropy = [0u8; 32];
+    entropy.copy_from_slice(&output[..32]);
+    output.zeroize();
+    Ok(entropy)
+}
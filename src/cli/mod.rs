@@ -0,0 +1,280 @@
+//! Helpers shared by the binary's commands (argument handling, output tables)
+
+pub mod progress;
+pub mod table;
+pub mod wallet;
+
+#[cfg(test)]
+mod tests;
+
+use crypto_key_manager::address::ScriptType;
+use crypto_key_manager::entropy::{EntropySource, MixedEntropy, SystemEntropy};
+use crypto_key_manager::versions::{self, Coin, KeyVersion, Visibility};
+use crypto_key_manager::{KeyManagerError, Network, Result};
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Command-line arguments as UTF-8 strings
+///
+/// `env::args()` panics on an argument that is not valid UTF-8, which some
+/// shells and locales can produce. The offending argument is reported by
+/// position only, since it may be a mnemonic. The program name is converted
+/// lossily; nothing reads it.
+pub fn utf8_args(args: impl IntoIterator<Item = OsString>) -> Result<Vec<String>> {
+    args.into_iter()
+        .enumerate()
+        .map(|(position, arg)| match arg.into_string() {
+            Ok(arg) => Ok(arg),
+            Err(arg) if position == 0 => Ok(arg.to_string_lossy().into_owned()),
+            Err(_) => Err(KeyManagerError::EncodingError(format!(
+                "Argument {} is not valid UTF-8; mnemonics, paths and options must be UTF-8 text",
+                position
+            ))),
+        })
+        .collect()
+}
+
+/// Remove `<name> <value>` from the argument list, returning the value
+///
+/// Options may appear anywhere after the command name. Returns an error if
+/// the option is present without a value.
+pub fn take_option(args: &mut Vec<String>, name: &str) -> std::result::Result<Option<String>, String> {
+    let Some(position) = args.iter().position(|arg| arg == name) else {
+        return Ok(None);
+    };
+    if position + 1 >= args.len() {
+        return Err(format!("{} requires a value", name));
+    }
+
+    let value = args.remove(position + 1);
+    args.remove(position);
+    Ok(Some(value))
+}
+
+/// Parse an inclusive index range like "0-19" (or a single index "5")
+pub fn parse_range(range: &str) -> std::result::Result<(u32, u32), String> {
+    let (start, end) = match range.split_once('-') {
+        Some((start, end)) => (start.trim(), end.trim()),
+        None => (range.trim(), range.trim()),
+    };
+
+    let start: u32 = start.parse().map_err(|_| format!("Invalid range start: {}", start))?;
+    let end: u32 = end.parse().map_err(|_| format!("Invalid range end: {}", end))?;
+    if start > end {
+        return Err(format!("Range start {} is after end {}", start, end));
+    }
+    if end >= 0x80000000 {
+        return Err(format!("Range end {} exceeds the non-hardened index limit", end));
+    }
+    Ok((start, end))
+}
+
+/// Parse a `--network` value
+pub fn parse_network(name: &str) -> std::result::Result<Network, String> {
+    match name.to_ascii_lowercase().as_str() {
+        "mainnet" | "main" | "bitcoin" => Ok(Network::Mainnet),
+        "testnet" | "test" => Ok(Network::Testnet),
+        "regtest" => Ok(Network::Regtest),
+        _ => Err(format!("Unknown network '{}' (expected mainnet, testnet or regtest)", name)),
+    }
+}
+
+/// Parse a `--coin` value
+pub fn parse_coin(name: &str) -> std::result::Result<Coin, String> {
+    match name.to_ascii_lowercase().as_str() {
+        "btc" | "bitcoin" => Ok(Coin::Bitcoin),
+        "ltc" | "litecoin" => Ok(Coin::Litecoin),
+        "doge" | "dogecoin" => Ok(Coin::Dogecoin),
+        _ => Err(format!("Unknown coin '{}' (expected btc, ltc or doge)", name)),
+    }
+}
+
+/// Extended key prefix for `coin` matching the script type of `path`
+///
+/// Falls back to the coin's P2PKH prefix when it has none for the script
+/// type (Dogecoin has no segwit prefixes).
+pub fn key_version_for_path(
+    coin: Coin,
+    path: &str,
+    network: Network,
+    visibility: Visibility,
+) -> std::result::Result<&'static KeyVersion, String> {
+    versions::lookup(coin, script_type_for_path(path), network, visibility)
+        .or_else(|| versions::lookup(coin, ScriptType::P2pkh, network, visibility))
+        .ok_or_else(|| format!("No extended key prefix registered for {} {:?}", coin.name(), network))
+}
+
+/// Entropy source for `--entropy-device <path>` and `--entropy-mix system,device`
+///
+/// Without `--entropy-mix` the device (if given) replaces the OS generator.
+/// A mix naming `device` requires `--entropy-device`.
+pub fn entropy_source(device: Option<&str>, mix: Option<&str>) -> std::result::Result<Box<dyn EntropySource>, String> {
+    let Some(mix) = mix else {
+        return Ok(Box::new(device.map_or_else(SystemEntropy::new, SystemEntropy::with_device)));
+    };
+
+    let sources = mix
+        .split(',')
+        .map(|name| match name.trim().to_ascii_lowercase().as_str() {
+            "system" => Ok(Box::new(SystemEntropy::new()) as Box<dyn EntropySource>),
+            "device" => device
+                .map(|path| Box::new(SystemEntropy::with_device(path)) as Box<dyn EntropySource>)
+                .ok_or_else(|| "--entropy-mix device requires --entropy-device <path>".to_string()),
+            other => Err(format!("Unknown entropy source '{}' (expected system or device)", other)),
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(Box::new(MixedEntropy(sources)))
+}
+
+/// Parse a `--type` value naming an address script type
+pub fn parse_script_type(name: &str) -> std::result::Result<ScriptType, String> {
+    match name.to_ascii_lowercase().as_str() {
+        "p2pkh" | "legacy" => Ok(ScriptType::P2pkh),
+        "p2sh-p2wpkh" | "nested" => Ok(ScriptType::P2shP2wpkh),
+        "p2wpkh" | "segwit" => Ok(ScriptType::P2wpkh),
+        "p2tr" | "taproot" => Ok(ScriptType::P2tr),
+        _ => Err(format!("Unknown address type '{}' (expected p2pkh, p2sh-p2wpkh, p2wpkh or p2tr)", name)),
+    }
+}
+
+/// Script type implied by a path's purpose (`m/84'/...` is P2WPKH), defaulting to P2PKH
+pub fn script_type_for_path(path: &str) -> ScriptType {
+    path.split('/')
+        .nth(1)
+        .and_then(|component| component.trim_end_matches(['\'', 'h']).parse().ok())
+        .and_then(ScriptType::from_purpose)
+        .unwrap_or(ScriptType::P2pkh)
+}
+
+/// Refuse to write secrets (mnemonics, private keys) to a non-terminal stdout
+///
+/// Redirecting secrets into files, pipes or terminal logs is a common way
+/// to leak them. Scripts that really want this pass `--allow-non-tty`.
+pub fn guard_secret_output(allow_non_tty: bool) -> std::result::Result<(), String> {
+    if allow_non_tty || std::io::stdout().is_terminal() {
+        Ok(())
+    } else {
+        Err("Refusing to print secrets to a non-terminal stdout; pass --allow-non-tty to override".to_string())
+    }
+}
+
+/// Remove a boolean `--flag` from the argument list, returning whether it was present
+pub fn take_flag(args: &mut Vec<String>, name: &str) -> bool {
+    match args.iter().position(|arg| arg == name) {
+        Some(position) => {
+            args.remove(position);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Numbered word grid for writing a mnemonic onto a backup sheet
+///
+/// Words are numbered down each column (1-6 in the first column for a
+/// 24-word phrase) the way printed backup cards are laid out.
+pub fn backup_sheet(mnemonic: &str) -> String {
+    const COLUMNS: usize = 4;
+
+    let words: Vec<&str> = mnemonic.split_whitespace().collect();
+    let rows = words.len().div_ceil(COLUMNS);
+    let mut sheet = String::new();
+    for row in 0..rows {
+        let line: Vec<String> = (0..COLUMNS)
+            .filter_map(|column| {
+                let index = column * rows + row;
+                words.get(index).map(|word| format!("{:>2}. {:<10}", index + 1, word))
+            })
+            .collect();
+        sheet.push_str("  ");
+        sheet.push_str(line.join(" ").trim_end());
+        sheet.push('\n');
+    }
+    sheet
+}
+
+/// Who may read a file written by `safe_write`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sensitivity {
+    /// Seeds, private keys, backups: owner read/write only (0600 on Unix)
+    Secret,
+    /// Watch-only data and signed transactions: the umask decides
+    Public,
+}
+
+/// Write `bytes` to `path` atomically, refusing to replace an existing file unless `overwrite`
+///
+/// The data goes to a temporary file in the same directory, is fsynced,
+/// then renamed into place, so readers never see a half-written file and a
+/// failure leaves the target untouched. Secret files are created 0600 on
+/// Unix; elsewhere they get the platform's default permissions, since
+/// restricting an ACL needs OS APIs this crate does not depend on.
+pub fn safe_write(path: &Path, bytes: &[u8], sensitivity: Sensitivity, overwrite: bool) -> std::result::Result<(), String> {
+    write_atomically(path, sensitivity, overwrite, |file| file.write_all(bytes))
+}
+
+fn write_atomically(
+    path: &Path,
+    sensitivity: Sensitivity,
+    overwrite: bool,
+    write: impl FnOnce(&mut File) -> io::Result<()>,
+) -> std::result::Result<(), String> {
+    let exists = || format!("{} already exists; pass --force to overwrite it", path.display());
+    if !overwrite && fs::symlink_metadata(path).is_ok() {
+        return Err(exists());
+    }
+
+    let temp = temp_path(path);
+    let written = create_temp(&temp, sensitivity).and_then(|mut file| {
+        write(&mut file)?;
+        file.sync_all()
+    });
+    let placed = written.and_then(|()| {
+        if overwrite {
+            fs::rename(&temp, path)
+        } else {
+            // A hard link fails if the target appeared since the check above
+            fs::hard_link(&temp, path).and_then(|()| fs::remove_file(&temp))
+        }
+    });
+    if let Err(e) = placed {
+        let _ = fs::remove_file(&temp);
+        return Err(match e.kind() {
+            io::ErrorKind::AlreadyExists => exists(),
+            _ => format!("Cannot write {}: {}", path.display(), e),
+        });
+    }
+
+    // Persist the rename itself; not every platform can open a directory
+    if let Ok(dir) = File::open(temp.parent().unwrap_or(Path::new("."))) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Unused hidden file next to `path`, so the final rename stays on one filesystem
+fn temp_path(path: &Path) -> PathBuf {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let temp = format!(".{}.{}-{}.tmp", name, std::process::id(), COUNTER.fetch_add(1, Ordering::Relaxed));
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.join(temp),
+        _ => PathBuf::from(temp),
+    }
+}
+
+fn create_temp(path: &Path, sensitivity: Sensitivity) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if sensitivity == Sensitivity::Secret {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = sensitivity;
+    options.open(path)
+}
@@ -0,0 +1,57 @@
+//! Progress line on stderr, with Ctrl-C cancelling the running search
+
+use crypto_key_manager::progress::Progress;
+use std::cell::Cell;
+use std::io::{self, IsTerminal, Write};
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Units of work between redraws of the progress line
+const REPORT_EVERY: u64 = 500;
+
+/// Set by the Ctrl-C handler, polled on every report
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Reports `<done>/<total> <unit>` on stderr and breaks once Ctrl-C is pressed
+///
+/// The line is only drawn when stderr is a terminal, so redirected output
+/// stays clean; cancellation works either way. Dropping it ends the line.
+pub struct StderrProgress {
+    unit: &'static str,
+    drawn: Cell<bool>,
+}
+
+impl StderrProgress {
+    /// Install the Ctrl-C handler and start reporting `unit` (e.g. "addresses")
+    pub fn new(unit: &'static str) -> Result<Self, String> {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::SeqCst))
+            .map_err(|e| format!("Cannot install Ctrl-C handler: {}", e))?;
+        Ok(StderrProgress { unit, drawn: Cell::new(false) })
+    }
+}
+
+impl Progress for StderrProgress {
+    fn report(&self, done: u64, total: Option<u64>) -> ControlFlow<()> {
+        if INTERRUPTED.load(Ordering::SeqCst) {
+            return ControlFlow::Break(());
+        }
+        if (done.is_multiple_of(REPORT_EVERY) || Some(done) == total) && io::stderr().is_terminal() {
+            let line = match total {
+                Some(total) => format!("\r{}/{} {} checked", done, total, self.unit),
+                None => format!("\r{} {} checked", done, self.unit),
+            };
+            let _ = io::stderr().write_all(line.as_bytes());
+            self.drawn.set(true);
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl Drop for StderrProgress {
+    fn drop(&mut self) {
+        if self.drawn.get() {
+            eprintln!();
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Tabular output shared by batch commands (text, CSV, TSV, JSON)
+
+use std::str::FromStr;
+
+pub use crypto_key_manager::utils::json_string;
+
+/// Output format for batch results
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Csv,
+    Tsv,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(Format::Text),
+            "csv" => Ok(Format::Csv),
+            "tsv" => Ok(Format::Tsv),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("Unknown format '{}' (expected text, csv, tsv or json)", s)),
+        }
+    }
+}
+
+/// Rows of string cells under named columns
+pub struct Table {
+    columns: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(columns: &[&'static str]) -> Self {
+        Table {
+            columns: columns.to_vec(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Append a row; must have one cell per column
+    pub fn push_row(&mut self, row: Vec<String>) {
+        assert_eq!(row.len(), self.columns.len(), "row width must match column count");
+        self.rows.push(row);
+    }
+
+    /// Keep only the comma-separated `selection` of columns, in that order
+    pub fn select_columns(&mut self, selection: &str) -> Result<(), String> {
+        let indices = selection
+            .split(',')
+            .map(|name| {
+                let name = name.trim();
+                self.columns
+                    .iter()
+                    .position(|column| *column == name)
+                    .ok_or_else(|| format!(
+                        "Unknown column '{}' (available: {})",
+                        name,
+                        self.columns.join(",")
+                    ))
+            })
+            .collect::<Result<Vec<usize>, String>>()?;
+
+        self.columns = indices.iter().map(|&i| self.columns[i]).collect();
+        self.rows = self
+            .rows
+            .iter()
+            .map(|row| indices.iter().map(|&i| row[i].clone()).collect())
+            .collect();
+        Ok(())
+    }
+
+    /// Render the table in the requested format (always newline-terminated)
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Text => self.render_delimited("  ", |cell| cell.to_string()),
+            Format::Csv => self.render_delimited(",", csv_escape),
+            Format::Tsv => self.render_delimited("\t", tsv_escape),
+            Format::Json => self.render_json(),
+        }
+    }
+
+    fn render_delimited(&self, separator: &str, escape: impl Fn(&str) -> String) -> String {
+        let mut output = String::new();
+        let header: Vec<String> = self.columns.iter().map(|c| escape(c)).collect();
+        output.push_str(&header.join(separator));
+        output.push('\n');
+        for row in &self.rows {
+            let cells: Vec<String> = row.iter().map(|c| escape(c)).collect();
+            output.push_str(&cells.join(separator));
+            output.push('\n');
+        }
+        output
+    }
+
+    fn render_json(&self) -> String {
+        let objects: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let fields: Vec<String> = self
+                    .columns
+                    .iter()
+                    .zip(row)
+                    .map(|(column, cell)| format!("{}:{}", json_string(column), json_string(cell)))
+                    .collect();
+                format!("{{{}}}", fields.join(","))
+            })
+            .collect();
+        format!("[{}]\n", objects.join(","))
+    }
+}
+
+/// Quote a CSV field (RFC 4180) when it contains a delimiter, quote or newline
+fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+/// TSV has no quoting; escape the characters that would break the layout
+fn tsv_escape(cell: &str) -> String {
+    cell.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
@@ -0,0 +1,84 @@
+// Unit tests for the CLI helpers
+
+use super::*;
+
+/// Fresh path in the temp directory, removed when dropped
+struct TempPath(PathBuf);
+
+impl TempPath {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("ckm-{}-{}", std::process::id(), name));
+        let _ = fs::remove_file(&path);
+        TempPath(path)
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Files in the temp directory left behind by writes to `target`
+fn leftover_temp_files(target: &Path) -> Vec<String> {
+    let prefix = format!(".{}.", target.file_name().unwrap().to_string_lossy());
+    fs::read_dir(target.parent().unwrap())
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(&prefix))
+        .collect()
+}
+
+// ============================================================================
+// safe_write
+// ============================================================================
+
+#[cfg(unix)]
+#[test]
+fn test_safe_write_secret_files_are_owner_only() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let secret = TempPath::new("secret.txt");
+    safe_write(&secret.0, b"seed\n", Sensitivity::Secret, false).unwrap();
+    assert_eq!(fs::read(&secret.0).unwrap(), b"seed\n");
+    assert_eq!(fs::metadata(&secret.0).unwrap().permissions().mode() & 0o777, 0o600);
+
+    // Overwriting keeps the restriction, since the new file replaces the old one
+    safe_write(&secret.0, b"other\n", Sensitivity::Secret, true).unwrap();
+    assert_eq!(fs::metadata(&secret.0).unwrap().permissions().mode() & 0o777, 0o600);
+}
+
+#[test]
+fn test_safe_write_refuses_to_overwrite_without_force() {
+    let target = TempPath::new("existing.txt");
+    fs::write(&target.0, b"keep me").unwrap();
+
+    let error = safe_write(&target.0, b"new", Sensitivity::Public, false).unwrap_err();
+    assert!(error.contains("already exists") && error.contains("--force"), "{}", error);
+    assert_eq!(fs::read(&target.0).unwrap(), b"keep me");
+    assert!(leftover_temp_files(&target.0).is_empty());
+
+    safe_write(&target.0, b"new", Sensitivity::Public, true).unwrap();
+    assert_eq!(fs::read(&target.0).unwrap(), b"new");
+}
+
+#[test]
+fn test_safe_write_failure_leaves_no_partial_file() {
+    let target = TempPath::new("partial.txt");
+    let failing = |file: &mut File| {
+        file.write_all(b"half of the")?;
+        Err(io::Error::other("disk full"))
+    };
+
+    let error = write_atomically(&target.0, Sensitivity::Secret, false, failing).unwrap_err();
+    assert!(error.contains("disk full"), "{}", error);
+    assert!(fs::symlink_metadata(&target.0).is_err());
+    assert!(leftover_temp_files(&target.0).is_empty());
+
+    // An existing file is left as it was
+    fs::write(&target.0, b"original").unwrap();
+    assert!(write_atomically(&target.0, Sensitivity::Secret, true, failing).is_err());
+    assert_eq!(fs::read(&target.0).unwrap(), b"original");
+    assert!(leftover_temp_files(&target.0).is_empty());
+}
@@ -0,0 +1,113 @@
+//! `wallet-new`: a fresh mnemonic plus everything needed to set up a watch-only wallet
+
+use super::backup_sheet;
+use super::table::json_string;
+use crypto_key_manager::address::{self, ScriptType};
+use crypto_key_manager::{seed, utils, ExtendedPublicKey, Network, Result, SecretPassphrase};
+
+/// Receive addresses listed per account
+const RECEIVE_ADDRESSES: u32 = 3;
+
+/// Account 0 of one script type
+pub struct AccountSummary {
+    pub script_type: ScriptType,
+    pub path: String,
+    pub xpub: String,
+    pub addresses: Vec<String>,
+}
+
+/// Mnemonic, fingerprint and the standard account 0 for each script type
+pub struct WalletSummary {
+    pub mnemonic: String,
+    pub network: Network,
+    pub fingerprint: [u8; 4],
+    pub accounts: Vec<AccountSummary>,
+}
+
+/// Derive the account-level xpubs and first receive addresses for a mnemonic
+pub fn summarize(mnemonic: &str, passphrase: &SecretPassphrase, network: Network) -> Result<WalletSummary> {
+    let master = seed::generate_master_key_from_mnemonic(mnemonic, passphrase)?;
+    let coin_type = network.coin_type();
+
+    let accounts = ScriptType::ALL
+        .into_iter()
+        .map(|script_type| {
+            let path = format!("m/{}'/{}'/0'", script_type.purpose(), coin_type);
+            let account = master.derive_path(&path)?;
+            let xpub = ExtendedPublicKey::from_private(&account, script_type.slip_version(network))?;
+            let addresses = (0..RECEIVE_ADDRESSES)
+                .map(|index| {
+                    let key = account.derive_path(&format!("m/0/{}", index))?;
+                    address::address(&key, script_type, network)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            Ok(AccountSummary {
+                script_type,
+                path,
+                xpub: xpub.to_xpub_string()?,
+                addresses,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(WalletSummary {
+        mnemonic: mnemonic.to_string(),
+        network,
+        fingerprint: master.fingerprint()?,
+        accounts,
+    })
+}
+
+impl WalletSummary {
+    /// Human-readable provisioning sheet
+    pub fn render_text(&self) -> String {
+        let word_count = self.mnemonic.split_whitespace().count();
+        let mut out = format!("\nNew {}-word mnemonic ({}):\n\n", word_count, self.network.name());
+        out.push_str(&backup_sheet(&self.mnemonic));
+        out.push_str("\n⚠️  Write these words down in order and store them offline.\n");
+        out.push_str(&format!("\nMaster fingerprint: {}\n", utils::bytes_to_hex(&self.fingerprint)));
+
+        for account in &self.accounts {
+            out.push_str(&format!(
+                "\nBIP{} {} [{}/{}]\n  {}\n",
+                account.script_type.purpose(),
+                account.script_type.name(),
+                utils::bytes_to_hex(&self.fingerprint),
+                account.path.trim_start_matches("m/"),
+                account.xpub
+            ));
+            for (index, address) in account.addresses.iter().enumerate() {
+                out.push_str(&format!("  {}/0/{}  {}\n", account.path, index, address));
+            }
+        }
+        out
+    }
+
+    /// Structured output for `--json`
+    pub fn render_json(&self) -> String {
+        let accounts: Vec<String> = self
+            .accounts
+            .iter()
+            .map(|account| {
+                let addresses: Vec<String> = account.addresses.iter().map(|a| json_string(a)).collect();
+                format!(
+                    "{{\"purpose\":{},\"script_type\":{},\"path\":{},\"xpub\":{},\"receive_addresses\":[{}]}}",
+                    account.script_type.purpose(),
+                    json_string(account.script_type.name()),
+                    json_string(&account.path),
+                    json_string(&account.xpub),
+                    addresses.join(",")
+                )
+            })
+            .collect();
+
+        format!(
+            "{{\"network\":{},\"mnemonic\":{},\"fingerprint\":{},\"accounts\":[{}]}}\n",
+            json_string(self.network.name()),
+            json_string(&self.mnemonic),
+            json_string(&utils::bytes_to_hex(&self.fingerprint)),
+            accounts.join(",")
+        )
+    }
+}
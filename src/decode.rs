@@ -0,0 +1,171 @@
+use crate::error::{KeyManagerError, Result};
+use crate::utils::{self, base58, bech32};
+use crate::versions;
+
+/// Encoding detected by `decode_any`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Base58Check,
+    Bech32,
+    Bech32m,
+    Hex,
+}
+
+/// Result of decoding an unknown blob: raw bytes plus structural hints
+#[derive(Clone, Debug)]
+pub struct Decoded {
+    /// Detected encoding
+    pub encoding: Encoding,
+    /// Decoded payload (checksum stripped; witness program for segwit addresses)
+    pub bytes: Vec<u8>,
+    /// Checksum validity, or `None` for encodings without a checksum (hex)
+    pub checksum_valid: Option<bool>,
+    /// Human-readable notes about the structure (version byte, HRP, ...)
+    pub hints: Vec<String>,
+}
+
+/// Decode a Base58Check, bech32/bech32m or hex string and describe its contents
+///
+/// A bad checksum is reported via `checksum_valid` rather than as an error, so
+/// corrupted strings can still be inspected. Only input that fits none of the
+/// encodings returns an error.
+pub fn decode_any(input: &str) -> Result<Decoded> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(KeyManagerError::EncodingError("Empty input".to_string()));
+    }
+
+    // Hex that happens to contain a `1` also splits as bech32; without a
+    // valid checksum it is far more likely to be hex
+    let is_hex = utils::validate_hex_string(input).is_ok();
+    if let Ok((hrp, data)) = bech32::split(input) {
+        if is_hex && bech32::verify_checksum(&hrp, &data).is_none() {
+            return Ok(describe_hex(utils::hex_to_bytes(input)?));
+        }
+        let mut decoded = describe_bech32(&hrp, data);
+        if let Some(typo) = bech32::locate_typo(input) {
+            decoded.hints.push(typo.to_string());
+        }
+        return Ok(decoded);
+    }
+
+    if is_hex {
+        return Ok(describe_hex(utils::hex_to_bytes(input)?));
+    }
+
+    if let Ok(raw) = base58::decode(input) {
+        if raw.len() > 4 {
+            return Ok(describe_base58(raw));
+        }
+    }
+
+    Err(KeyManagerError::EncodingError(
+        "Input is not valid Base58Check, bech32 or hex".to_string(),
+    ))
+}
+
+fn describe_hex(bytes: Vec<u8>) -> Decoded {
+    let mut hints = vec![format!("{} bytes", bytes.len())];
+    match (bytes.len(), bytes.first()) {
+        (33, Some(0x02 | 0x03)) => hints.push("Looks like a compressed secp256k1 public key".to_string()),
+        (65, Some(0x04)) => hints.push("Looks like an uncompressed secp256k1 public key".to_string()),
+        (32, _) => hints.push("32 bytes: private key, chain code or hash".to_string()),
+        (20, _) => hints.push("20 bytes: likely a Hash160 (address payload)".to_string()),
+        (64, _) => hints.push("64 bytes: possibly a BIP39 seed or compact signature".to_string()),
+        _ => {}
+    }
+
+    Decoded {
+        encoding: Encoding::Hex,
+        bytes,
+        checksum_valid: None,
+        hints,
+    }
+}
+
+fn describe_base58(raw: Vec<u8>) -> Decoded {
+    let payload_len = raw.len() - 4;
+    let checksum_valid = base58::checksum(&raw[..payload_len]) == raw[payload_len..];
+    let payload = raw[..payload_len].to_vec();
+
+    let mut hints = vec![format!("{}-byte payload", payload.len())];
+    if let Some(kind) = base58_kind(&payload) {
+        hints.push(kind);
+    } else {
+        hints.push(format!("Unknown version byte 0x{:02x}", payload[0]));
+    }
+
+    Decoded {
+        encoding: Encoding::Base58Check,
+        bytes: payload,
+        checksum_valid: Some(checksum_valid),
+        hints,
+    }
+}
+
+/// Name the structure behind a Base58Check payload from its version prefix
+fn base58_kind(payload: &[u8]) -> Option<String> {
+    match (payload.len(), payload[0]) {
+        (21, 0x00) => return Some("Version 0x00: P2PKH address (mainnet)".to_string()),
+        (21, 0x05) => return Some("Version 0x05: P2SH address (mainnet)".to_string()),
+        (21, 0x6f) => return Some("Version 0x6f: P2PKH address (testnet)".to_string()),
+        (21, 0xc4) => return Some("Version 0xc4: P2SH address (testnet)".to_string()),
+        (33, 0x80) => return Some("Version 0x80: WIF private key (mainnet, uncompressed)".to_string()),
+        (34, 0x80) if payload[33] == 0x01 => {
+            return Some("Version 0x80: WIF private key (mainnet, compressed)".to_string())
+        }
+        (33, 0xef) => return Some("Version 0xef: WIF private key (testnet, uncompressed)".to_string()),
+        (34, 0xef) if payload[33] == 0x01 => {
+            return Some("Version 0xef: WIF private key (testnet, compressed)".to_string())
+        }
+        _ => {}
+    }
+
+    if payload.len() == 78 {
+        let version = [payload[0], payload[1], payload[2], payload[3]];
+        let name = versions::by_bytes(version).map_or("unknown", |version| version.name);
+        return Some(format!(
+            "Extended key ({}): version {}, depth {}, child index {}",
+            name,
+            utils::bytes_to_hex(&payload[..4]),
+            payload[4],
+            u32::from_be_bytes([payload[9], payload[10], payload[11], payload[12]])
+        ));
+    }
+
+    None
+}
+
+fn describe_bech32(hrp: &str, data: Vec<u8>) -> Decoded {
+    let variant = bech32::verify_checksum(hrp, &data);
+    let values = &data[..data.len() - 6];
+
+    let mut hints = vec![format!("HRP: {}", hrp)];
+    let is_segwit_hrp = matches!(hrp, "bc" | "tb" | "bcrt");
+
+    let bytes = if is_segwit_hrp && !values.is_empty() {
+        let version = values[0];
+        hints.push(format!("Witness version: {}", version));
+        let program = bech32::convert_bits(&values[1..], 5, 8, false).unwrap_or_default();
+        match (version, program.len()) {
+            (0, 20) => hints.push("P2WPKH (20-byte key hash)".to_string()),
+            (0, 32) => hints.push("P2WSH (32-byte script hash)".to_string()),
+            (1, 32) => hints.push("P2TR (32-byte x-only output key)".to_string()),
+            (_, len) => hints.push(format!("{}-byte witness program", len)),
+        }
+        let expected = if version == 0 { bech32::Variant::Bech32 } else { bech32::Variant::Bech32m };
+        if variant.is_some() && variant != Some(expected) {
+            hints.push(format!("Witness version {} must use {:?}", version, expected));
+        }
+        program
+    } else {
+        bech32::convert_bits(values, 5, 8, false).unwrap_or_default()
+    };
+
+    Decoded {
+        encoding: if variant == Some(bech32::Variant::Bech32m) { Encoding::Bech32m } else { Encoding::Bech32 },
+        bytes,
+        checksum_valid: Some(variant.is_some()),
+        hints,
+    }
+}
@@ -0,0 +1,405 @@
+//! Output script descriptors (BIP380-386) for single-sig and sorted multisig
+//!
+//! `parse` reads the descriptors Bitcoin Core, Sparrow and most coordinators
+//! export: `pkh(KEY)`, `wpkh(KEY)`, `sh(wpkh(KEY))`, `tr(KEY)` (key path only)
+//! and `wsh(sortedmulti(k,KEY,...))`. A KEY is an optional
+//! `[fingerprint/path]` origin followed by a hex public key or an extended
+//! key with unhardened steps and an optional trailing `*` wildcard, e.g.
+//! `[73c5da0a/84'/0'/0']xpub.../0/*`. Extended private keys are accepted
+//! and immediately neutered; hardened steps after the key are not supported.
+//! A trailing `#checksum` is verified when present.
+
+use crate::address::{ScriptType, p2pkh_from_pubkey, p2sh_p2wpkh_from_pubkey, p2tr_from_pubkey, p2wpkh_from_pubkey};
+use crate::error::{KeyManagerError, Result};
+use crate::hd_key::{ExtendedKey, HARDENED_OFFSET};
+use crate::network::Network;
+use crate::path::DerivationPath;
+use crate::utils::{bech32, hex_to_bytes};
+use crate::xpub::ExtendedPublicKey;
+use secp256k1::PublicKey;
+use sha2::{Digest, Sha256};
+
+/// Characters allowed in a descriptor, in checksum order (BIP380)
+const INPUT_CHARSET: &[u8] =
+    b"0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+
+/// Characters of the 8-character checksum
+const CHECKSUM_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Length of the checksum after `#`
+const CHECKSUM_LEN: usize = 8;
+
+/// Most keys `sortedmulti` may have under `wsh` (the standardness limit)
+pub const MAX_MULTISIG_KEYS: usize = 20;
+
+/// A parsed descriptor
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Descriptor {
+    /// `pkh(KEY)`: legacy P2PKH
+    Pkh(DescriptorKey),
+    /// `wpkh(KEY)`: native segwit P2WPKH
+    Wpkh(DescriptorKey),
+    /// `sh(wpkh(KEY))`: nested segwit
+    ShWpkh(DescriptorKey),
+    /// `tr(KEY)`: BIP86 key-path-only taproot
+    Tr(DescriptorKey),
+    /// `wsh(sortedmulti(k,KEY,...))`: k-of-n P2WSH multisig with BIP67 key order
+    WshSortedMulti { threshold: usize, keys: Vec<DescriptorKey> },
+}
+
+/// Where a key came from: `[fingerprint/path]`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyOrigin {
+    /// Master key fingerprint
+    pub fingerprint: [u8; 4],
+    /// Path from the master key to the key that follows the origin
+    pub path: DerivationPath,
+}
+
+/// The key part of a key expression
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Key {
+    /// A fixed compressed public key (x-only `tr` keys are stored with an 0x02 prefix)
+    Single([u8; 33]),
+    /// An extended public key, the unhardened steps below it, and whether a `*` follows them
+    Extended { xpub: ExtendedPublicKey, path: DerivationPath, wildcard: bool },
+}
+
+/// One key expression, e.g. `[73c5da0a/84'/0'/0']xpub.../0/*`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DescriptorKey {
+    pub origin: Option<KeyOrigin>,
+    pub key: Key,
+}
+
+impl DescriptorKey {
+    /// Whether this key ends in a `*` wildcard
+    pub fn is_ranged(&self) -> bool {
+        matches!(self.key, Key::Extended { wildcard: true, .. })
+    }
+
+    /// Compressed public key at `index` (ignored unless the key is ranged)
+    fn public_key_at(&self, index: u32, network: Network) -> Result<[u8; 33]> {
+        match &self.key {
+            Key::Single(public_key) => Ok(*public_key),
+            Key::Extended { xpub, path, wildcard } => {
+                if xpub.version().is_testnet() != (network != Network::Mainnet) {
+                    return Err(KeyManagerError::EncodingError(format!(
+                        "Descriptor key is for {}, not {}",
+                        if xpub.version().is_testnet() { "testnet" } else { "mainnet" },
+                        network.name()
+                    )));
+                }
+                let mut key = xpub.derive(path)?;
+                if *wildcard {
+                    key = key.derive_child(index)?;
+                }
+                Ok(*key.public_key())
+            }
+        }
+    }
+}
+
+impl Descriptor {
+    /// Key expressions in the order they appear
+    pub fn keys(&self) -> &[DescriptorKey] {
+        match self {
+            Descriptor::Pkh(key) | Descriptor::Wpkh(key) | Descriptor::ShWpkh(key) | Descriptor::Tr(key) => {
+                std::slice::from_ref(key)
+            }
+            Descriptor::WshSortedMulti { keys, .. } => keys,
+        }
+    }
+
+    /// Whether any key has a `*` wildcard, i.e. the descriptor describes a chain of addresses
+    pub fn is_ranged(&self) -> bool {
+        self.keys().iter().any(DescriptorKey::is_ranged)
+    }
+
+    /// Address at `index` of a ranged descriptor (`*` replaced by `index`)
+    ///
+    /// A descriptor without a wildcard has a single address, at index 0.
+    /// Extended keys must match `network` (xpub for mainnet, tpub otherwise).
+    pub fn address_at(&self, index: u32, network: Network) -> Result<String> {
+        let public_keys = self.public_keys_at(index, network)?;
+        match self {
+            Descriptor::Pkh(_) => Ok(p2pkh_from_pubkey(&public_keys[0], network)),
+            Descriptor::Wpkh(_) => p2wpkh_from_pubkey(&public_keys[0], network),
+            Descriptor::ShWpkh(_) => Ok(p2sh_p2wpkh_from_pubkey(&public_keys[0], network)),
+            Descriptor::Tr(_) => p2tr_from_pubkey(&public_keys[0], network),
+            Descriptor::WshSortedMulti { threshold, .. } => {
+                let script = multisig_script(*threshold, &public_keys);
+                bech32::encode_segwit(network.bech32_hrp(), 0, &Sha256::digest(&script))
+            }
+        }
+    }
+
+    /// Public keys behind the address at `index`, in script order
+    ///
+    /// For `sortedmulti` that is BIP67 order (sorted), not the order the
+    /// keys are written in. Index rules are those of `address_at`.
+    pub fn public_keys_at(&self, index: u32, network: Network) -> Result<Vec<[u8; 33]>> {
+        if index >= HARDENED_OFFSET {
+            return Err(KeyManagerError::InvalidDerivationPath(format!(
+                "Descriptor index {} is hardened; wildcards are unhardened",
+                index
+            )));
+        }
+        if index > 0 && !self.is_ranged() {
+            return Err(KeyManagerError::EncodingError(format!(
+                "Descriptor has no wildcard, so it has no address at index {}",
+                index
+            )));
+        }
+
+        let mut public_keys = self
+            .keys()
+            .iter()
+            .map(|key| key.public_key_at(index, network))
+            .collect::<Result<Vec<_>>>()?;
+        if let Descriptor::WshSortedMulti { .. } = self {
+            public_keys.sort();
+        }
+        Ok(public_keys)
+    }
+}
+
+/// Parse a descriptor, verifying its `#checksum` if present
+pub fn parse(descriptor: &str) -> Result<Descriptor> {
+    let descriptor = descriptor.trim();
+    let body = match descriptor.split_once('#') {
+        Some((body, expected)) => {
+            let actual = checksum(body)?;
+            if expected != actual {
+                return Err(invalid(format!("checksum mismatch: expected #{}, computed #{}", expected, actual)));
+            }
+            body
+        }
+        None => descriptor,
+    };
+
+    if let Some(inner) = call(body, "pkh") {
+        Ok(Descriptor::Pkh(parse_key(inner, false)?))
+    } else if let Some(inner) = call(body, "wpkh") {
+        Ok(Descriptor::Wpkh(parse_key(inner, false)?))
+    } else if let Some(inner) = call(body, "sh") {
+        let inner = call(inner, "wpkh").ok_or_else(|| invalid("only sh(wpkh(...)) is supported".to_string()))?;
+        Ok(Descriptor::ShWpkh(parse_key(inner, false)?))
+    } else if let Some(inner) = call(body, "tr") {
+        if inner.contains(',') {
+            return Err(invalid("tr() script trees are not supported, only a single key".to_string()));
+        }
+        Ok(Descriptor::Tr(parse_key(inner, true)?))
+    } else if let Some(inner) = call(body, "wsh") {
+        let inner = call(inner, "sortedmulti")
+            .ok_or_else(|| invalid("only wsh(sortedmulti(...)) is supported".to_string()))?;
+        parse_sorted_multi(inner)
+    } else {
+        Err(invalid(format!(
+            "unsupported descriptor '{}' (expected pkh, wpkh, sh(wpkh), tr or wsh(sortedmulti))",
+            body
+        )))
+    }
+}
+
+/// The 8-character BIP380 checksum of a descriptor (without its `#`)
+pub fn checksum(descriptor: &str) -> Result<String> {
+    let mut c: u64 = 1;
+    let mut class = 0;
+    let mut class_count = 0;
+    for ch in descriptor.chars() {
+        let position = INPUT_CHARSET
+            .iter()
+            .position(|&allowed| ch.is_ascii() && allowed == ch as u8)
+            .ok_or_else(|| invalid(format!("character '{}' is not allowed", ch)))? as u64;
+        c = polymod(c, position & 31);
+        class = class * 3 + (position >> 5);
+        class_count += 1;
+        if class_count == 3 {
+            c = polymod(c, class);
+            class = 0;
+            class_count = 0;
+        }
+    }
+    if class_count > 0 {
+        c = polymod(c, class);
+    }
+    for _ in 0..CHECKSUM_LEN {
+        c = polymod(c, 0);
+    }
+    c ^= 1;
+
+    Ok((0..CHECKSUM_LEN)
+        .map(|i| CHECKSUM_CHARSET[((c >> (5 * (CHECKSUM_LEN - 1 - i))) & 31) as usize] as char)
+        .collect())
+}
+
+/// `descriptor#checksum`
+pub fn with_checksum(descriptor: &str) -> Result<String> {
+    Ok(format!("{}#{}", descriptor, checksum(descriptor)?))
+}
+
+/// Checksummed single-key descriptor for `script_type` around a key expression
+///
+/// `single_key(ScriptType::P2shP2wpkh, "[73c5da0a/49'/0'/0']xpub.../0/*")`
+/// gives `sh(wpkh([73c5da0a/49'/0'/0']xpub.../0/*))#...`.
+pub fn single_key(script_type: ScriptType, key: &str) -> Result<String> {
+    with_checksum(&match script_type {
+        ScriptType::P2pkh => format!("pkh({})", key),
+        ScriptType::P2shP2wpkh => format!("sh(wpkh({}))", key),
+        ScriptType::P2wpkh => format!("wpkh({})", key),
+        ScriptType::P2tr => format!("tr({})", key),
+    })
+}
+
+/// BIP380 checksum step over GF(32)
+fn polymod(c: u64, value: u64) -> u64 {
+    const GENERATOR: [u64; 5] = [0xf5dee51989, 0xa9fdca3312, 0x1bab10e32d, 0x3706b1677a, 0x644d626ffd];
+
+    let top = c >> 35;
+    let mut c = ((c & 0x7ffffffff) << 5) ^ value;
+    for (bit, generator) in GENERATOR.iter().enumerate() {
+        if (top >> bit) & 1 == 1 {
+            c ^= generator;
+        }
+    }
+    c
+}
+
+/// `sortedmulti` arguments: the threshold, then the keys
+fn parse_sorted_multi(arguments: &str) -> Result<Descriptor> {
+    let mut arguments = arguments.split(',');
+    let threshold = arguments.next().unwrap_or_default();
+    let threshold: usize = threshold
+        .parse()
+        .map_err(|_| invalid(format!("sortedmulti threshold '{}' is not a number", threshold)))?;
+    let keys = arguments.map(|key| parse_key(key, false)).collect::<Result<Vec<_>>>()?;
+
+    if keys.len() > MAX_MULTISIG_KEYS {
+        return Err(invalid(format!("sortedmulti has {} keys, at most {} allowed", keys.len(), MAX_MULTISIG_KEYS)));
+    }
+    if threshold == 0 || threshold > keys.len() {
+        return Err(invalid(format!("sortedmulti threshold {} must be between 1 and {}", threshold, keys.len())));
+    }
+    Ok(Descriptor::WshSortedMulti { threshold, keys })
+}
+
+/// One key expression; `x_only` allows 32-byte hex keys (inside `tr`)
+fn parse_key(expression: &str, x_only: bool) -> Result<DescriptorKey> {
+    let (origin, rest) = match expression.strip_prefix('[') {
+        Some(bracketed) => {
+            let (origin, rest) =
+                bracketed.split_once(']').ok_or_else(|| invalid(format!("unclosed key origin in '{}'", expression)))?;
+            (Some(parse_origin(origin)?), rest)
+        }
+        None => (None, expression),
+    };
+
+    let mut components = rest.split('/');
+    let encoded = components.next().unwrap_or_default();
+    let mut steps: Vec<&str> = components.collect();
+    let wildcard = match steps.last() {
+        Some(&"*") => {
+            steps.pop();
+            true
+        }
+        Some(&"*'") | Some(&"*h") => {
+            return Err(invalid("hardened wildcards need private keys and are not supported".to_string()))
+        }
+        _ => false,
+    };
+
+    let key = if encoded.bytes().all(|b| b.is_ascii_hexdigit()) {
+        if !steps.is_empty() || wildcard {
+            return Err(invalid(format!("public key '{}' cannot have derivation steps", encoded)));
+        }
+        Key::Single(parse_public_key(encoded, x_only)?)
+    } else {
+        let path = if steps.is_empty() { DerivationPath::default() } else { format!("m/{}", steps.join("/")).parse()? };
+        if path.indices().iter().any(|&index| index >= HARDENED_OFFSET) {
+            return Err(invalid(format!(
+                "hardened steps after '{}' need the private key and are not supported",
+                encoded
+            )));
+        }
+        Key::Extended { xpub: parse_extended_key(encoded)?, path, wildcard }
+    };
+
+    Ok(DescriptorKey { origin, key })
+}
+
+/// `fingerprint/path` inside the origin brackets
+fn parse_origin(origin: &str) -> Result<KeyOrigin> {
+    let (fingerprint, path) = origin.split_once('/').unwrap_or((origin, ""));
+    let fingerprint: [u8; 4] = hex_to_bytes(fingerprint)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| invalid(format!("key origin fingerprint '{}' is not 8 hex digits", fingerprint)))?;
+    let path = if path.is_empty() { DerivationPath::default() } else { format!("m/{}", path).parse()? };
+    Ok(KeyOrigin { fingerprint, path })
+}
+
+/// Hex public key: 33-byte compressed, or 32-byte x-only where allowed
+fn parse_public_key(encoded: &str, x_only: bool) -> Result<[u8; 33]> {
+    let mut bytes = hex_to_bytes(encoded)?;
+    if x_only && bytes.len() == 32 {
+        bytes.insert(0, 0x02);
+    }
+    if bytes.len() != 33 {
+        return Err(invalid(format!(
+            "public key '{}' must be 33 bytes compressed{}",
+            encoded,
+            if x_only { " or 32 bytes x-only" } else { "" }
+        )));
+    }
+    PublicKey::from_slice(&bytes).map_err(|e| KeyManagerError::Secp256k1Error(e.to_string()))?;
+
+    let mut public_key = [0u8; 33];
+    public_key.copy_from_slice(&bytes);
+    Ok(public_key)
+}
+
+/// xpub/tpub (or a SLIP-132 variant), or an xprv/tprv neutered on the spot
+fn parse_extended_key(encoded: &str) -> Result<ExtendedPublicKey> {
+    if let Ok(xpub) = encoded.parse::<ExtendedPublicKey>() {
+        return Ok(xpub);
+    }
+    let (key, network) = ExtendedKey::from_str_with_network(encoded)
+        .map_err(|_| invalid(format!("'{}' is not a public key or extended key", encoded)))?;
+    ExtendedPublicKey::from_private(&key, network.xpub_version())
+}
+
+/// `OP_k <key>... OP_n OP_CHECKMULTISIG`
+fn multisig_script(threshold: usize, public_keys: &[[u8; 33]]) -> Vec<u8> {
+    const OP_CHECKMULTISIG: u8 = 0xae;
+
+    let mut script = Vec::with_capacity(3 + public_keys.len() * 34);
+    push_small_int(&mut script, threshold);
+    for public_key in public_keys {
+        script.push(public_key.len() as u8);
+        script.extend_from_slice(public_key);
+    }
+    push_small_int(&mut script, public_keys.len());
+    script.push(OP_CHECKMULTISIG);
+    script
+}
+
+/// OP_1..OP_16, or a one-byte push for 17..=20
+fn push_small_int(script: &mut Vec<u8>, value: usize) {
+    const OP_1: u8 = 0x51;
+
+    if value <= 16 {
+        script.push(OP_1 + value as u8 - 1);
+    } else {
+        script.extend_from_slice(&[1, value as u8]);
+    }
+}
+
+/// `name(inner)` → `inner`
+fn call<'a>(expression: &'a str, name: &str) -> Option<&'a str> {
+    expression.strip_prefix(name)?.strip_prefix('(')?.strip_suffix(')')
+}
+
+fn invalid(reason: String) -> KeyManagerError {
+    KeyManagerError::EncodingError(format!("Invalid descriptor: {}", reason))
+}
@@ -0,0 +1,214 @@
+//! BIP44-style wallet discovery driven by a caller-supplied history check
+//!
+//! The crate never talks to the network: callers pass a closure that answers
+//! "has this address ever been used?" from their own backend (Electrum
+//! server, block explorer, local index).
+
+use crate::address::ScriptType;
+use crate::error::{KeyManagerError, Result};
+use crate::hd_key::{ExtendedKey, HARDENED_OFFSET};
+use crate::network::Network;
+use crate::progress::{checkpoint, NoProgress, Progress};
+use crate::utils::{base58, bech32};
+use crate::xpub::ExtendedPublicKey;
+
+/// Consecutive unused addresses after which a chain is considered exhausted (BIP44)
+pub const GAP_LIMIT: u32 = 20;
+
+/// Receive (0) and change (1) chains under an account
+const CHAINS: [u32; 2] = [0, 1];
+
+/// Used addresses on an account's receive and change chains
+///
+/// Walks each chain with public derivation from the account xpub and stops
+/// after `GAP_LIMIT` consecutive addresses without history. Returns the used
+/// addresses as `(chain, index, address)`, receive chain first.
+pub fn discover_addresses(
+    account: &ExtendedPublicKey,
+    script_type: ScriptType,
+    network: Network,
+    has_history: impl Fn(&str) -> bool,
+) -> Result<Vec<(u32, u32, String)>> {
+    discover_addresses_with_progress(account, script_type, network, has_history, &NoProgress)
+}
+
+/// `discover_addresses`, reporting each address checked to `progress`
+///
+/// The total is unknown, since the scan ends at the first full gap. Fails
+/// with `KeyManagerError::Cancelled` if `progress` breaks.
+pub fn discover_addresses_with_progress(
+    account: &ExtendedPublicKey,
+    script_type: ScriptType,
+    network: Network,
+    has_history: impl Fn(&str) -> bool,
+    progress: &impl Progress,
+) -> Result<Vec<(u32, u32, String)>> {
+    scan_account(account, script_type, network, &has_history, progress, &mut 0)
+}
+
+/// Addresses to derive on a chain so that `gap_limit` unused ones follow the last used one
+///
+/// `highest_used_index` is the last index with history, or `None` for an
+/// untouched chain, so `(None, 20)` gives 20 and `(Some(5), 20)` gives 26.
+/// Capped at `HARDENED_OFFSET`, the number of non-hardened indices.
+pub fn addresses_to_scan(highest_used_index: Option<u32>, gap_limit: u32) -> u32 {
+    let used = highest_used_index.map_or(0, |index| u64::from(index) + 1);
+    (used + u64::from(gap_limit)).min(u64::from(HARDENED_OFFSET)) as u32
+}
+
+/// Accounts (`m/purpose'/coin'/account'`) that have address history
+///
+/// Scans account 0, 1, 2, ... with `discover_addresses` and stops at the
+/// first account without any used address (the BIP44 account gap), which is
+/// not included in the result.
+pub fn discover_accounts(
+    master: &ExtendedKey,
+    coin: u32,
+    script_type: ScriptType,
+    network: Network,
+    has_history: impl Fn(&str) -> bool,
+) -> Result<Vec<u32>> {
+    discover_accounts_with_progress(master, coin, script_type, network, has_history, &NoProgress)
+}
+
+/// `discover_accounts`, reporting every address checked across all accounts
+pub fn discover_accounts_with_progress(
+    master: &ExtendedKey,
+    coin: u32,
+    script_type: ScriptType,
+    network: Network,
+    has_history: impl Fn(&str) -> bool,
+    progress: &impl Progress,
+) -> Result<Vec<u32>> {
+    let mut accounts = Vec::new();
+    let mut checked = 0;
+
+    for account in 0..HARDENED_OFFSET {
+        let path = format!("m/{}'/{}'/{}'", script_type.purpose(), coin, account);
+        let account_xpub = master.derive_path(&path)?.to_extended_public_key()?;
+        if scan_account(&account_xpub, script_type, network, &has_history, progress, &mut checked)?.is_empty() {
+            break;
+        }
+        accounts.push(account);
+    }
+
+    Ok(accounts)
+}
+
+/// Chain and index of `address` under an account key, as `(is_change, index)`
+///
+/// Checks indices `0..=max_index` on the receive and change chains side by
+/// side, so low indices on either chain are found first. Derivation is
+/// public (CKDpub) from the account xpub. Returns `None` if no address in
+/// the window matches; bech32 addresses are compared case-insensitively.
+pub fn address_belongs_to(
+    account: &ExtendedKey,
+    address: &str,
+    script_type: ScriptType,
+    network: Network,
+    max_index: u32,
+) -> Result<Option<(bool, u32)>> {
+    let account = account.to_extended_public_key()?;
+    let chains = [account.derive_child(0)?, account.derive_child(1)?];
+    let matches = |candidate: &str| match script_type {
+        ScriptType::P2wpkh | ScriptType::P2tr => candidate.eq_ignore_ascii_case(address),
+        ScriptType::P2pkh | ScriptType::P2shP2wpkh => candidate == address,
+    };
+
+    for index in 0..=max_index.min(HARDENED_OFFSET - 1) {
+        for (chain, chain_key) in CHAINS.into_iter().zip(&chains) {
+            if matches(&script_type.address_from_pubkey(chain_key.derive_child(index)?.public_key(), network)?) {
+                return Ok(Some((chain == 1, index)));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// First receive address whose body starts with `prefix`, as `(index, address)`
+///
+/// The body is what follows the part fixed by network and script type:
+/// `bc1q`/`bc1p` (or the testnet/regtest HRP) for bech32, the leading
+/// version character for base58. Bech32 prefixes match case-insensitively.
+/// Only indices `0..=max_index` of the receive chain are tried, so every
+/// match is an ordinary wallet address; `None` if none matches. Each extra
+/// base58 character multiplies the expected search by 58, bech32 by 32.
+pub fn find_vanity(
+    account: &ExtendedKey,
+    prefix: &str,
+    script_type: ScriptType,
+    network: Network,
+    max_index: u32,
+) -> Result<Option<(u32, String)>> {
+    find_vanity_with_progress(account, prefix, script_type, network, max_index, &NoProgress)
+}
+
+/// `find_vanity`, reporting each index tried out of the `max_index + 1` in the window
+///
+/// Fails with `KeyManagerError::Cancelled` if `progress` breaks.
+pub fn find_vanity_with_progress(
+    account: &ExtendedKey,
+    prefix: &str,
+    script_type: ScriptType,
+    network: Network,
+    max_index: u32,
+    progress: &impl Progress,
+) -> Result<Option<(u32, String)>> {
+    let bech32 = matches!(script_type, ScriptType::P2wpkh | ScriptType::P2tr);
+    let prefix = if bech32 { prefix.to_ascii_lowercase() } else { prefix.to_string() };
+    let alphabet: &[u8] = if bech32 { bech32::CHARSET } else { base58::BASE58_ALPHABET };
+    if let Some(c) = prefix.chars().find(|c| !c.is_ascii() || !alphabet.contains(&(*c as u8))) {
+        return Err(KeyManagerError::EncodingError(format!(
+            "'{}' never appears in {} addresses",
+            c,
+            script_type.name()
+        )));
+    }
+    // HRP, separator and witness version character for bech32
+    let fixed = if bech32 { network.bech32_hrp().len() + 2 } else { 1 };
+
+    let receive = account.to_extended_public_key()?.derive_child(0)?;
+    let max_index = max_index.min(HARDENED_OFFSET - 1);
+    for index in 0..=max_index {
+        let address = script_type.address_from_pubkey(receive.derive_child(index)?.public_key(), network)?;
+        if address[fixed..].starts_with(&prefix) {
+            return Ok(Some((index, address)));
+        }
+        checkpoint(progress, u64::from(index) + 1, Some(u64::from(max_index) + 1))?;
+    }
+
+    Ok(None)
+}
+
+/// Gap-limit walk of both chains; `checked` carries the running address count
+fn scan_account(
+    account: &ExtendedPublicKey,
+    script_type: ScriptType,
+    network: Network,
+    has_history: &impl Fn(&str) -> bool,
+    progress: &impl Progress,
+    checked: &mut u64,
+) -> Result<Vec<(u32, u32, String)>> {
+    let mut used = Vec::new();
+
+    for chain in CHAINS {
+        let chain_key = account.derive_child(chain)?;
+        let mut gap = 0;
+        let mut index = 0;
+        while gap < GAP_LIMIT && index < HARDENED_OFFSET {
+            let address = script_type.address_from_pubkey(chain_key.derive_child(index)?.public_key(), network)?;
+            if has_history(&address) {
+                used.push((chain, index, address));
+                gap = 0;
+            } else {
+                gap += 1;
+            }
+            index += 1;
+            *checked += 1;
+            checkpoint(progress, *checked, None)?;
+        }
+    }
+
+    Ok(used)
+}
@@ -0,0 +1,231 @@
+//! Entropy sources and the NIST SP 800-90B continuous health tests
+//!
+//! These tests catch catastrophic failures (a source stuck on one value,
+//! or heavily biased towards one), not subtle weaknesses. The cutoffs assume
+//! a conservative 1 bit of min-entropy per byte with a false-positive rate
+//! of 2^-20, so a healthy OS generator essentially never trips them.
+
+use crate::error::{KeyManagerError, Result};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use zeroize::Zeroize;
+
+/// Bytes drawn from the source for one health check (two APT windows)
+pub const SAMPLE_SIZE: usize = 1024;
+
+/// Repetition count cutoff: 1 + ceil(20 / H) with H = 1 (SP 800-90B 4.4.1)
+pub const REPETITION_CUTOFF: usize = 21;
+
+/// Adaptive proportion window for non-binary samples (SP 800-90B 4.4.2)
+pub const ADAPTIVE_WINDOW: usize = 512;
+
+/// Adaptive proportion cutoff for H = 1 and a 512-sample window
+pub const ADAPTIVE_CUTOFF: usize = 410;
+
+/// Source of random bytes for key generation
+pub trait EntropySource {
+    /// Fill `dest` entirely with random bytes
+    fn fill_bytes(&mut self, dest: &mut [u8]) -> Result<()>;
+}
+
+/// Device the OS generator is read from by default
+pub const DEFAULT_DEVICE: &str = "/dev/urandom";
+
+impl<T: EntropySource + ?Sized> EntropySource for Box<T> {
+    fn fill_bytes(&mut self, dest: &mut [u8]) -> Result<()> {
+        (**self).fill_bytes(dest)
+    }
+}
+
+/// A character device of random bytes, by default the OS generator (`/dev/urandom`)
+///
+/// Air-gapped machines may point this at a hardware RNG instead (`/dev/hwrng`,
+/// a TPM-backed path). Failing to open or fully read the device is an error;
+/// there is no fallback to another source.
+#[derive(Clone, Debug)]
+pub struct SystemEntropy {
+    device: PathBuf,
+}
+
+impl SystemEntropy {
+    /// The OS generator
+    pub fn new() -> Self {
+        SystemEntropy::with_device(DEFAULT_DEVICE)
+    }
+
+    /// Read from `path` instead of the OS generator
+    pub fn with_device(path: impl Into<PathBuf>) -> Self {
+        SystemEntropy { device: path.into() }
+    }
+
+    /// Path of the device bytes are read from
+    pub fn device(&self) -> &Path {
+        &self.device
+    }
+}
+
+impl Default for SystemEntropy {
+    fn default() -> Self {
+        SystemEntropy::new()
+    }
+}
+
+impl EntropySource for SystemEntropy {
+    fn fill_bytes(&mut self, dest: &mut [u8]) -> Result<()> {
+        let mut file = File::open(&self.device).map_err(|e| {
+            KeyManagerError::KeyGenerationError(format!("Cannot open entropy device {}: {}", self.device.display(), e))
+        })?;
+        file.read_exact(dest).map_err(|e| {
+            let reason = if e.kind() == ErrorKind::UnexpectedEof {
+                format!("returned fewer than the {} bytes requested", dest.len())
+            } else {
+                e.to_string()
+            };
+            KeyManagerError::KeyGenerationError(format!("Entropy device {} {}", self.device.display(), reason))
+        })
+    }
+}
+
+/// Several entropy sources combined through HMAC-SHA256
+///
+/// Every source is read in full for each request and the outputs are fed
+/// into one HMAC-SHA256 extraction, then expanded HKDF-style to the
+/// requested length. Unlike XOR, a source that is broken or even chosen
+/// adversarially after seeing the others cannot cancel them out, so the
+/// result is at least as unpredictable as the best source. Any source
+/// failing fails the whole request.
+pub struct MixedEntropy(pub Vec<Box<dyn EntropySource>>);
+
+/// Domain-separation key for the extraction step
+const MIX_KEY: &[u8] = b"crypto-key-manager entropy mix v1";
+
+impl EntropySource for MixedEntropy {
+    fn fill_bytes(&mut self, dest: &mut [u8]) -> Result<()> {
+        if self.0.is_empty() {
+            return Err(KeyManagerError::KeyGenerationError("No entropy sources to mix".to_string()));
+        }
+        if dest.len() > 255 * 32 {
+            return Err(KeyManagerError::KeyGenerationError(format!(
+                "Cannot mix more than {} bytes per request",
+                255 * 32
+            )));
+        }
+
+        // Extract: each source contributes at least one full hash output
+        let mut extract = hmac_sha256(MIX_KEY)?;
+        let mut buffer = vec![0u8; dest.len().max(32)];
+        for (index, source) in self.0.iter_mut().enumerate() {
+            let drawn = source.fill_bytes(&mut buffer);
+            if drawn.is_ok() {
+                extract.update(&(index as u32).to_be_bytes());
+                extract.update(&buffer);
+            }
+            // Wipe in place; zeroizing the Vec itself would also truncate it
+            buffer.as_mut_slice().zeroize();
+            drawn?;
+        }
+        let mut prk = extract.finalize().into_bytes();
+
+        // Expand: T(i) = HMAC(prk, T(i-1) || i)
+        let mut previous = [0u8; 32];
+        for (counter, chunk) in dest.chunks_mut(32).enumerate() {
+            let mut expand = hmac_sha256(&prk)?;
+            if counter > 0 {
+                expand.update(&previous);
+            }
+            expand.update(&[(counter + 1) as u8]);
+            previous.copy_from_slice(&expand.finalize().into_bytes());
+            chunk.copy_from_slice(&previous[..chunk.len()]);
+        }
+        previous.zeroize();
+        prk.zeroize();
+        Ok(())
+    }
+}
+
+fn hmac_sha256(key: &[u8]) -> Result<Hmac<Sha256>> {
+    Hmac::<Sha256>::new_from_slice(key).map_err(|e| KeyManagerError::HmacError(e.to_string()))
+}
+
+#[cfg(test)]
+thread_local! {
+    static TEST_ENTROPY: std::cell::RefCell<Option<Vec<u8>>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Make the next `generate_mnemonic` call on this thread use `bytes` as its entropy
+///
+/// Only compiled into the crate's own unit tests (`cfg(test)`), so no build
+/// of the library that other code links against can contain the override.
+/// The bytes are consumed by one generation; later calls use the OS again.
+#[cfg(test)]
+pub(crate) fn set_test_entropy(bytes: Vec<u8>) {
+    TEST_ENTROPY.with(|entropy| *entropy.borrow_mut() = Some(bytes));
+}
+
+/// Take the override set by `set_test_entropy`, if any
+#[cfg(test)]
+pub(crate) fn take_test_entropy() -> Option<Vec<u8>> {
+    TEST_ENTROPY.with(|entropy| entropy.borrow_mut().take())
+}
+
+/// Statistics from a passing health check, for diagnostics output
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HealthReport {
+    /// Number of bytes tested
+    pub samples: usize,
+    /// Longest run of identical consecutive bytes
+    pub longest_run: usize,
+    /// Highest count of a window's first byte within that window
+    pub max_window_count: usize,
+}
+
+/// Run the repetition count and adaptive proportion tests on a fresh sample
+///
+/// Draws `SAMPLE_SIZE` bytes from `source` and discards them afterwards.
+/// Fails with `EntropySourceSuspect` if either test trips.
+pub fn health_check(source: &mut impl EntropySource) -> Result<HealthReport> {
+    let mut sample = [0u8; SAMPLE_SIZE];
+    source.fill_bytes(&mut sample)?;
+
+    let longest_run = longest_run(&sample);
+    if longest_run >= REPETITION_CUTOFF {
+        return Err(KeyManagerError::EntropySourceSuspect(format!(
+            "repetition count test failed: {} identical bytes in a row (cutoff {})",
+            longest_run, REPETITION_CUTOFF
+        )));
+    }
+
+    let max_window_count = sample
+        .chunks_exact(ADAPTIVE_WINDOW)
+        .map(|window| window.iter().filter(|&&b| b == window[0]).count())
+        .max()
+        .unwrap_or(0);
+    if max_window_count >= ADAPTIVE_CUTOFF {
+        return Err(KeyManagerError::EntropySourceSuspect(format!(
+            "adaptive proportion test failed: one value in {} of {} bytes (cutoff {})",
+            max_window_count, ADAPTIVE_WINDOW, ADAPTIVE_CUTOFF
+        )));
+    }
+
+    Ok(HealthReport {
+        samples: sample.len(),
+        longest_run,
+        max_window_count,
+    })
+}
+
+/// Length of the longest run of equal consecutive bytes
+fn longest_run(sample: &[u8]) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous = None;
+    for &byte in sample {
+        run = if previous == Some(byte) { run + 1 } else { 1 };
+        previous = Some(byte);
+        longest = longest.max(run);
+    }
+    longest
+}
@@ -1,3 +1,4 @@
+use crate::path::{format_component, DerivationPath};
 use std::fmt;
 
 /// Result type for key manager operations
@@ -28,6 +29,27 @@ pub enum KeyManagerError {
     /// Secp256k1 operation failed
     Secp256k1Error(String),
 
+    /// Entropy source failed a health test and must not be used
+    EntropySourceSuspect(String),
+
+    /// A step of a multi-component derivation failed
+    DerivationFailed {
+        path: DerivationPath,
+        /// Zero-based position of the failing component in `path`
+        component_index: usize,
+        source: Box<KeyManagerError>,
+    },
+
+    /// A progress callback asked a long-running operation to stop
+    Cancelled,
+
+    /// Extended key version prefix this operation cannot use
+    UnsupportedVersionBytes {
+        version: [u8; 4],
+        /// What the prefix is, from the registry, or "unknown (0x...)"
+        name: String,
+    },
+
 }
 
 impl fmt::Display for KeyManagerError {
@@ -59,11 +81,37 @@ impl fmt::Display for KeyManagerError {
             KeyManagerError::Secp256k1Error(msg) => {
                 write!(f, "Secp256k1 operation failed: {}", msg)
             }
+            KeyManagerError::EntropySourceSuspect(msg) => {
+                write!(f, "Entropy source looks broken: {}", msg)
+            }
+            KeyManagerError::DerivationFailed { path, component_index, source } => {
+                let component = path
+                    .indices()
+                    .get(*component_index)
+                    .map_or_else(String::new, |&index| format_component(index));
+                write!(
+                    f,
+                    "derivation failed at component {} ('{}') of {}: {}",
+                    component_index, component, path, source
+                )
+            }
+            KeyManagerError::Cancelled => write!(f, "Operation cancelled"),
+            KeyManagerError::UnsupportedVersionBytes { name, .. } => {
+                write!(f, "Unsupported extended key version: {}", name)
+            }
         }
     }
 }
 
-impl std::error::Error for KeyManagerError {}
+impl std::error::Error for KeyManagerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KeyManagerError::IoError(err) => Some(err),
+            KeyManagerError::DerivationFailed { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 // Convenience conversions
 impl From<String> for KeyManagerError {
@@ -0,0 +1,189 @@
+//! Offline Ethereum transaction signing (legacy EIP-155 and EIP-1559)
+//!
+//! Transactions are RLP-encoded, hashed with Keccak-256 and signed with a
+//! recoverable secp256k1 signature. The signed output is the raw transaction
+//! hex expected by `eth_sendRawTransaction`.
+
+pub mod rlp;
+
+use crate::error::{KeyManagerError, Result};
+use crate::hd_key::ExtendedKey;
+use crate::signing;
+use crate::utils;
+use secp256k1::{Message, PublicKey, SecretKey};
+use sha3::{Digest, Keccak256};
+
+/// EIP-2718 type byte for EIP-1559 transactions
+const EIP1559_TX_TYPE: u8 = 0x02;
+
+/// Legacy transaction with EIP-155 replay protection
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LegacyTx {
+    pub nonce: u64,
+    /// Gas price in wei
+    pub gas_price: u128,
+    /// Gas limit
+    pub gas: u64,
+    /// Recipient, or `None` for contract creation
+    pub to: Option<[u8; 20]>,
+    /// Value in wei
+    pub value: u128,
+    pub data: Vec<u8>,
+    pub chain_id: u64,
+}
+
+/// One entry of an EIP-2930 access list
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccessListItem {
+    pub address: [u8; 20],
+    pub storage_keys: Vec<[u8; 32]>,
+}
+
+/// EIP-1559 (type 2) fee-market transaction
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Eip1559Tx {
+    pub chain_id: u64,
+    pub nonce: u64,
+    /// Priority fee (tip) per gas in wei
+    pub max_priority_fee_per_gas: u128,
+    /// Fee cap per gas in wei
+    pub max_fee_per_gas: u128,
+    /// Gas limit
+    pub gas: u64,
+    /// Recipient, or `None` for contract creation
+    pub to: Option<[u8; 20]>,
+    /// Value in wei
+    pub value: u128,
+    pub data: Vec<u8>,
+    pub access_list: Vec<AccessListItem>,
+}
+
+/// Keccak-256 (the pre-standard SHA-3 padding used by Ethereum)
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+/// EIP-55 checksummed address (`0x...`) for a derived key
+pub fn address(key: &ExtendedKey) -> Result<String> {
+    let public_key = PublicKey::from_slice(&key.public_key()?)
+        .map_err(|e| KeyManagerError::Secp256k1Error(e.to_string()))?;
+    Ok(checksum_address(&address_bytes(&public_key)))
+}
+
+/// Sign a legacy transaction and return the raw signed transaction as `0x` hex
+pub fn sign_legacy_tx(key: &ExtendedKey, tx: &LegacyTx) -> Result<String> {
+    sign_legacy_tx_with(&secret_key(key.private_key())?, tx)
+}
+
+/// Sign an EIP-1559 transaction and return the raw signed transaction as `0x` hex
+pub fn sign_eip1559_tx(key: &ExtendedKey, tx: &Eip1559Tx) -> Result<String> {
+    sign_eip1559_tx_with(&secret_key(key.private_key())?, tx)
+}
+
+pub(crate) fn sign_legacy_tx_with(secret_key: &SecretKey, tx: &LegacyTx) -> Result<String> {
+    let mut fields = legacy_fields(tx);
+
+    // EIP-155: sign over the chain id with empty r and s
+    let mut unsigned = fields.clone();
+    unsigned.extend([rlp::encode_uint(tx.chain_id as u128), rlp::encode_uint(0), rlp::encode_uint(0)]);
+    let (recovery_id, signature) = sign_hash(secret_key, &keccak256(&rlp::encode_list(&unsigned)))?;
+
+    let v = tx.chain_id as u128 * 2 + 35 + recovery_id as u128;
+    fields.extend([
+        rlp::encode_uint(v),
+        rlp::encode_scalar(&signature[..32]),
+        rlp::encode_scalar(&signature[32..]),
+    ]);
+    Ok(format!("0x{}", utils::bytes_to_hex(&rlp::encode_list(&fields))))
+}
+
+pub(crate) fn sign_eip1559_tx_with(secret_key: &SecretKey, tx: &Eip1559Tx) -> Result<String> {
+    let mut fields = eip1559_fields(tx);
+
+    let mut unsigned = vec![EIP1559_TX_TYPE];
+    unsigned.extend(rlp::encode_list(&fields));
+    let (recovery_id, signature) = sign_hash(secret_key, &keccak256(&unsigned))?;
+
+    fields.extend([
+        rlp::encode_uint(recovery_id as u128),
+        rlp::encode_scalar(&signature[..32]),
+        rlp::encode_scalar(&signature[32..]),
+    ]);
+    let mut raw = vec![EIP1559_TX_TYPE];
+    raw.extend(rlp::encode_list(&fields));
+    Ok(format!("0x{}", utils::bytes_to_hex(&raw)))
+}
+
+fn legacy_fields(tx: &LegacyTx) -> Vec<Vec<u8>> {
+    vec![
+        rlp::encode_uint(tx.nonce as u128),
+        rlp::encode_uint(tx.gas_price),
+        rlp::encode_uint(tx.gas as u128),
+        encode_to(&tx.to),
+        rlp::encode_uint(tx.value),
+        rlp::encode_bytes(&tx.data),
+    ]
+}
+
+fn eip1559_fields(tx: &Eip1559Tx) -> Vec<Vec<u8>> {
+    let access_list: Vec<Vec<u8>> = tx
+        .access_list
+        .iter()
+        .map(|item| {
+            let keys: Vec<Vec<u8>> = item.storage_keys.iter().map(|k| rlp::encode_bytes(k)).collect();
+            rlp::encode_list(&[rlp::encode_bytes(&item.address), rlp::encode_list(&keys)])
+        })
+        .collect();
+
+    vec![
+        rlp::encode_uint(tx.chain_id as u128),
+        rlp::encode_uint(tx.nonce as u128),
+        rlp::encode_uint(tx.max_priority_fee_per_gas),
+        rlp::encode_uint(tx.max_fee_per_gas),
+        rlp::encode_uint(tx.gas as u128),
+        encode_to(&tx.to),
+        rlp::encode_uint(tx.value),
+        rlp::encode_bytes(&tx.data),
+        rlp::encode_list(&access_list),
+    ]
+}
+
+/// Contract creation encodes the recipient as an empty byte string
+fn encode_to(to: &Option<[u8; 20]>) -> Vec<u8> {
+    match to {
+        Some(address) => rlp::encode_bytes(address),
+        None => rlp::encode_bytes(&[]),
+    }
+}
+
+/// Recoverable signature over a 32-byte hash: (recovery id, r || s)
+fn sign_hash(secret_key: &SecretKey, hash: &[u8; 32]) -> Result<(u8, [u8; 64])> {
+    let signature = signing::signing_context()?.sign_ecdsa_recoverable(&Message::from_digest(*hash), secret_key);
+    let (recovery_id, compact) = signature.serialize_compact();
+    Ok((recovery_id.to_i32() as u8, compact))
+}
+
+fn secret_key(private_key: &[u8; 32]) -> Result<SecretKey> {
+    SecretKey::from_slice(private_key)
+        .map_err(|e| KeyManagerError::Secp256k1Error(format!("Invalid private key: {}", e)))
+}
+
+/// Last 20 bytes of keccak256 of the uncompressed public key (without 0x04)
+pub(crate) fn address_bytes(public_key: &PublicKey) -> [u8; 20] {
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&keccak256(&public_key.serialize_uncompressed()[1..])[12..]);
+    address
+}
+
+/// EIP-55 mixed-case checksum encoding
+pub fn checksum_address(address: &[u8; 20]) -> String {
+    let lower = utils::bytes_to_hex(address);
+    let hash = keccak256(lower.as_bytes());
+    let mut out = String::with_capacity(42);
+    out.push_str("0x");
+    for (i, c) in lower.chars().enumerate() {
+        let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+        out.push(if nibble >= 8 { c.to_ascii_uppercase() } else { c });
+    }
+    out
+}
@@ -0,0 +1,50 @@
+//! Recursive Length Prefix encoding (Ethereum yellow paper, appendix B)
+//!
+//! Only the encoder is needed for transaction signing. Items are encoded
+//! eagerly to bytes and lists are built from already-encoded items.
+
+/// Encode a byte string
+pub fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        return vec![bytes[0]];
+    }
+    let mut out = header(0x80, bytes.len());
+    out.extend_from_slice(bytes);
+    out
+}
+
+/// Encode an unsigned integer as its minimal big-endian byte string (0 is empty)
+pub fn encode_uint(value: u128) -> Vec<u8> {
+    encode_bytes(trim_leading_zeros(&value.to_be_bytes()))
+}
+
+/// Encode a big-endian scalar (e.g. a signature's r or s) without leading zeros
+pub fn encode_scalar(bytes: &[u8]) -> Vec<u8> {
+    encode_bytes(trim_leading_zeros(bytes))
+}
+
+/// Encode a list from items that are already RLP-encoded
+pub fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload_len = items.iter().map(Vec::len).sum();
+    let mut out = header(0xc0, payload_len);
+    for item in items {
+        out.extend_from_slice(item);
+    }
+    out
+}
+
+/// Short form `offset + len` for payloads up to 55 bytes, long form otherwise
+fn header(offset: u8, len: usize) -> Vec<u8> {
+    if len <= 55 {
+        return vec![offset + len as u8];
+    }
+    let len_bytes = trim_leading_zeros(&len.to_be_bytes()).to_vec();
+    let mut out = vec![offset + 55 + len_bytes.len() as u8];
+    out.extend_from_slice(&len_bytes);
+    out
+}
+
+fn trim_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
@@ -0,0 +1,84 @@
+//! Watch-only export of one account index across every script type
+//!
+//! `accounts_json` is the bundle a coordinator (multisig setup, watch-only
+//! wallet, server-side address generation) needs for one account. No private
+//! keys are included. Schema, version 1:
+//!
+//! ```text
+//! {
+//!   "version": 1,
+//!   "network": "mainnet" | "testnet" | "regtest",
+//!   "fingerprint": "<master fingerprint, 8 hex chars>",
+//!   "account": <account index>,
+//!   "accounts": [                          // BIP44, BIP49, BIP84, BIP86 in order
+//!     {
+//!       "purpose": 44,
+//!       "script_type": "p2pkh" | "p2sh-p2wpkh" | "p2wpkh" | "p2tr",
+//!       "path": "m/44'/0'/0'",
+//!       "key_origin": "[<fingerprint>/44'/0'/0']",
+//!       "xpub": "<account key with the plain xpub/tpub prefix>",
+//!       "slip132": "<same key with its SLIP-132 prefix (ypub, zpub, ...)>",
+//!       "first_receive_address": "<address at path/0/0>",
+//!       "receive_descriptor": "<checksummed descriptor for path/0/*, e.g. wpkh([...]xpub.../0/*)#...>"
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Fields are only ever added within a version; renaming or removing one
+//! bumps `version`.
+
+use crate::address::{self, ScriptType};
+use crate::descriptor;
+use crate::error::Result;
+use crate::hd_key::ExtendedKey;
+use crate::network::Network;
+use crate::utils::{bytes_to_hex, json_string};
+use crate::xpub::ExtendedPublicKey;
+
+/// Schema version written as the top-level `"version"`
+pub const ACCOUNTS_JSON_VERSION: u32 = 1;
+
+/// The version 1 accounts document for `account` below `master` (see the module docs)
+///
+/// `master` must be the wallet's master key, since its fingerprint goes
+/// into every key origin.
+pub fn accounts_json(master: &ExtendedKey, account: u32, network: Network) -> Result<String> {
+    let fingerprint = bytes_to_hex(&master.fingerprint()?);
+
+    let accounts = ScriptType::ALL
+        .into_iter()
+        .map(|script_type| {
+            let origin = format!("{}'/{}'/{}'", script_type.purpose(), network.coin_type(), account);
+            let path = format!("m/{}", origin);
+            let account_key = master.derive_path(&path)?;
+            let xpub = ExtendedPublicKey::from_private(&account_key, network.xpub_version())?;
+            let xpub_string = xpub.to_xpub_string()?;
+            let slip132 = xpub.reserialize(script_type.slip_version(network))?;
+            let receive = address::address(&account_key.derive_path("m/0/0")?, script_type, network)?;
+            let key_origin = format!("[{}/{}]", fingerprint, origin);
+            let receive_descriptor = descriptor::single_key(script_type, &format!("{}{}/0/*", key_origin, xpub_string))?;
+
+            Ok(format!(
+                "{{\"purpose\":{},\"script_type\":{},\"path\":{},\"key_origin\":{},\"xpub\":{},\"slip132\":{},\"first_receive_address\":{},\"receive_descriptor\":{}}}",
+                script_type.purpose(),
+                json_string(script_type.name()),
+                json_string(&path),
+                json_string(&key_origin),
+                json_string(&xpub_string),
+                json_string(&slip132),
+                json_string(&receive),
+                json_string(&receive_descriptor)
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(format!(
+        "{{\"version\":{},\"network\":{},\"fingerprint\":{},\"account\":{},\"accounts\":[{}]}}\n",
+        ACCOUNTS_JSON_VERSION,
+        json_string(network.name()),
+        json_string(&fingerprint),
+        account,
+        accounts.join(",")
+    ))
+}
@@ -1,31 +1,68 @@
 use crate::error::{KeyManagerError, Result};
+use crate::network::Network;
+use crate::path::{normalize_path_string, DerivationPath};
+use crate::taproot;
+use crate::utils::secret::SecretArray;
+use crate::utils::{base58, constant_time_eq, hash160};
+use crate::versions::{self, KeyVersion, Visibility};
+use crate::xpub::{ExtendedPublicKey, SlipVersion};
 use hmac::{Hmac, Mac};
-use sha2::{Digest, Sha256, Sha512};
-use ripemd::Ripemd160;
+use sha2::Sha512;
 use num_bigint::BigUint;
 use num_traits::Num;
-use num_traits::ToPrimitive;
-use secp256k1::{Secp256k1, SecretKey, PublicKey};
+use secp256k1::{Scalar, SecretKey, PublicKey, SECP256K1};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use zeroize::Zeroize;
 
 type HmacSha512 = Hmac<Sha512>;
 
-const HARDENED_OFFSET: u32 = 0x80000000; // 2^31
+pub(crate) const HARDENED_OFFSET: u32 = 0x80000000; // 2^31
+
+/// HMAC key BIP32 uses to turn a seed into a master key
+pub const BIP32_SEED_KEY: &[u8] = b"Bitcoin seed";
+
 const CURVE_ORDER_HEX: &str = "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141";
-const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// The secp256k1 group order n, parsed once and shared by every derivation
+fn curve_order() -> &'static BigUint {
+    static CURVE_ORDER: OnceLock<BigUint> = OnceLock::new();
+    CURVE_ORDER.get_or_init(|| BigUint::from_str_radix(CURVE_ORDER_HEX, 16).expect("valid curve order constant"))
+}
+
+#[cfg(test)]
+thread_local! {
+    static INVALID_CHILD: std::cell::Cell<Option<u32>> = const { std::cell::Cell::new(None) };
+}
+
+/// Make child `index` derive to the zero key on this thread (BIP32's invalid-child case)
+///
+/// The real case needs IL = n - k_par, which no test can find by search.
+#[cfg(test)]
+pub(crate) fn set_invalid_child(index: Option<u32>) {
+    INVALID_CHILD.with(|forced| forced.set(index));
+}
 
 /// Extended Key structure for BIP32 hierarchical deterministic keys
 #[derive(Clone, Debug)]
 pub struct ExtendedKey {
     /// 32-byte private key
-    private_key: [u8; 32],
+    private_key: SecretArray<32>,
     /// 32-byte chain code
-    chain_code: [u8; 32],
+    chain_code: SecretArray<32>,
     /// Depth in the derivation tree (0 for master)
     depth: u8,
     /// Parent key fingerprint (4 bytes)
     parent_fingerprint: [u8; 4],
     /// Child index
     child_index: u32,
+    /// Compressed public key, computed on first use
+    ///
+    /// Shared by `public_key`, `fingerprint` and every non-hardened
+    /// `derive_child`, so deriving many siblings costs one EC
+    /// multiplication for the parent instead of one per child.
+    public_key: OnceLock<[u8; 33]>,
 }
 
 impl ExtendedKey {
@@ -37,13 +74,25 @@ impl ExtendedKey {
     /// # Returns
     /// Master extended private key
     pub fn from_seed(seed: &[u8]) -> Result<Self> {
+        Self::from_seed_with_key(seed, BIP32_SEED_KEY)
+    }
+
+    /// Generate a master key using a non-standard BIP32 HMAC key
+    ///
+    /// Some altcoins replace `"Bitcoin seed"` with their own string. The HMAC
+    /// key is public domain separation, not a secret: it adds no security,
+    /// and the seed stays the only thing protecting the funds. It does change
+    /// every derived key, so funds under a custom key are invisible to
+    /// wallets that use the standard one, and recovering them later needs
+    /// the exact key bytes. Only use the key the target chain specifies.
+    pub fn from_seed_with_key(seed: &[u8], hmac_key: &[u8]) -> Result<Self> {
         // Validate seed length (recommended: 128-512 bits)
         if seed.len() < 16 || seed.len() > 64 {
             return Err(KeyManagerError::InvalidSeedLength);
         }
 
         // BIP32: I = HMAC-SHA512(Key = "Bitcoin seed", Data = seed)
-        let mut hmac = HmacSha512::new_from_slice(b"Bitcoin seed")
+        let mut hmac = HmacSha512::new_from_slice(hmac_key)
             .map_err(|_| KeyManagerError::KeyGenerationError("HMAC init failed".to_string()))?;
         hmac.update(seed);
         let result = hmac.finalize().into_bytes();
@@ -58,16 +107,40 @@ impl ExtendedKey {
         Self::validate_private_key(&private_key)?;
 
         Ok(ExtendedKey {
-            private_key,
-            chain_code,
+            private_key: private_key.into(),
+            chain_code: chain_code.into(),
             depth: 0,
             parent_fingerprint: [0u8; 4],
             child_index: 0,
+            public_key: OnceLock::new(),
+        })
+    }
+
+    /// Build a key from explicit BIP32 fields, bypassing `from_seed`
+    ///
+    /// Test-only: lets derivation edge cases start from a chosen private key
+    /// and chain code. The private key is still range-checked.
+    #[cfg(test)]
+    pub(crate) fn from_parts(
+        private_key: [u8; 32],
+        chain_code: [u8; 32],
+        depth: u8,
+        parent_fingerprint: [u8; 4],
+        child_index: u32,
+    ) -> Result<Self> {
+        Self::validate_private_key(&private_key)?;
+        Ok(ExtendedKey {
+            private_key: private_key.into(),
+            chain_code: chain_code.into(),
+            depth,
+            parent_fingerprint,
+            child_index,
+            public_key: OnceLock::new(),
         })
     }
 
     /// Validate that private key is within valid range for secp256k1
-    fn validate_private_key(private_key: &[u8; 32]) -> Result<()> {
+    pub(crate) fn validate_private_key(private_key: &[u8; 32]) -> Result<()> {
         // Check if all bytes are zero
         if private_key.iter().all(|&b| b == 0) {
             return Err(KeyManagerError::KeyGenerationError(
@@ -77,10 +150,7 @@ impl ExtendedKey {
 
         // Check if key is less than curve order n
         let key_num = BigUint::from_bytes_be(private_key);
-        let curve_order = BigUint::from_str_radix(CURVE_ORDER_HEX, 16)
-            .map_err(|_| KeyManagerError::KeyGenerationError("Invalid curve order".to_string()))?;
-
-        if key_num >= curve_order {
+        if &key_num >= curve_order() {
             return Err(KeyManagerError::KeyGenerationError(
                 "Private key exceeds curve order".to_string(),
             ));
@@ -93,10 +163,7 @@ impl ExtendedKey {
     fn add_keys_modulo(key1: &[u8], key2: &[u8]) -> Result<[u8; 32]> {
         let num1 = BigUint::from_bytes_be(key1);
         let num2 = BigUint::from_bytes_be(key2);
-        let curve_order = BigUint::from_str_radix(CURVE_ORDER_HEX, 16)
-            .map_err(|_| KeyManagerError::KeyGenerationError("Invalid curve order".to_string()))?;
-
-        let sum = (num1 + num2) % &curve_order;
+        let sum = (num1 + num2) % curve_order();
         let sum_bytes = sum.to_bytes_be();
 
         // Pad to 32 bytes if necessary
@@ -116,28 +183,26 @@ impl ExtendedKey {
     ///
     /// # Arguments
     /// * `index` - Child index (use index >= 2^31 for hardened derivation)
-    fn derive_child(&self, index: u32) -> Result<Self> {
+    pub(crate) fn derive_child(&self, index: u32) -> Result<Self> {
         let hardened = index >= HARDENED_OFFSET;
+        let parent_pub = self.get_public_key()?;
 
-        // Prepare data for HMAC
-        let mut data = Vec::new();
-
+        // HMAC input on the stack: 33 key bytes followed by ser32(index)
+        let mut data = [0u8; 37];
         if hardened {
             // Hardened child: data = 0x00 || ser256(private_key) || ser32(index)
-            data.push(0x00);
-            data.extend_from_slice(&self.private_key);
+            data[1..33].copy_from_slice(&self.private_key[..]);
         } else {
             // Non-hardened child: data = serP(public_key) || ser32(index)
-            let public_key = self.get_public_key()?;
-            data.extend_from_slice(&public_key);
+            data[..33].copy_from_slice(&parent_pub);
         }
-
-        data.extend_from_slice(&index.to_be_bytes());
+        data[33..].copy_from_slice(&index.to_be_bytes());
 
         // I = HMAC-SHA512(Key = chain_code, Data = data)
-        let mut hmac = HmacSha512::new_from_slice(&self.chain_code)
+        let mut hmac = HmacSha512::new_from_slice(&self.chain_code[..])
             .map_err(|_| KeyManagerError::KeyGenerationError("HMAC init failed".to_string()))?;
         hmac.update(&data);
+        data.zeroize();
         let result = hmac.finalize().into_bytes();
 
         // Split result
@@ -145,7 +210,9 @@ impl ExtendedKey {
         let ir = &result[32..];
 
         // Child private key = (parse256(IL) + parent_private_key) mod n
-        let child_key = Self::add_keys_modulo(il, &self.private_key)?;
+        let child_key = Self::add_keys_modulo(il, &self.private_key[..])?;
+        #[cfg(test)]
+        let child_key = if INVALID_CHILD.with(|forced| forced.get()) == Some(index) { [0u8; 32] } else { child_key };
 
         // Validate child key
         Self::validate_private_key(&child_key)?;
@@ -154,188 +221,499 @@ impl ExtendedKey {
         chain_code.copy_from_slice(ir);
 
         // Compute parent fingerprint (first 4 bytes of Hash160 of parent public key)
-        let parent_pub = self.get_public_key()?;
         let parent_fingerprint = Self::fingerprint_from_public(&parent_pub);
 
+        let depth = self.depth.checked_add(1).ok_or_else(|| {
+            KeyManagerError::KeyGenerationError("Maximum derivation depth (255) exceeded".to_string())
+        })?;
+
         Ok(ExtendedKey {
-            private_key: child_key,
-            chain_code,
-            depth: self.depth + 1,
+            private_key: child_key.into(),
+            chain_code: chain_code.into(),
+            depth,
             parent_fingerprint,
             child_index: index,
+            public_key: OnceLock::new(),
         })
     }
 
+    /// Derive the hardened child `index'`
+    ///
+    /// `index` is the unhardened number (44 for `44'`); the 2^31 offset is
+    /// added here. Chains like a path:
+    ///
+    /// ```ignore
+    /// let key = master.derive_hardened(44)?.derive_hardened(0)?.derive_hardened(0)?.derive_normal(0)?.derive_normal(5)?;
+    /// ```
+    pub fn derive_hardened(&self, index: u32) -> Result<Self> {
+        self.derive_child(Self::unhardened_index(index)? + HARDENED_OFFSET)
+    }
+
+    /// Derive the non-hardened child `index`, rejecting indices >= 2^31
+    pub fn derive_normal(&self, index: u32) -> Result<Self> {
+        self.derive_child(Self::unhardened_index(index)?)
+    }
+
+    fn unhardened_index(index: u32) -> Result<u32> {
+        if index >= HARDENED_OFFSET {
+            return Err(KeyManagerError::InvalidDerivationPath(format!(
+                "index {} is out of range (must be below 2^31)",
+                index
+            )));
+        }
+        Ok(index)
+    }
+
+    /// Derive a path, run `f` on the derived key, then wipe the derived key
+    ///
+    /// Useful when only a value computed from the key (an address, a
+    /// signature) is needed: the derived private key never escapes the closure
+    /// and is zeroized as soon as `f` returns.
+    ///
+    /// # Usage
+    /// ```ignore
+    /// let address = master.derive_and("m/44'/0'/0'/0/0", |k| p2pkh_address(k, Network::Mainnet))?;
+    /// ```
+    pub fn derive_and<T>(&self, path: &str, f: impl FnOnce(&ExtendedKey) -> Result<T>) -> Result<T> {
+        let derived = self.derive_path(path)?;
+        f(&derived)
+        // `derived` is dropped (and zeroized) here
+    }
+
     /// Derive key using a BIP32 path (e.g., "m/44'/0'/0'/0/0")
     ///
+    /// Uppercase `M/...` paths name public keys in BIP32 notation and are
+    /// rejected here; use `derive_path_public` for them.
+    ///
     /// # Arguments
     /// * `path` - Derivation path string
     ///
     /// # Returns
     /// Derived extended key
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(path = %path)))]
     pub fn derive_path(&self, path: &str) -> Result<Self> {
-        let path = path.trim();
-
-        // Check if path starts with "m" or "M"
-        if !path.starts_with('m') && !path.starts_with('M') {
+        if path.trim_start().starts_with('M') {
             return Err(KeyManagerError::InvalidDerivationPath(
-                "Path must start with 'm' or 'M'".to_string(),
+                "'M' paths derive public keys; use derive_path_public or start the path with 'm'".to_string(),
             ));
         }
+        let derived = self.derive(&path.parse()?)?;
 
-        // Remove "m/" or "M/" prefix
-        let path = if path.len() > 2 && &path[1..2] == "/" {
-            &path[2..]
-        } else if path.len() == 1 {
-            return Ok(self.clone()); // Just "m" returns master key
-        } else {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(depth = derived.depth, "derivation complete");
+
+        Ok(derived)
+    }
+
+    /// Extended public key at an `M/...` path (e.g. "M/44'/0'/0'/0/0")
+    ///
+    /// Steps up to the last hardened one are derived privately, since only
+    /// a private parent can produce hardened children; the key is neutered
+    /// there and the remaining non-hardened steps use public derivation.
+    /// The result equals neutering the private key at the same `m/` path.
+    pub fn derive_path_public(&self, path: &str) -> Result<ExtendedPublicKey> {
+        if !path.trim_start().starts_with('M') {
             return Err(KeyManagerError::InvalidDerivationPath(
-                "Invalid path format".to_string(),
+                "Public derivation paths start with 'M'".to_string(),
             ));
-        };
-
-        // If empty after "m/", return master key
-        if path.is_empty() {
-            return Ok(self.clone());
         }
+        let path: DerivationPath = path.parse()?;
+        let indices = path.indices();
+        let private_steps = indices.iter().rposition(|&index| index >= HARDENED_OFFSET).map_or(0, |last| last + 1);
+
+        let mut public = self
+            .derive(&DerivationPath::from_indices(indices[..private_steps].to_vec()))?
+            .to_extended_public_key()?;
+        for &index in &indices[private_steps..] {
+            public = public.derive_child(index)?;
+        }
+        Ok(public)
+    }
 
-        // Parse path components
-        let mut current = self.clone();
-        for component in path.split('/') {
-            if component.is_empty() {
-                continue;
-            }
-
-            // Check for hardened derivation (ends with ' or h)
-            let (index_str, hardened) = if component.ends_with('\'') || component.ends_with('h') {
-                (&component[..component.len() - 1], true)
-            } else {
-                (component, false)
-            };
-
-            // Parse index
-            let index: u32 = index_str
-                .parse()
-                .map_err(|_| KeyManagerError::InvalidDerivationPath(
-                    format!("Invalid index: {}", index_str)
-                ))?;
-
-            // Apply hardened offset if needed
-            let final_index = if hardened {
-                index.checked_add(HARDENED_OFFSET)
-                    .ok_or_else(|| KeyManagerError::InvalidDerivationPath(
-                        "Index overflow".to_string()
-                    ))?
-            } else {
-                index
-            };
+    /// Like `derive_path`, but accepts pasted paths with stray spaces or backslashes
+    ///
+    /// See `path::normalize_path_string`; `derive_path` itself stays strict.
+    pub fn derive_path_lenient(&self, path: &str) -> Result<Self> {
+        self.derive_path(&normalize_path_string(path))
+    }
 
-            // Derive child
-            current = current.derive_child(final_index)?;
-        }
+    /// Derive a path whose unmarked components may default to hardened
+    ///
+    /// With `default_hardened` set, "m/44/0/0" derives like "m/44'/0'/0'".
+    /// Components carrying an explicit `'` or `h` marker are hardened either
+    /// way; see `DerivationPath::parse_with_default`.
+    pub fn derive_path_with_default(&self, path: &str, default_hardened: bool) -> Result<Self> {
+        self.derive(&DerivationPath::parse_with_default(path, default_hardened)?)
+    }
 
+    /// Derive the key at a parsed derivation path
+    pub fn derive(&self, path: &DerivationPath) -> Result<Self> {
+        let mut current = self.clone();
+        for (component_index, &index) in path.indices().iter().enumerate() {
+            current = current.derive_child(index).map_err(|source| KeyManagerError::DerivationFailed {
+                path: path.clone(),
+                component_index,
+                source: Box::new(source),
+            })?;
+        }
         Ok(current)
     }
 
     /// Get compressed public key from private key using secp256k1
-    fn get_public_key(&self) -> Result<Vec<u8>> {
-        let secp = Secp256k1::new();
-        let secret_key = SecretKey::from_slice(&self.private_key)
-            .map_err(|e| KeyManagerError::KeyGenerationError(
-                format!("Invalid private key: {}", e)
-            ))?;
-        let public_key = PublicKey::from_secret_key(&secp, &secret_key);
+    fn get_public_key(&self) -> Result<[u8; 33]> {
+        if let Some(public_key) = self.public_key.get() {
+            return Ok(*public_key);
+        }
+        let public_key = PublicKey::from_secret_key(SECP256K1, &self.secret_key()?);
         // serialize() returns 33-byte compressed public key [u8; 33]
-        Ok(public_key.serialize().to_vec())
+        Ok(*self.public_key.get_or_init(|| public_key.serialize()))
+    }
+
+    /// Whether the private key and chain code sit in page-locked memory
+    #[cfg(all(test, feature = "locked-memory"))]
+    pub(crate) fn is_locked(&self) -> bool {
+        self.private_key.is_locked() && self.chain_code.is_locked()
+    }
+
+    /// Raw private key bytes, for in-crate signing
+    pub(crate) fn private_key(&self) -> &[u8; 32] {
+        &self.private_key
+    }
+
+    /// Depth in the derivation tree (0 for master)
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    /// Fingerprint of the parent key ([0; 4] for master)
+    pub fn parent_fingerprint(&self) -> [u8; 4] {
+        self.parent_fingerprint
+    }
+
+    /// Child index this key was derived at (>= 2^31 for hardened)
+    pub fn child_index(&self) -> u32 {
+        self.child_index
+    }
+
+    /// 32-byte chain code
+    pub fn chain_code(&self) -> &[u8; 32] {
+        &self.chain_code
+    }
+
+    /// Neuter into the extended public key, serialized as `xpub`
+    pub fn to_extended_public_key(&self) -> Result<ExtendedPublicKey> {
+        ExtendedPublicKey::from_private(self, SlipVersion::Xpub)
+    }
+
+    /// Get the 33-byte compressed public key for this extended key
+    pub fn public_key(&self) -> Result<[u8; 33]> {
+        self.get_public_key()
+    }
+
+    /// 32-byte x-only public key (BIP340), the taproot internal key
+    pub fn x_only_public_key(&self) -> Result<[u8; 32]> {
+        let mut x_only = [0u8; 32];
+        x_only.copy_from_slice(&self.get_public_key()?[1..]);
+        Ok(x_only)
+    }
+
+    /// BIP341 output key for a key-path-only (BIP86) taproot output
+    ///
+    /// The x-only internal key tweaked by `tagged_hash("TapTweak", x(P))`,
+    /// as committed to by `tr(KEY)` descriptors and `bc1p` addresses.
+    pub fn taproot_output_key(&self) -> Result<[u8; 32]> {
+        let (output_key, _parity) = taproot::tweak_public_key(&self.x_only_public_key()?, None)?;
+        Ok(output_key)
+    }
+
+    /// Private key negated modulo the curve order (`n - k`)
+    ///
+    /// Building block for adaptor signatures and BIP340 even-Y fixes. The
+    /// result is raw secret material; the caller is responsible for wiping it.
+    pub fn private_key_negate(&self) -> Result<[u8; 32]> {
+        Ok(self.secret_key()?.negate().secret_bytes())
+    }
+
+    /// Private key multiplied by `tweak` modulo the curve order
+    ///
+    /// The tweak must be nonzero and below the curve order.
+    pub fn private_key_tweak_mul(&self, tweak: &[u8; 32]) -> Result<[u8; 32]> {
+        if tweak.iter().all(|&b| b == 0) {
+            return Err(KeyManagerError::Secp256k1Error("Tweak must be nonzero".to_string()));
+        }
+        let tweak = Scalar::from_be_bytes(*tweak).map_err(|_| {
+            KeyManagerError::Secp256k1Error("Tweak is not below the curve order".to_string())
+        })?;
+        let tweaked = self
+            .secret_key()?
+            .mul_tweak(&tweak)
+            .map_err(|e| KeyManagerError::Secp256k1Error(format!("Private key tweak failed: {}", e)))?;
+        Ok(tweaked.secret_bytes())
+    }
+
+    fn secret_key(&self) -> Result<SecretKey> {
+        SecretKey::from_slice(&self.private_key[..])
+            .map_err(|e| KeyManagerError::KeyGenerationError(format!("Invalid private key: {}", e)))
+    }
+
+    /// Whether `other_pubkey` (33-byte compressed) is this key's public key
+    ///
+    /// Lets a signer confirm it holds the private key behind an xpub or a
+    /// descriptor key. The bytes are compared in constant time; anything
+    /// other than a 33-byte key with an 0x02/0x03 prefix is an error.
+    pub fn same_public_key(&self, other_pubkey: &[u8]) -> Result<bool> {
+        if other_pubkey.len() != 33 {
+            return Err(KeyManagerError::EncodingError(format!(
+                "Expected a 33-byte compressed public key, got {} bytes",
+                other_pubkey.len()
+            )));
+        }
+        if !matches!(other_pubkey[0], 0x02 | 0x03) {
+            return Err(KeyManagerError::EncodingError(format!(
+                "Expected a compressed public key prefix 0x02 or 0x03, got 0x{:02x}",
+                other_pubkey[0]
+            )));
+        }
+        Ok(constant_time_eq(&self.get_public_key()?, other_pubkey))
+    }
+
+    /// Get this key's own fingerprint (first 4 bytes of Hash160 of its public key)
+    ///
+    /// For a master key this is the "master fingerprint" used to label
+    /// descriptors and to match hardware wallets.
+    pub fn fingerprint(&self) -> Result<[u8; 4]> {
+        let public_key = self.get_public_key()?;
+        Ok(Self::fingerprint_from_public(&public_key))
     }
 
     /// Calculate fingerprint from public key using Hash160
     /// Hash160 = RIPEMD160(SHA256(public_key))
     fn fingerprint_from_public(public_key: &[u8]) -> [u8; 4] {
-        // Step 1: SHA256(public_key)
-        let sha256_hash = Sha256::digest(public_key);
-
-        // Step 2: RIPEMD160(SHA256 result)
-        let mut hasher = Ripemd160::new();
-        hasher.update(&sha256_hash);
-        let hash160 = hasher.finalize();
-
         // Take first 4 bytes as fingerprint
         let mut fingerprint = [0u8; 4];
-        fingerprint.copy_from_slice(&hash160[..4]);
+        fingerprint.copy_from_slice(&hash160(public_key)[..4]);
         fingerprint
     }
 
-    /// Serialize to xprv format (Base58Check encoded)
-    pub fn to_string(&self) -> String {
-        // BIP32 serialization format
-        let mut data = Vec::new();
+    /// Parse a Base58Check xprv or tprv, returning the key and its network
+    ///
+    /// Testnet-versioned keys (`tprv`) are reported as `Network::Testnet`;
+    /// regtest uses the same version bytes.
+    pub fn from_str_with_network(s: &str) -> Result<(Self, Network)> {
+        let mut data = base58::decode_check(s.trim())?;
+        let parsed = Self::parse_bytes(&data);
+        data.zeroize();
+        parsed
+    }
+
+    /// Parse the raw 78-byte BIP32 serialization of a private key
+    fn parse_bytes(data: &[u8]) -> Result<(Self, Network)> {
+        if data.len() != 78 {
+            return Err(KeyManagerError::EncodingError(format!(
+                "Wrong extended key length: {} bytes, expected 78",
+                data.len()
+            )));
+        }
+
+        let version = [data[0], data[1], data[2], data[3]];
+        let network = [Network::Mainnet, Network::Testnet]
+            .into_iter()
+            .find(|network| version == network.xprv_version())
+            .ok_or_else(|| versions::unsupported(version))?;
+        if data[45] != 0x00 {
+            return Err(KeyManagerError::EncodingError(
+                "Extended private key must have a 0x00 key prefix".to_string(),
+            ));
+        }
+
+        let mut key = ExtendedKey {
+            private_key: [0u8; 32].into(),
+            chain_code: [0u8; 32].into(),
+            depth: data[4],
+            parent_fingerprint: [data[5], data[6], data[7], data[8]],
+            child_index: u32::from_be_bytes([data[9], data[10], data[11], data[12]]),
+            public_key: OnceLock::new(),
+        };
+        key.chain_code.copy_from_slice(&data[13..45]);
+        key.private_key.copy_from_slice(&data[46..78]);
+        Self::validate_private_key(&key.private_key)?;
+
+        // A master key has no parent and no index
+        if key.depth == 0 && (key.parent_fingerprint != [0u8; 4] || key.child_index != 0) {
+            return Err(KeyManagerError::EncodingError(
+                "Depth 0 key with a non-zero parent fingerprint or index".to_string(),
+            ));
+        }
+
+        Ok((key, network))
+    }
+
+    /// Base58Check serialization with the network's version (xprv or tprv)
+    pub fn to_string_for_network(&self, network: Network) -> String {
+        self.serialize_with(network.xprv_version())
+    }
 
-        // Version bytes (4 bytes) - mainnet private key
-        data.extend_from_slice(&[0x04, 0x88, 0xAD, 0xE4]);
+    /// Base58Check serialization under a registered private-key prefix (e.g. `Ltpv`)
+    ///
+    /// Only the prefix changes; the key material is the same for every coin.
+    pub fn to_string_with_version(&self, version: &KeyVersion) -> Result<String> {
+        if version.visibility != Visibility::Private {
+            return Err(versions::unsupported(version.bytes));
+        }
+        Ok(self.serialize_with(version.bytes))
+    }
+
+    fn serialize_with(&self, version: [u8; 4]) -> String {
+        // Payload and checksum share one stack buffer that is wiped afterwards
+        let mut data = [0u8; 82];
+        self.serialize_into(version, &mut data[..78]);
+        let checksum = base58::checksum(&data[..78]);
+        data[78..].copy_from_slice(&checksum);
+        let encoded = base58::encode(&data);
+        data.zeroize();
+        encoded
+    }
+
+    /// Raw 78-byte BIP32 serialization
+    fn serialize_bytes(&self, network: Network) -> [u8; 78] {
+        let mut data = [0u8; 78];
+        self.serialize_into(network.xprv_version(), &mut data);
+        data
+    }
+
+    /// Write the 78-byte serialization into `data` (which must be 78 bytes long)
+    fn serialize_into(&self, version: [u8; 4], data: &mut [u8]) {
+        // Version bytes (4 bytes) - xprv, tprv or an altcoin prefix
+        data[..4].copy_from_slice(&version);
 
         // Depth (1 byte)
-        data.push(self.depth);
+        data[4] = self.depth;
 
         // Parent fingerprint (4 bytes)
-        data.extend_from_slice(&self.parent_fingerprint);
+        data[5..9].copy_from_slice(&self.parent_fingerprint);
 
         // Child index (4 bytes)
-        data.extend_from_slice(&self.child_index.to_be_bytes());
+        data[9..13].copy_from_slice(&self.child_index.to_be_bytes());
 
         // Chain code (32 bytes)
-        data.extend_from_slice(&self.chain_code);
+        data[13..45].copy_from_slice(&self.chain_code[..]);
 
         // Private key (33 bytes: 0x00 + 32 bytes)
-        data.push(0x00);
-        data.extend_from_slice(&self.private_key);
+        data[45] = 0x00;
+        data[46..].copy_from_slice(&self.private_key[..]);
+    }
+}
 
-        // Base58Check encode
-        base58_check_encode(&data)
+impl Drop for ExtendedKey {
+    fn drop(&mut self) {
+        self.private_key.zeroize();
+        self.chain_code.zeroize();
+        self.public_key.take();
     }
 }
 
+impl FromStr for ExtendedKey {
+    type Err = KeyManagerError;
+
+    /// Parse an xprv or tprv (the network is discarded; see `from_str_with_network`)
+    fn from_str(s: &str) -> Result<Self> {
+        Self::from_str_with_network(s).map(|(key, _)| key)
+    }
+}
+
+impl fmt::Display for ExtendedKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_string_for_network(Network::Mainnet))
+    }
+}
+
+/// Whether 32 bytes are a usable secp256k1 private key (1 <= k < n)
+///
+/// For raw keys imported from WIF or an HSM, before building an
+/// `ExtendedKey` around them. Uses the same range check as derivation.
+pub fn is_valid_private_key(bytes: &[u8; 32]) -> bool {
+    ExtendedKey::validate_private_key(bytes).is_ok()
+}
+
 /// Generate master key from seed (convenience function)
 pub fn master_key_from_seed(seed: &[u8]) -> Result<ExtendedKey> {
     ExtendedKey::from_seed(seed)
 }
 
-// ============================================================================
-// Base58Check encoding (Bitcoin standard)
-// ============================================================================
-
-fn base58_check_encode(data: &[u8]) -> String {
-    // Calculate checksum: first 4 bytes of SHA256(SHA256(data))
-    let hash1 = Sha256::digest(data);
-    let hash2 = Sha256::digest(&hash1);
-    let checksum = &hash2[..4];
-
-    // Append checksum
-    let mut payload = data.to_vec();
-    payload.extend_from_slice(checksum);
-
-    // Convert to base58
-    let mut num = BigUint::from_bytes_be(&payload);
-    let mut encoded = String::new();
-    let base = BigUint::from(58u32);
-    let zero = BigUint::from(0u32);
-
-    while num > zero {
-        let remainder = &num % &base;
-        // Convert remainder to usize (always < 58, so safe)
-        let digit: usize = remainder.to_u64().unwrap_or(0) as usize;
-        encoded.insert(0, BASE58_ALPHABET[digit] as char);
-        num = num / &base;
-    }
-
-    // Add leading '1's for leading zero bytes
-    for &byte in payload.iter() {
-        if byte == 0 {
-            encoded.insert(0, '1');
-        } else {
-            break;
-        }
-    }
+/// Parse a serialized xprv/tprv and derive further along `relative_path`
+///
+/// `relative_path` is relative to the imported key ("0/0" or "m/0/0"); the
+/// result keeps counting depth from the imported key and is serialized with
+/// the same version bytes as the input.
+///
+/// # Usage
+/// ```ignore
+/// let receive0 = derive_from_xprv(&account_xprv, "0/0")?;
+/// ```
+pub fn derive_from_xprv(xprv: &str, relative_path: &str) -> Result<String> {
+    let (key, network) = ExtendedKey::from_str_with_network(xprv)?;
+    let path: DerivationPath = match relative_path.trim() {
+        "" => DerivationPath::from_indices(Vec::new()),
+        path if path.starts_with(['m', 'M']) => path.parse()?,
+        path => format!("m/{}", path).parse()?,
+    };
+    Ok(key.derive(&path)?.to_string_for_network(network))
+}
 
-    encoded
-}
\ No newline at end of file
+/// Check that `child` is what `master` derives to along `path`
+///
+/// Compares the full BIP32 serialization (depth, parent fingerprint, child
+/// index, chain code and key), so a backup or hardware-wallet response only
+/// passes if it really descends from `master` through `path`. Returns
+/// `Ok(false)` on mismatch and an error only if `path` cannot be derived.
+pub fn verify_path_descent(master: &ExtendedKey, path: &str, child: &ExtendedKey) -> Result<bool> {
+    let derived = master.derive_path(path)?;
+    let mut expected = derived.serialize_bytes(Network::Mainnet);
+    let mut actual = child.serialize_bytes(Network::Mainnet);
+    let matches = expected == actual;
+    expected.zeroize();
+    actual.zeroize();
+    Ok(matches)
+}
+
+/// One level of a `derivation_report`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DerivationStep {
+    /// Depth in the tree (0 for a master key)
+    pub depth: u8,
+    /// Child index used to reach this level (>= 2^31 for hardened; 0 at the root)
+    pub child_number: u32,
+    /// This level's own key fingerprint
+    pub fingerprint: [u8; 4],
+    /// Public half of the key at this level; its parent fingerprint links it to the previous step
+    pub xpub: ExtendedPublicKey,
+}
+
+/// Every level from `master` down to `path`, for audit logs
+///
+/// Returns `path.len() + 1` steps, starting with `master` itself. Only
+/// public data is recorded; the intermediate private keys are wiped as
+/// derivation moves past them.
+pub fn derivation_report(master: &ExtendedKey, path: &str) -> Result<Vec<DerivationStep>> {
+    let path: DerivationPath = path.parse()?;
+    let step = |key: &ExtendedKey| -> Result<DerivationStep> {
+        Ok(DerivationStep {
+            depth: key.depth(),
+            child_number: key.child_index(),
+            fingerprint: key.fingerprint()?,
+            xpub: key.to_extended_public_key()?,
+        })
+    };
+
+    let mut current = master.clone();
+    let mut steps = vec![step(&current)?];
+    for (component_index, &index) in path.indices().iter().enumerate() {
+        current = current.derive_child(index).map_err(|source| KeyManagerError::DerivationFailed {
+            path: path.clone(),
+            component_index,
+            source: Box::new(source),
+        })?;
+        steps.push(step(&current)?);
+    }
+    Ok(steps)
+}
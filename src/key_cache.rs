@@ -0,0 +1,181 @@
+//! In-memory cache of derived keys
+//!
+//! Wallet front ends derive the same account and address keys over and over
+//! (render, sign, re-render). `KeyCache` remembers every node it derives,
+//! keyed by path, so a request for `m/84'/0'/0'/0/5` after `m/84'/0'/0'/0/4`
+//! only derives the last step.
+
+use crate::error::{KeyManagerError, Result};
+use crate::hd_key::ExtendedKey;
+use crate::path::DerivationPath;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+/// Hit and miss counters for a `KeyCache`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// `get` calls answered without deriving anything
+    pub hits: u64,
+    /// `get` calls that had to derive at least one node
+    pub misses: u64,
+    /// Child derivations performed across all misses
+    pub derivations: u64,
+    /// Entries dropped to stay within capacity
+    pub evictions: u64,
+}
+
+/// LRU cache of derived keys below one master key
+///
+/// Every intermediate node is cached, so sibling paths share their common
+/// prefix. Evicted keys are dropped, which zeroizes them. The cache locks
+/// internally and can be shared between threads (e.g. in an `Arc`). The
+/// lock only covers lookups and inserts; derivation runs outside it, so one
+/// thread's miss does not hold up another's hit. Two threads missing the
+/// same path at once may both derive it.
+pub struct KeyCache {
+    master: ExtendedKey,
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    /// Derived key and the tick it was last used at
+    entries: HashMap<DerivationPath, (ExtendedKey, u64)>,
+    /// Paths by last-used tick, oldest first
+    recency: BTreeMap<u64, DerivationPath>,
+    tick: u64,
+    stats: CacheStats,
+}
+
+impl CacheState {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    /// The cached key at `path`, marked as used now
+    fn touch(&mut self, path: &DerivationPath) -> Option<ExtendedKey> {
+        let tick = self.next_tick();
+        let (key, last_used) = self.entries.get_mut(path)?;
+        self.recency.remove(last_used);
+        *last_used = tick;
+        self.recency.insert(tick, path.clone());
+        Some(key.clone())
+    }
+}
+
+impl KeyCache {
+    /// Cache up to `capacity` derived keys below `master`
+    pub fn new(master: ExtendedKey, capacity: usize) -> Self {
+        KeyCache {
+            master,
+            capacity,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// The key at `path`, derived from the longest cached prefix
+    ///
+    /// Errors are the same as `ExtendedKey::derive`.
+    pub fn get(&self, path: &DerivationPath) -> Result<ExtendedKey> {
+        let indices = path.indices();
+        let (start, mut current) = {
+            let mut state = self.lock()?;
+
+            // Longest prefix already in the cache (the root is always "cached")
+            let mut start = indices.len();
+            let mut current = None;
+            while start > 0 {
+                current = state.touch(&DerivationPath::from_indices(indices[..start].to_vec()));
+                if current.is_some() {
+                    break;
+                }
+                start -= 1;
+            }
+            let current = current.unwrap_or_else(|| self.master.clone());
+
+            if start == indices.len() {
+                state.stats.hits += 1;
+                return Ok(current);
+            }
+            state.stats.misses += 1;
+            (start, current)
+        };
+
+        let mut derived = Vec::with_capacity(indices.len() - start);
+        let mut result = Ok(());
+        for (component_index, &index) in indices.iter().enumerate().skip(start) {
+            match current.derive_child(index) {
+                Ok(child) => {
+                    current = child;
+                    derived.push(current.clone());
+                }
+                Err(source) => {
+                    result = Err(KeyManagerError::DerivationFailed {
+                        path: path.clone(),
+                        component_index,
+                        source: Box::new(source),
+                    });
+                    break;
+                }
+            }
+        }
+
+        let mut state = self.lock()?;
+        state.stats.derivations += derived.len() as u64;
+        for (offset, key) in derived.into_iter().enumerate() {
+            let prefix = DerivationPath::from_indices(indices[..=start + offset].to_vec());
+            self.insert(&mut state, prefix, key);
+        }
+        result.map(|()| current)
+    }
+
+    /// Counters since the cache was created
+    pub fn stats(&self) -> CacheStats {
+        self.lock().map(|state| state.stats).unwrap_or_default()
+    }
+
+    /// Number of keys currently cached
+    pub fn len(&self) -> usize {
+        self.lock().map(|state| state.entries.len()).unwrap_or(0)
+    }
+
+    /// Whether nothing is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop (and zeroize) every cached key
+    pub fn clear(&self) {
+        if let Ok(mut state) = self.lock() {
+            state.entries.clear();
+            state.recency.clear();
+        }
+    }
+
+    fn insert(&self, state: &mut CacheState, path: DerivationPath, key: ExtendedKey) {
+        if self.capacity == 0 {
+            return;
+        }
+        let tick = state.next_tick();
+        if let Some((_, last_used)) = state.entries.get(&path) {
+            // Another thread derived it meanwhile; keep one entry
+            let last_used = *last_used;
+            state.recency.remove(&last_used);
+        } else if state.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = state.recency.pop_first() {
+                state.entries.remove(&oldest);
+                state.stats.evictions += 1;
+            }
+        }
+        state.recency.insert(tick, path.clone());
+        state.entries.insert(path, (key, tick));
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, CacheState>> {
+        self.state
+            .lock()
+            .map_err(|_| KeyManagerError::KeyGenerationError("Key cache lock poisoned".to_string()))
+    }
+}
@@ -0,0 +1,183 @@
+//! Standalone secp256k1 keypairs, outside any HD wallet
+//!
+//! For one-off keys (paper wallets, test fixtures) where a mnemonic and a
+//! derivation path would be overkill. The private key goes through the same
+//! range check as BIP32 keys and is zeroized when the `Keypair` is dropped.
+//! `from_wif` and `from_hex` import a key exported from another wallet.
+//!
+//! The public key is stored compressed. Keys imported from an uncompressed
+//! WIF still have their legacy addresses: pass `compressed = false` to
+//! `wif_and_address` and `sign_bitcoin_message`.
+
+use crate::address::{self, ScriptType};
+use crate::entropy::EntropySource;
+use crate::error::{KeyManagerError, Result};
+use crate::hd_key::ExtendedKey;
+use crate::network::Network;
+use crate::signing::{self, Signature};
+use crate::utils::secret::SecretArray;
+use crate::utils::{base58, bytes_to_hex, hex_to_bytes};
+use secp256k1::{Message, PublicKey, SecretKey, SECP256K1};
+use std::fmt;
+use zeroize::Zeroize;
+
+/// Draws before `generate` gives up on a source
+///
+/// A healthy source lands outside the valid range with probability about
+/// 2^-128 per draw, so a second failure already means the source is broken.
+const MAX_ATTEMPTS: usize = 4;
+
+/// A private key and its compressed public key
+#[derive(Clone)]
+pub struct Keypair {
+    private_key: SecretArray<32>,
+    public_key: [u8; 33],
+}
+
+// Only the public half, so a stray `{:?}` cannot leak the key
+impl fmt::Debug for Keypair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Keypair").field("public_key", &bytes_to_hex(&self.public_key)).finish_non_exhaustive()
+    }
+}
+
+impl Keypair {
+    /// Keypair for an existing 32-byte private key, range checked
+    pub fn from_private_key(private_key: &[u8; 32]) -> Result<Self> {
+        ExtendedKey::validate_private_key(private_key)?;
+        let secret_key = SecretKey::from_slice(private_key)
+            .map_err(|e| KeyManagerError::KeyGenerationError(format!("Invalid private key: {}", e)))?;
+        Ok(Keypair {
+            private_key: (*private_key).into(),
+            public_key: PublicKey::from_secret_key(SECP256K1, &secret_key).serialize(),
+        })
+    }
+
+    /// The raw 32-byte private key
+    pub fn private_key(&self) -> &[u8; 32] {
+        &self.private_key
+    }
+
+    /// The 33-byte compressed public key
+    pub fn public_key(&self) -> &[u8; 33] {
+        &self.public_key
+    }
+
+    /// Private key as compressed-key WIF for `network`
+    pub fn to_wif(&self, network: Network) -> String {
+        address::encode_wif(&self.private_key, network, true)
+    }
+
+    /// Native segwit (`bc1q...`) address for `network`
+    pub fn p2wpkh_address(&self, network: Network) -> Result<String> {
+        address::p2wpkh_from_pubkey(&self.public_key, network)
+    }
+
+    /// WIF and `script_type` address for `network`, as `address::wif_and_address` gives for an HD key
+    ///
+    /// With `compressed` false the WIF lacks the 0x01 flag and the address
+    /// hashes the uncompressed public key; only P2PKH exists in that form.
+    pub fn wif_and_address(&self, script_type: ScriptType, network: Network, compressed: bool) -> Result<(String, String)> {
+        let address = address::address_for_encoding(&self.public_key, script_type, network, compressed)?;
+        Ok((address::encode_wif(&self.private_key, network, compressed), address))
+    }
+
+    /// Sign a 32-byte digest (RFC 6979 nonce)
+    pub fn sign_ecdsa(&self, digest: &[u8; 32]) -> Result<Signature> {
+        Ok(signing::signing_context()?.sign_ecdsa(&Message::from_digest(*digest), &self.secret_key()?))
+    }
+
+    /// Sign a message the way Bitcoin Core's `signmessage` does
+    ///
+    /// `compressed` must match how the key was imported, since verifiers
+    /// check the signature against the matching P2PKH address.
+    pub fn sign_bitcoin_message(&self, message: &str, compressed: bool) -> Result<String> {
+        signing::sign_message_with(&self.secret_key()?, message, compressed)
+    }
+
+    fn secret_key(&self) -> Result<SecretKey> {
+        SecretKey::from_slice(&self.private_key[..])
+            .map_err(|e| KeyManagerError::KeyGenerationError(format!("Invalid private key: {}", e)))
+    }
+
+    /// EIP-55 checksummed Ethereum address (`0x...`)
+    #[cfg(feature = "ethereum")]
+    pub fn ethereum_address(&self) -> Result<String> {
+        let public_key = PublicKey::from_slice(&self.public_key)
+            .map_err(|e| KeyManagerError::Secp256k1Error(e.to_string()))?;
+        Ok(crate::ethereum::checksum_address(&crate::ethereum::address_bytes(&public_key)))
+    }
+}
+
+/// Keypair, network and compression flag from a WIF private key
+///
+/// The Base58Check checksum, version byte (mainnet 0x80, testnet and regtest
+/// 0xef) and length are all checked; a 34-byte payload must end in the 0x01
+/// compression flag.
+pub fn from_wif(wif: &str) -> Result<(Keypair, Network, bool)> {
+    let invalid = |reason: &str| KeyManagerError::EncodingError(format!("Invalid WIF: {}", reason));
+
+    let mut payload = base58::decode_check(wif.trim()).map_err(|_| invalid("bad base58 or checksum"))?;
+    let parsed = match payload.as_slice() {
+        [version, key @ ..] if key.len() == 32 => decode_wif_key(*version, key, false),
+        [version, key @ .., 0x01] if key.len() == 32 => decode_wif_key(*version, key, true),
+        [_, key @ .., _] if key.len() == 32 => Err(invalid("compression flag must be 0x01")),
+        _ => Err(invalid("wrong length")),
+    };
+    payload.zeroize();
+    parsed
+}
+
+/// Keypair for the 32-byte key of a WIF payload with the given version byte
+fn decode_wif_key(version: u8, key: &[u8], compressed: bool) -> Result<(Keypair, Network, bool)> {
+    let network = [Network::Mainnet, Network::Testnet]
+        .into_iter()
+        .find(|network| network.wif_version() == version)
+        .ok_or_else(|| KeyManagerError::EncodingError(format!("Invalid WIF: unknown version byte 0x{:02x}", version)))?;
+    let mut private_key = [0u8; 32];
+    private_key.copy_from_slice(key);
+    let keypair = Keypair::from_private_key(&private_key);
+    private_key.zeroize();
+    Ok((keypair?, network, compressed))
+}
+
+/// Keypair from 64 hex characters, with or without a `0x` prefix
+pub fn from_hex(hex: &str) -> Result<Keypair> {
+    let hex = hex.trim();
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    let mut bytes = hex_to_bytes(hex)
+        .map_err(|_| KeyManagerError::EncodingError("Private key must be hex".to_string()))?;
+    let keypair = match <[u8; 32]>::try_from(bytes.as_slice()) {
+        Ok(mut private_key) => {
+            let keypair = Keypair::from_private_key(&private_key);
+            private_key.zeroize();
+            keypair
+        }
+        Err(_) => Err(KeyManagerError::EncodingError(format!(
+            "Private key must be 32 bytes (64 hex characters), got {} bytes",
+            bytes.len()
+        ))),
+    };
+    bytes.zeroize();
+    keypair
+}
+
+/// Fresh keypair from `source`
+///
+/// Out-of-range draws (zero, or at least the curve order) are discarded and
+/// redrawn; a source that keeps producing them is reported as an error.
+pub fn generate(source: &mut impl EntropySource) -> Result<Keypair> {
+    let mut private_key = [0u8; 32];
+    for _ in 0..MAX_ATTEMPTS {
+        source.fill_bytes(&mut private_key)?;
+        let keypair = Keypair::from_private_key(&private_key);
+        private_key.zeroize();
+        if let Ok(keypair) = keypair {
+            return Ok(keypair);
+        }
+    }
+    Err(KeyManagerError::KeyGenerationError(format!(
+        "Entropy source produced no valid private key in {} attempts",
+        MAX_ATTEMPTS
+    )))
+}
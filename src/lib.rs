@@ -1,13 +1,62 @@
 pub mod error;
+pub mod entropy;
 pub mod mnemonic;
+pub mod mnemonic_type;
 pub mod utils;
 pub mod seed;
+pub mod passphrase;
 pub mod hd_key;
+pub mod path;
+pub mod xpub;
+pub mod decode;
+pub mod network;
+pub mod address;
+pub mod taproot;
+pub mod discovery;
+pub mod progress;
+pub mod lightning;
+pub mod signing;
+pub mod psbt;
+pub mod bip47;
+pub mod key_cache;
+pub mod versions;
+pub mod export;
+pub mod bip21;
+pub mod seedqr;
+pub mod descriptor;
+pub mod watch;
+pub mod keypair;
+pub mod bip85;
+
+#[cfg(feature = "monero")]
+pub mod monero;
+
+#[cfg(feature = "ethereum")]
+pub mod ethereum;
+
+#[cfg(feature = "pgp")]
+pub mod pgp;
+
+#[cfg(feature = "ssh")]
+pub mod ssh;
+
+#[cfg(feature = "age")]
+pub mod age;
+
+#[cfg(feature = "encryption")]
+pub mod backup;
 
 // Re-export commonly used types
 pub use error::{KeyManagerError, Result};
 pub use hd_key::ExtendedKey;
+pub use path::{DerivationPath, PathTemplate};
+pub use network::Network;
+pub use xpub::{ExtendedPublicKey, SlipVersion};
 pub use seed::mnemonic_to_seed;
+pub use mnemonic_type::{detect_mnemonic_type, MnemonicType};
+pub use passphrase::{passphrase_strength, PassphraseStrength, SecretPassphrase};
+#[cfg(feature = "locked-memory")]
+pub use utils::secret::memory_lock_failed;
 
 // Unit tests are in a separate module
 #[cfg(test)]
@@ -0,0 +1,74 @@
+//! lnd-compatible Lightning key derivation
+//!
+//! lnd derives every key it controls from the wallet's BIP32 root at
+//! `m/1017'/coin_type'/key_family'/0/index`, where the key family selects
+//! the key's role. The node identity key is family 6, index 0, so its
+//! public key is the node ID shown by `lncli getinfo`.
+//!
+//! lnd seeds the root from its aezeed cipher seed; pass the master key
+//! built from that seed.
+
+use crate::error::{KeyManagerError, Result};
+use crate::hd_key::{ExtendedKey, HARDENED_OFFSET};
+use crate::network::Network;
+use crate::path::DerivationPath;
+
+/// lnd's BIP43 purpose
+pub const LND_PURPOSE: u32 = 1017;
+
+/// 2-of-2 funding output keys
+pub const KEY_FAMILY_MULTI_SIG: u32 = 0;
+/// Revocation base points
+pub const KEY_FAMILY_REVOCATION_BASE: u32 = 1;
+/// HTLC base points
+pub const KEY_FAMILY_HTLC_BASE: u32 = 2;
+/// Payment base points
+pub const KEY_FAMILY_PAYMENT_BASE: u32 = 3;
+/// Delay base points
+pub const KEY_FAMILY_DELAY_BASE: u32 = 4;
+/// Revocation root for per-commitment secrets
+pub const KEY_FAMILY_REVOCATION_ROOT: u32 = 5;
+/// Node identity key
+pub const KEY_FAMILY_NODE_KEY: u32 = 6;
+/// Static channel backup encryption key
+pub const KEY_FAMILY_BASE_ENCRYPTION: u32 = 7;
+/// Watchtower client session keys
+pub const KEY_FAMILY_TOWER_SESSION: u32 = 8;
+/// Watchtower identity key
+pub const KEY_FAMILY_TOWER_ID: u32 = 9;
+
+/// lnd coin type: 0 on mainnet, 1 on testnet and regtest
+fn coin_type(network: Network) -> u32 {
+    match network {
+        Network::Mainnet => 0,
+        Network::Testnet | Network::Regtest => 1,
+    }
+}
+
+/// Path lnd uses for a key: `m/1017'/coin_type'/family'/0/index`
+pub fn key_path(network: Network, family: u32, index: u32) -> Result<DerivationPath> {
+    if family >= HARDENED_OFFSET || index >= HARDENED_OFFSET {
+        return Err(KeyManagerError::InvalidDerivationPath(format!(
+            "lnd key family {} / index {} out of range",
+            family, index
+        )));
+    }
+
+    Ok(DerivationPath::from_indices(vec![
+        LND_PURPOSE + HARDENED_OFFSET,
+        coin_type(network) + HARDENED_OFFSET,
+        family + HARDENED_OFFSET,
+        0,
+        index,
+    ]))
+}
+
+/// Derive the key lnd uses for `family` and `index`
+pub fn derive_key(master: &ExtendedKey, network: Network, family: u32, index: u32) -> Result<ExtendedKey> {
+    master.derive(&key_path(network, family, index)?)
+}
+
+/// Compressed node identity public key (the Lightning node ID)
+pub fn node_pubkey(master: &ExtendedKey, network: Network) -> Result<[u8; 33]> {
+    derive_key(master, network, KEY_FAMILY_NODE_KEY, 0)?.public_key()
+}
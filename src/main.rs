@@ -1,5 +1,13 @@
-use crypto_key_manager::{mnemonic, Result};
+mod cli;
+
+use cli::table::{json_string, Format, Table};
+use cli::Sensitivity;
+use crypto_key_manager::psbt::Psbt;
+use crypto_key_manager::utils::ec;
+use crypto_key_manager::versions::Visibility;
+use crypto_key_manager::{mnemonic, utils, ExtendedKey, ExtendedPublicKey, MnemonicType, Network, Result, SecretPassphrase};
 use std::env;
+use std::path::Path;
 
 
 fn print_usage() {
@@ -8,6 +16,11 @@ fn print_usage() {
     println!("  crypto-key-manager <command> [options]");
     println!("\nCommands:");
     println!("  generate [--words <12|15|18|21|24>]  Generate a new mnemonic phrase");
+    println!("    --entropy <hex>                 Use this entropy (32-64 hex digits) instead of the OS source");
+    println!("    --qr [--format seedqr|compact-seedqr]");
+    println!("                                    Also print the SeedQR payload (12/24 words; needs a terminal)");
+    println!("  generate-passphrase [--words 6]     Generate a random BIP39 passphrase (25th word)");
+    println!("    --allow-non-tty                 Print it even when stdout is not a terminal");
     println!("  validate <mnemonic>                   Validate a mnemonic phrase");
     println!("  help                                  Show this help message");
     println!("\nExamples:");
@@ -15,11 +28,505 @@ fn print_usage() {
     println!("  crypto-key-manager validate \"abandon ability able about above absent absorb abstract absurd abuse access accident\"");
     println!("\nNote: Current implementation uses basic validation.");
     println!("  seed <mnemonic> [passphrase]      Generate seed from mnemonic");
+    println!("    --out <file> [--force]          Write the seed to a new owner-only file instead");
+    println!("    --nonstandard-rounds <N>        Slower NON-BIP39 seed with N >= 2048 PBKDF2 rounds");
     println!("  derive <mnemonic> <path> [pass]   Derive key at BIP32 path (m/44'/0'/0'/0/0)");
+    println!("    --range <start-end>             List <path>/i addresses instead of one xprv");
+    println!("    --format text|csv|tsv|json      Output format for --range (default text)");
+    println!("    --columns path,address,pubkey   Select and order --range output columns");
+    println!("  wallet-new [--words 24] [--passphrase-prompt] [--testnet] [--json]");
+    println!("                                    New mnemonic with fingerprint, account xpubs and addresses");
+    println!("  children <mnemonic> <account-path> <start> <count> [pass] [--type p2wpkh]");
+    println!("                                    List receive addresses account-path/0/i");
+    println!("  vanity <mnemonic> <account-path> <prefix> [pass] [--type p2wpkh] [--max-index 1000000]");
+    println!("                                    First receive address starting with prefix (Ctrl-C stops)");
+    println!("  xpub <mnemonic> <path> [pass]     Show the extended public key at a path");
+    println!("  addresses --xpub <xpub|ypub|zpub> [--chain 0 | --path m/0] [--range 0-19] [--type auto]");
+    println!("    --format text|csv|tsv|json      Output format (default text)");
+    println!("    --columns path,address,pubkey   Select and order output columns");
+    println!("                                    Watch-only addresses, no secrets needed");
+    println!("  descriptor-addresses --descriptor <descriptor> [--range 0-9] [--format text|csv|tsv|json | --json]");
+    println!("                                    Index/address table (plus pubkeys for multisig)");
+    println!("  address <mnemonic> <path> [pass]  Show the address at a path (P2WPKH for m/84')");
+    println!("    --type p2pkh|p2sh-p2wpkh|p2wpkh|p2tr");
+    println!("                                    Address type (required below an --xprv account key)");
+    println!("    --uri [--amount <btc>] [--label <text>] [--message <text>]");
+    println!("                                    Print it as a BIP21 bitcoin: payment URI");
+    println!("  pubkey <mnemonic|xprv> <path> [pass]");
+    println!("                                    Show the public key, its hash160 and fingerprint");
+    println!("    --uncompressed | --x-only       Public key encoding (default compressed)");
+    println!("    --json                          Output as JSON");
+    println!("  recover-pubkey --message <text|@file> --signature <base64|hex> [--raw]");
+    println!("                                    Recover a message signer's key and its addresses");
+    println!("  fingerprint <mnemonic> [pass]     Show the master key fingerprint");
+    println!("  export <mnemonic> [pass] --format accounts [--account N] [--out <file> [--force]]");
+    println!("                                    Watch-only JSON for BIP44/49/84/86 of one account");
+    println!("  decode <string>                   Inspect a Base58Check, bech32 or hex string");
+    println!("  psbt sign <mnemonic> [pass] --in <file> [--out <file> [--force]]");
+    println!("    --xprv <master xprv>            Sign with a master xprv instead of the mnemonic");
+    println!("                                    Sign our P2WPKH/P2TR inputs of a PSBT (base64 or binary)");
+    println!("  keypair new [--coin btc|eth]      Standalone key with its WIF and P2WPKH address");
+    println!("                                    (or hex key and address for eth)");
+    println!("  import-key --wif <wif> | --hex <key>");
+    println!("                                    Addresses of a single imported private key");
+    println!("\nGlobal options:");
+    println!("  --network mainnet|testnet|regtest Key and address prefixes (default mainnet)");
+    println!("  --coin btc|ltc|doge               Extended key prefix for derive/xpub (e.g. Ltpv/Ltub)");
+    println!("  --xprv <xprv>                     Root for derive/address/pubkey instead of a mnemonic;");
+    println!("                                    the path is relative to this key");
+    println!("  --entropy-device <path>           Read generate/generate-passphrase/wallet-new entropy from this device");
+    println!("  --entropy-mix system,device       Mix the OS generator and the device through HMAC-SHA256");
+    println!("  --allow-non-tty                   Print secrets even when stdout is not a terminal");
+    #[cfg(feature = "monero")]
+    {
+        println!("  monero-key <seed>                 Decode a 25-word Monero seed to its spend key");
+        println!("  monero-seed <hex>                 Encode a Monero spend key as a 25-word seed");
+    }
+}
+
+/// Hint for phrases that fail BIP39 validation because they are Monero seeds
+#[cfg(feature = "monero")]
+fn monero_hint(phrase: &str) -> Option<&'static str> {
+    crypto_key_manager::monero::looks_like_monero_seed(phrase)
+        .then_some("This looks like a Monero seed, not BIP39. Use the monero-key command.")
+}
+
+#[cfg(not(feature = "monero"))]
+fn monero_hint(phrase: &str) -> Option<&'static str> {
+    (phrase.split_whitespace().count() == 25)
+        .then_some("25 words may be a Monero seed, not BIP39. Rebuild with --features monero to decode it.")
+}
+
+/// Recover the signer of a message and list the key with its P2PKH, P2WPKH and P2TR addresses
+///
+/// `message` is the text itself or `@file`; `signature` is base64 or hex.
+/// Without `raw` it is a `signmessage` signature, otherwise `r || s || recovery id`
+/// over the same signed-message digest.
+fn recover_signer(message: &str, signature: &str, raw: bool, network: Network) -> std::result::Result<String, String> {
+    use crypto_key_manager::address::{self, ScriptType};
+    use crypto_key_manager::signing;
+
+    let message = match message.strip_prefix('@') {
+        Some(path) => std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?,
+        None => message.to_string(),
+    };
+    let signature = signature.trim();
+    let signature = if signature.len() == 130 && signature.bytes().all(|b| b.is_ascii_hexdigit()) {
+        utils::hex_to_bytes(signature)
+    } else {
+        utils::base64::decode(signature)
+    }
+    .map_err(|e| e.to_string())?;
+
+    let recovered = if raw {
+        let public_key = signing::recover_raw_signer(&signing::bitcoin_message_hash(&message), &signature);
+        public_key.map(|public_key| signing::RecoveredKey { public_key, compressed: true })
+    } else {
+        signing::recover_bitcoin_message_signer(&message, &signature)
+    }
+    .map_err(|e| e.to_string())?;
+
+    let public_key = match recovered.compressed {
+        true => recovered.public_key.to_vec(),
+        false => ec::pubkey_uncompress(&recovered.public_key).map_err(|e| e.to_string())?.to_vec(),
+    };
+    let mut output = format!("pubkey: {}\n", utils::bytes_to_hex(&public_key));
+    output += &format!("p2pkh: {}\n", address::p2pkh_from_pubkey(&public_key, network));
+    for script_type in [ScriptType::P2wpkh, ScriptType::P2tr] {
+        let address = script_type.address_from_pubkey(&recovered.public_key, network).map_err(|e| e.to_string())?;
+        output += &format!("{}: {}\n", script_type.name(), address);
+    }
+    Ok(output)
+}
+
+/// Render `<path>/i` for every index in `range` as a path/address/pubkey table
+fn derive_range_table(
+    master: &ExtendedKey,
+    path: &str,
+    range: &str,
+    format: Option<&str>,
+    columns: Option<&str>,
+    script_type: Option<&str>,
+    network: Network,
+) -> std::result::Result<String, String> {
+    let (start, end) = cli::parse_range(range)?;
+    let format: Format = format.unwrap_or("text").parse()?;
+    let script_type = root_script_type(master, path, script_type)?;
+
+    // Siblings share the parent, whose public key is computed once
+    let parent = master.derive_path(path).map_err(|e| e.to_string())?;
+    let mut table = Table::new(&["path", "address", "pubkey"]);
+    for index in start..=end {
+        let child_path = format!("{}/{}", path.trim_end_matches('/'), index);
+        let key = parent.derive_path(&format!("m/{}", index)).map_err(|e| e.to_string())?;
+        let address = crypto_key_manager::address::address(&key, script_type, network).map_err(|e| e.to_string())?;
+        let pubkey = key.public_key().map_err(|e| e.to_string())?;
+        table.push_row(vec![child_path, address, utils::bytes_to_hex(&pubkey)]);
+    }
+
+    if let Some(columns) = columns {
+        table.select_columns(columns)?;
+    }
+    Ok(table.render(format))
+}
+
+/// Default last receive index `vanity` tries
+const VANITY_MAX_INDEX: u32 = 1_000_000;
+
+/// "path address" of the first receive address under `account_path` whose body starts with `prefix`
+///
+/// Progress goes to stderr and Ctrl-C stops the search with `Cancelled`.
+fn find_vanity_address(
+    master: &ExtendedKey,
+    account_path: &str,
+    prefix: &str,
+    script_type: Option<&str>,
+    max_index: Option<&str>,
+    network: Network,
+) -> std::result::Result<String, String> {
+    let script_type = match script_type {
+        Some(name) => cli::parse_script_type(name)?,
+        None => cli::script_type_for_path(account_path),
+    };
+    let max_index = match max_index {
+        Some(max_index) => max_index.parse().map_err(|_| format!("Invalid --max-index: {}", max_index))?,
+        None => VANITY_MAX_INDEX,
+    };
+
+    let account = master.derive_path(account_path).map_err(|e| e.to_string())?;
+    let progress = cli::progress::StderrProgress::new("addresses")?;
+    let found = crypto_key_manager::discovery::find_vanity_with_progress(
+        &account,
+        prefix,
+        script_type,
+        network,
+        max_index,
+        &progress,
+    );
+    drop(progress);
+    match found.map_err(|e| e.to_string())? {
+        Some((index, address)) => Ok(format!("{}/0/{} {}\n", account_path.trim_end_matches('/'), index, address)),
+        None => Err(format!("No receive address up to index {} starts with '{}'", max_index, prefix)),
+    }
+}
+
+/// Script type for addresses at `path` below `root`: `--type`, else the path's purpose
+///
+/// The purpose is only known when `root` is a master key; below an account
+/// `--xprv` the path is relative, so `--type` must be given.
+fn root_script_type(
+    root: &ExtendedKey,
+    path: &str,
+    script_type: Option<&str>,
+) -> std::result::Result<crypto_key_manager::address::ScriptType, String> {
+    match script_type {
+        Some(name) => cli::parse_script_type(name),
+        None if root.depth() == 0 => Ok(cli::script_type_for_path(path)),
+        None => Err(format!(
+            "The path is relative to a depth-{} key, so its address type is unknown; pass --type p2pkh|p2sh-p2wpkh|p2wpkh|p2tr",
+            root.depth()
+        )),
+    }
+}
+
+/// "index address" lines for `account_path/0/i`, `i` in `start..start + count`
+///
+/// Derivation below the account is public (CKDpub), so only the account key
+/// needs private derivation.
+fn list_children(
+    master: &ExtendedKey,
+    account_path: &str,
+    start: &str,
+    count: &str,
+    script_type: Option<&str>,
+    network: Network,
+) -> std::result::Result<String, String> {
+    let start: u32 = start.parse().map_err(|_| format!("Invalid start index: {}", start))?;
+    let count: u32 = count.parse().map_err(|_| format!("Invalid count: {}", count))?;
+    let script_type = match script_type {
+        Some(name) => cli::parse_script_type(name)?,
+        None => cli::script_type_for_path(account_path),
+    };
+
+    let account = master
+        .derive_path(account_path)
+        .and_then(|key| key.to_extended_public_key())
+        .map_err(|e| e.to_string())?;
+    let public_keys = account.derive_pub_range(false, start, count).map_err(|e| e.to_string())?;
+
+    let mut output = String::new();
+    for (index, public_key) in (start..).zip(&public_keys) {
+        let address = script_type.address_from_pubkey(public_key, network).map_err(|e| e.to_string())?;
+        output.push_str(&format!("{} {}\n", index, address));
+    }
+    Ok(output)
+}
+
+/// Watch-only `addresses` rows below an xpub: path, address and pubkey, public derivation only
+///
+/// The base is `--path` (relative to the xpub) or `m/<chain>`; `script_type`
+/// "auto" follows the SLIP-132 prefix. The network comes from the prefix,
+/// with `--network regtest` allowed for testnet keys.
+fn watch_only_addresses(
+    xpub: &str,
+    path: Option<&str>,
+    chain: Option<&str>,
+    range: &str,
+    script_type: &str,
+    network: Network,
+) -> std::result::Result<Table, String> {
+    let xpub: ExtendedPublicKey = xpub.parse().map_err(|e: crypto_key_manager::KeyManagerError| e.to_string())?;
+    let network = match (xpub.version().is_testnet(), network) {
+        (false, Network::Mainnet) => Network::Mainnet,
+        (false, network) => return Err(format!("A mainnet key cannot derive {} addresses", network.name())),
+        (true, Network::Mainnet) => Network::Testnet,
+        (true, network) => network,
+    };
+    let script_type = match script_type {
+        "auto" => xpub.version().script_type(),
+        name => cli::parse_script_type(name)?,
+    };
+    let base = match (path, chain) {
+        (Some(_), Some(_)) => return Err("--path and --chain cannot be combined".to_string()),
+        (Some(path), None) => path.to_string(),
+        (None, chain) => {
+            let chain = chain.unwrap_or("0");
+            let chain: u32 = chain.parse().map_err(|_| format!("Invalid chain: {}", chain))?;
+            format!("m/{}", chain)
+        }
+    };
+    let (start, end) = cli::parse_range(range)?;
+
+    let base = base.parse::<crypto_key_manager::DerivationPath>().map_err(|e| e.to_string())?;
+    let base_key = xpub.derive(&base).map_err(|e| e.to_string())?;
+    let mut table = Table::new(&["path", "address", "pubkey"]);
+    for index in start..=end {
+        let child = base_key.derive_child(index).map_err(|e| e.to_string())?;
+        let address = script_type.address_from_pubkey(child.public_key(), network).map_err(|e| e.to_string())?;
+        table.push_row(vec![
+            format!("{}/{}", base, index),
+            address,
+            utils::bytes_to_hex(child.public_key()),
+        ]);
+    }
+    Ok(table)
+}
+
+/// `descriptor-addresses` output: an index/address table, plus the script's pubkeys for multisig
+///
+/// Multisig pubkeys are listed in script (BIP67) order, comma-separated in
+/// one cell. A descriptor without a `#checksum` gets a warning on stderr
+/// with the checksummed form to use next time.
+fn descriptor_address_table(
+    descriptor: &str,
+    range: Option<&str>,
+    format: Option<&str>,
+    network: Network,
+) -> std::result::Result<String, String> {
+    let format: Format = format.unwrap_or("text").parse()?;
+    let parsed = crypto_key_manager::descriptor::parse(descriptor).map_err(|e| e.to_string())?;
+    if !descriptor.contains('#') {
+        let checksummed = crypto_key_manager::descriptor::with_checksum(descriptor.trim()).map_err(|e| e.to_string())?;
+        eprintln!("Warning: descriptor has no checksum; with its checksum it is:\n{}", checksummed);
+    }
+    let network = descriptor_network(&parsed, network);
+    let default_range = if parsed.is_ranged() { "0-19" } else { "0" };
+    let (start, end) = cli::parse_range(range.unwrap_or(default_range))?;
+
+    let multisig = matches!(parsed, crypto_key_manager::descriptor::Descriptor::WshSortedMulti { .. });
+    let mut table = Table::new(if multisig { &["index", "address", "pubkeys"] } else { &["index", "address"] });
+    for index in start..=end {
+        let address = parsed.address_at(index, network).map_err(|e| e.to_string())?;
+        let mut row = vec![index.to_string(), address];
+        if multisig {
+            let public_keys = parsed.public_keys_at(index, network).map_err(|e| e.to_string())?;
+            row.push(public_keys.iter().map(|key| utils::bytes_to_hex(key)).collect::<Vec<_>>().join(","));
+        }
+        table.push_row(row);
+    }
+    Ok(table.render(format))
+}
+
+/// `--network`, except that tpub keys select testnet when it was left at mainnet
+fn descriptor_network(descriptor: &crypto_key_manager::descriptor::Descriptor, network: Network) -> Network {
+    let testnet_key = descriptor.keys().iter().any(|key| {
+        matches!(&key.key, crypto_key_manager::descriptor::Key::Extended { xpub, .. } if xpub.version().is_testnet())
+    });
+    if testnet_key && network == Network::Mainnet { Network::Testnet } else { network }
+}
+
+/// `pubkey` output: the public key in the chosen encoding, its hash160 and the key fingerprint
+///
+/// The hash160 is taken over the key bytes as printed; the fingerprint is
+/// always BIP32's (hash160 of the compressed key).
+fn render_pubkey(key: &ExtendedKey, uncompressed: bool, x_only: bool, json: bool) -> std::result::Result<String, String> {
+    if uncompressed && x_only {
+        return Err("--uncompressed and --x-only cannot be combined".to_string());
+    }
+
+    let compressed = key.public_key().map_err(|e| e.to_string())?;
+    let (encoding, public_key) = if uncompressed {
+        ("uncompressed", ec::pubkey_uncompress(&compressed).map_err(|e| e.to_string())?.to_vec())
+    } else if x_only {
+        ("x-only", compressed[1..].to_vec())
+    } else {
+        ("compressed", compressed.to_vec())
+    };
+    let public_key_hex = utils::bytes_to_hex(&public_key);
+    let hash160 = utils::bytes_to_hex(&utils::hash160(&public_key));
+    let fingerprint = utils::bytes_to_hex(&key.fingerprint().map_err(|e| e.to_string())?);
+
+    Ok(if json {
+        format!(
+            "{{\"encoding\":{},\"pubkey\":{},\"hash160\":{},\"fingerprint\":{}}}\n",
+            json_string(encoding),
+            json_string(&public_key_hex),
+            json_string(&hash160),
+            json_string(&fingerprint)
+        )
+    } else {
+        format!("pubkey: {}\nhash160: {}\nfingerprint: {}\n", public_key_hex, hash160, fingerprint)
+    })
+}
+
+/// Root key and path from `<mnemonic> <path> [passphrase]` or `--xprv <key> <path>`
+///
+/// With `--xprv` the given key is the root and the path is relative to it,
+/// so `m/0/5` is two levels below that key. `positional_xprv` also accepts
+/// an xprv in place of the mnemonic. Returns `Ok(None)` when arguments are
+/// missing so the caller can print its usage.
+///
+/// The returned network is `network` (the `--network` option) if given,
+/// otherwise the xprv's own network, or mainnet for a mnemonic.
+fn derivation_root(
+    args: &mut Vec<String>,
+    positional_xprv: bool,
+    network: Option<Network>,
+) -> std::result::Result<Option<(ExtendedKey, String, Network)>, String> {
+    let Some(xprv) = cli::take_option(args, "--xprv")? else {
+        if args.len() < 4 {
+            return Ok(None);
+        }
+        let (root, network) = match ExtendedKey::from_str_with_network(&args[2]) {
+            Ok((root, key_network)) if positional_xprv => (root, xprv_network(key_network, network)?),
+            _ => {
+                let passphrase = args.get(4).map(|s| s.as_str()).unwrap_or("");
+                let master = crypto_key_manager::seed::generate_master_key_from_mnemonic(&args[2], passphrase)
+                    .map_err(|e| e.to_string())?;
+                (master, network.unwrap_or(Network::Mainnet))
+            }
+        };
+        return Ok(Some((root, args[3].clone(), network)));
+    };
+
+    if args.len() > 3 {
+        return Err("--xprv cannot be combined with a mnemonic or passphrase; pass only the path".to_string());
+    }
+    let Some(path) = args.get(2) else {
+        return Ok(None);
+    };
+    let (root, key_network) =
+        ExtendedKey::from_str_with_network(&xprv).map_err(|e| format!("Invalid --xprv key: {}", e))?;
+    Ok(Some((root, path.clone(), xprv_network(key_network, network)?)))
+}
+
+/// Master key for `psbt sign`: the mnemonic (args[3], passphrase args[4]) or `--xprv`
+///
+/// PSBT key origins start at the master fingerprint, so an `--xprv` must be
+/// a master key; an account xprv would never match any input.
+fn psbt_signing_root(args: &[String], xprv: Option<&str>) -> std::result::Result<ExtendedKey, String> {
+    let Some(xprv) = xprv else {
+        let passphrase = args.get(4).map(|s| s.as_str()).unwrap_or("");
+        return crypto_key_manager::seed::generate_master_key_from_mnemonic(&args[3], passphrase)
+            .map_err(|e| e.to_string());
+    };
+    if args.len() > 3 {
+        return Err("--xprv cannot be combined with a mnemonic or passphrase".to_string());
+    }
+    let (root, _) = ExtendedKey::from_str_with_network(xprv).map_err(|e| format!("Invalid --xprv key: {}", e))?;
+    if root.depth() != 0 {
+        return Err(format!(
+            "--xprv must be the master key (depth 0), got a depth {} key; PSBT key origins start at the master",
+            root.depth()
+        ));
+    }
+    Ok(root)
+}
+
+/// Network for an xprv: its own, or `--network` if that agrees with it
+///
+/// tprv keys are shared by testnet and regtest, so `--network regtest` is
+/// allowed for them.
+fn xprv_network(key_network: Network, requested: Option<Network>) -> std::result::Result<Network, String> {
+    match requested {
+        None => Ok(key_network),
+        Some(network) if (network == Network::Mainnet) == (key_network == Network::Mainnet) => Ok(network),
+        Some(network) => Err(format!(
+            "The key is a {} key, but --network is {}",
+            key_network.name(),
+            network.name()
+        )),
+    }
+}
+
+fn derive_from_mnemonic(mnemonic: &str, path: &str, passphrase: &str) -> ExtendedKey {
+    match crypto_key_manager::seed::generate_master_key_from_mnemonic(mnemonic, passphrase)
+        .and_then(|master| master.derive_path(path))
+    {
+        Ok(key) => key,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
 }
 
 fn main() -> Result<()> {
-    let args: Vec<String> = env::args().collect();
+    let mut args = match cli::utf8_args(env::args_os()) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Global options may appear anywhere, including before the command
+    let network_option = match cli::take_option(&mut args, "--network")
+        .and_then(|name| name.map(|name| cli::parse_network(&name)).transpose())
+    {
+        Ok(network) => network,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let network = network_option.unwrap_or(Network::Mainnet);
+
+    // `keypair new` takes its own coin names (btc|eth), so the raw value is kept for it
+    let coin_name = cli::take_option(&mut args, "--coin");
+    let keypair_command = args.get(1).is_some_and(|command| command == "keypair");
+    let coin = match coin_name.clone().and_then(|name| match name {
+        Some(name) if !keypair_command => cli::parse_coin(&name).map(Some),
+        _ => Ok(None),
+    }) {
+        Ok(coin) => coin,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let entropy = cli::take_option(&mut args, "--entropy-device").and_then(|device| {
+        let mix = cli::take_option(&mut args, "--entropy-mix")?;
+        cli::entropy_source(device.as_deref(), mix.as_deref())
+    });
+    let mut entropy = match entropy {
+        Ok(entropy) => entropy,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
 
     if args.len() < 2 {
         print_usage();
@@ -28,17 +535,83 @@ fn main() -> Result<()> {
 
     match args[1].as_str() {
         "generate" => {
+            let qr = cli::take_flag(&mut args, "--qr");
+            let allow_non_tty = cli::take_flag(&mut args, "--allow-non-tty");
+            let options = cli::take_option(&mut args, "--entropy").and_then(|supplied| {
+                let qr_format = match cli::take_option(&mut args, "--format")? {
+                    Some(_) if !qr => return Err("--format only applies together with --qr".to_string()),
+                    _ if !qr => None,
+                    None => Some("seedqr".to_string()),
+                    Some(format) if format == "seedqr" || format == "compact-seedqr" => Some(format),
+                    Some(format) => return Err(format!("Unknown QR format '{}' (expected seedqr or compact-seedqr)", format)),
+                };
+                Ok((supplied, qr_format))
+            });
+            let (supplied, qr_format) = match options {
+                Ok(options) => options,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            // A SeedQR payload is the whole seed in machine-readable form
+            if qr {
+                if let Err(e) = cli::guard_secret_output(allow_non_tty) {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
             let mut words = 12;
 
             // Parse --words flag if present
-            if args.len() > 3 && args[2] == "--words" {
+            let words_given = args.len() > 3 && args[2] == "--words";
+            if words_given {
                 words = args[3].parse().unwrap_or(12);
             }
+            // Refuse an unencodable word count before any phrase reaches stdout
+            if qr && supplied.is_none() {
+                if let Err(e) = crypto_key_manager::seedqr::check_word_count(words) {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
 
-            match mnemonic::generate_mnemonic(words) {
+            // Supplied entropy replaces the OS source; its length sets the word count
+            let generated = match supplied {
+                Some(hex) => mnemonic::mnemonic_from_entropy_hex(&hex).and_then(|phrase| {
+                    let produced = mnemonic::get_word_count(&phrase);
+                    if words_given && produced != words {
+                        return Err(crypto_key_manager::KeyManagerError::EncodingError(format!(
+                            "--entropy gives {} words but --words asked for {}",
+                            produced, words
+                        )));
+                    }
+                    if qr {
+                        crypto_key_manager::seedqr::check_word_count(produced)?;
+                    }
+                    words = produced;
+                    Ok(phrase)
+                }),
+                None => mnemonic::generate_mnemonic_from(words, &mut entropy),
+            };
+            match generated {
                 Ok(mnemonic_phrase) => {
                     println!("\nGenerated {}-word mnemonic:", words);
                     println!("{}", mnemonic_phrase);
+                    let payload = match qr_format.as_deref() {
+                        Some("compact-seedqr") => crypto_key_manager::seedqr::encode_compact(&mnemonic_phrase)
+                            .map(|bytes| Some(format!("CompactSeedQR (hex): {}", utils::bytes_to_hex(&bytes)))),
+                        Some(_) => crypto_key_manager::seedqr::encode(&mnemonic_phrase).map(|digits| Some(format!("SeedQR: {}", digits))),
+                        None => Ok(None),
+                    };
+                    match payload {
+                        Ok(Some(payload)) => println!("\n{}", payload),
+                        Ok(None) => {}
+                        Err(e) => {
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
                     println!("\n⚠️  IMPORTANT: Write this down and store it securely!");
                     println!("    This is a demo - use proper entropy in production.\n");
                 }
@@ -49,6 +622,42 @@ fn main() -> Result<()> {
             }
             Ok(())
         }
+        "generate-passphrase" => {
+            let allow_non_tty = cli::take_flag(&mut args, "--allow-non-tty");
+            let words = match cli::take_option(&mut args, "--words")
+                .and_then(|words| words.map_or(Ok(6), |w| w.parse::<usize>().map_err(|_| format!("Invalid word count: {}", w))))
+            {
+                Ok(words) => words,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            if let Err(e) = cli::guard_secret_output(allow_non_tty) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+
+            match mnemonic::generate_passphrase_from(words, ' ', &mut entropy) {
+                Ok(passphrase) => {
+                    println!("{}", passphrase);
+                    println!(
+                        "\nEntropy: ~{} bits ({} words x {} bits)",
+                        words * mnemonic::PASSPHRASE_BITS_PER_WORD,
+                        words,
+                        mnemonic::PASSPHRASE_BITS_PER_WORD
+                    );
+                    println!("\n⚠️  This is a BIP39 PASSPHRASE, not a seed phrase. It has no checksum and");
+                    println!("    cannot restore a wallet on its own; store it together with your mnemonic.");
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Ok(())
+        }
         "validate" => {
             if args.len() < 3 {
                 println!("Error: Mnemonic phrase required");
@@ -57,6 +666,11 @@ fn main() -> Result<()> {
             }
 
             let mnemonic_phrase = &args[2];
+
+            if !mnemonic::is_nfkd_normalized(mnemonic_phrase) {
+                eprintln!("⚠️  Warning: mnemonic is not NFKD-normalized; it will be normalized before use.");
+                eprintln!("    Visually identical phrases with different encodings can produce different seeds.");
+            }
             
             match mnemonic::validate_mnemonic(mnemonic_phrase) {
                 Ok(()) => {
@@ -66,6 +680,14 @@ fn main() -> Result<()> {
                 }
                 Err(e) => {
                     eprintln!("✗ Invalid mnemonic: {}", e);
+                    if let Some(hint) = monero_hint(mnemonic_phrase) {
+                        eprintln!("  {}", hint);
+                    }
+                    match crypto_key_manager::detect_mnemonic_type(mnemonic_phrase) {
+                        MnemonicType::Electrum => eprintln!("  This looks like an Electrum seed, which is not BIP39."),
+                        MnemonicType::Slip39Share => eprintln!("  This looks like a SLIP39 Shamir share, which is not BIP39."),
+                        _ => {}
+                    }
                     std::process::exit(1);
                 }
             }
@@ -74,17 +696,51 @@ fn main() -> Result<()> {
         // After the existing commands, add these:
 
 "seed" => {
+    let force = cli::take_flag(&mut args, "--force");
+    let options = cli::take_option(&mut args, "--nonstandard-rounds")
+        .and_then(|rounds| rounds.map(|r| r.parse::<u32>().map_err(|_| format!("Invalid round count: {}", r))).transpose())
+        .and_then(|rounds| Ok((rounds, cli::take_option(&mut args, "--out")?)));
+    let (rounds, out) = match options {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
     if args.len() < 3 {
         println!("Error: Mnemonic required");
         return Ok(());
     }
     let mnemonic_phrase = &args[2];
     let passphrase = args.get(3).map(|s| s.as_str()).unwrap_or("");
+
+    if !mnemonic::is_nfkd_normalized(mnemonic_phrase) || !mnemonic::is_nfkd_normalized(passphrase) {
+        eprintln!("⚠️  Warning: input is not NFKD-normalized; it will be normalized per BIP39.");
+        eprintln!("    Check that your backup uses the same encoding.");
+    }
     
-    match crypto_key_manager::seed::mnemonic_to_seed(mnemonic_phrase, passphrase) {
-        Ok(seed) => {
-            println!("Seed (hex): {}", hex::encode(seed));
+    let seed = match rounds {
+        Some(rounds) => {
+            eprintln!("⚠️  Warning: --nonstandard-rounds produces a NON-BIP39 seed.");
+            eprintln!("    No other wallet will restore these keys from the mnemonic alone;");
+            eprintln!("    back up the round count ({}) together with the phrase.", rounds);
+            crypto_key_manager::seed::mnemonic_to_seed_hardened(mnemonic_phrase, passphrase, rounds)
         }
+        None => crypto_key_manager::seed::mnemonic_to_seed(mnemonic_phrase, passphrase),
+    };
+
+    match seed {
+        Ok(seed) => match out {
+            Some(path) => {
+                let hex = utils::bytes_to_hex(&seed) + "\n";
+                if let Err(e) = cli::safe_write(Path::new(&path), hex.as_bytes(), Sensitivity::Secret, force) {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+                println!("Wrote seed to {}", path);
+            }
+            None => println!("Seed (hex): {}", utils::bytes_to_hex(&seed)),
+        },
         Err(e) => {
             eprintln!("Error: {}", e);
             std::process::exit(1);
@@ -93,26 +749,691 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-"derive" => {
-    if args.len() < 4 {
-        println!("Usage: crypto-key-manager derive <mnemonic> <path> [passphrase]");
-        return Ok(());
-    }
-    let mnemonic = &args[2];
-    let path = &args[3];
-    let passphrase = args.get(4).map(|s| s.as_str()).unwrap_or("");
-    
-    match crypto_key_manager::seed::generate_master_key_from_mnemonic(mnemonic, passphrase)
-        .and_then(|master| master.derive_path(path)) 
-    {
-        Ok(key) => println!("xprv: {}", key.to_string()),
-        Err(e) => {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
+        "derive" => {
+            let mut args = args.clone();
+            let options = cli::take_option(&mut args, "--range").and_then(|range| {
+                Ok((
+                    range,
+                    cli::take_option(&mut args, "--format")?,
+                    cli::take_option(&mut args, "--columns")?,
+                    cli::take_option(&mut args, "--type")?,
+                ))
+            });
+            let (range, format, columns, script_type) = match options {
+                Ok(options) => options,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let (master, path, network) = match derivation_root(&mut args, false, network_option) {
+                Ok(Some(root)) => root,
+                Ok(None) => {
+                    println!("Usage: crypto-key-manager derive <mnemonic> <path> [passphrase]");
+                    println!("       crypto-key-manager derive --xprv <xprv> <relative path>");
+                    println!("       [--range <start-end> [--format text|csv|tsv|json] [--columns path,address,pubkey]");
+                    println!("        [--type p2pkh|p2sh-p2wpkh|p2wpkh|p2tr]]");
+                    return Ok(());
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            let path = &path;
+
+            let Some(range) = range else {
+                let serialized = master.derive_path(path).map_err(|e| e.to_string()).and_then(|key| match coin {
+                    Some(coin) => {
+                        let version = cli::key_version_for_path(coin, path, network, Visibility::Private)?;
+                        let encoded = key.to_string_with_version(version).map_err(|e| e.to_string())?;
+                        Ok((version.name, encoded))
+                    }
+                    None => {
+                        let label = if network == Network::Mainnet { "xprv" } else { "tprv" };
+                        Ok((label, key.to_string_for_network(network)))
+                    }
+                });
+                match serialized {
+                    Ok((label, encoded)) => println!("{}: {}", label, encoded),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                return Ok(());
+            };
+
+            let table = derive_range_table(
+                &master,
+                path,
+                &range,
+                format.as_deref(),
+                columns.as_deref(),
+                script_type.as_deref(),
+                network,
+            );
+            match table {
+                Ok(output) => print!("{}", output),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Ok(())
+        }
+        "wallet-new" => {
+            let json = cli::take_flag(&mut args, "--json");
+            let prompt = cli::take_flag(&mut args, "--passphrase-prompt");
+            let allow_non_tty = cli::take_flag(&mut args, "--allow-non-tty");
+            let network = if cli::take_flag(&mut args, "--testnet") { Network::Testnet } else { network };
+            let words = match cli::take_option(&mut args, "--words")
+                .and_then(|words| words.map_or(Ok(24), |w| w.parse::<usize>().map_err(|_| format!("Invalid word count: {}", w))))
+            {
+                Ok(words) => words,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            if let Err(e) = cli::guard_secret_output(allow_non_tty) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+
+            // Moved straight into a SecretPassphrase, which wipes it on drop
+            let passphrase = if prompt {
+                match rpassword::prompt_password("BIP39 passphrase: ") {
+                    Ok(passphrase) => SecretPassphrase::new(passphrase),
+                    Err(e) => {
+                        eprintln!("Error reading passphrase: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                SecretPassphrase::default()
+            };
+
+            match mnemonic::generate_mnemonic_from(words, &mut entropy)
+                .and_then(|phrase| cli::wallet::summarize(&phrase, &passphrase, network))
+            {
+                Ok(summary) if json => print!("{}", summary.render_json()),
+                Ok(summary) => print!("{}", summary.render_text()),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Ok(())
+        }
+        "vanity" => {
+            let options = (|| -> std::result::Result<_, String> {
+                Ok((cli::take_option(&mut args, "--type")?, cli::take_option(&mut args, "--max-index")?))
+            })();
+            let (script_type, max_index) = match options {
+                Ok(options) => options,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            if args.len() < 5 {
+                println!("Usage: crypto-key-manager vanity <mnemonic> <account-path> <prefix> [passphrase]");
+                println!("       [--type p2pkh|p2sh-p2wpkh|p2wpkh|p2tr] [--max-index <N>]");
+                return Ok(());
+            }
+            let passphrase = args.get(5).map(|s| s.as_str()).unwrap_or("");
+
+            match crypto_key_manager::seed::generate_master_key_from_mnemonic(&args[2], passphrase)
+                .map_err(|e| e.to_string())
+                .and_then(|master| {
+                    find_vanity_address(&master, &args[3], &args[4], script_type.as_deref(), max_index.as_deref(), network)
+                }) {
+                Ok(output) => print!("{}", output),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Ok(())
+        }
+        "children" => {
+            let script_type = match cli::take_option(&mut args, "--type") {
+                Ok(script_type) => script_type,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            if args.len() < 6 {
+                println!("Usage: crypto-key-manager children <mnemonic> <account-path> <start> <count> [passphrase]");
+                println!("       [--type p2pkh|p2sh-p2wpkh|p2wpkh|p2tr]");
+                return Ok(());
+            }
+            let passphrase = args.get(6).map(|s| s.as_str()).unwrap_or("");
+
+            match crypto_key_manager::seed::generate_master_key_from_mnemonic(&args[2], passphrase)
+                .map_err(|e| e.to_string())
+                .and_then(|master| list_children(&master, &args[3], &args[4], &args[5], script_type.as_deref(), network))
+            {
+                Ok(output) => print!("{}", output),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Ok(())
+        }
+        "addresses" => {
+            let options = (|| -> std::result::Result<_, String> {
+                Ok((
+                    cli::take_option(&mut args, "--xpub")?,
+                    cli::take_flag(&mut args, "--descriptor"),
+                    cli::take_option(&mut args, "--path")?,
+                    cli::take_option(&mut args, "--chain")?,
+                    cli::take_option(&mut args, "--range")?,
+                    cli::take_option(&mut args, "--type")?,
+                    cli::take_option(&mut args, "--format")?,
+                    cli::take_option(&mut args, "--columns")?,
+                ))
+            })();
+            let (format, columns) = match &options {
+                Ok((.., format, columns)) => (format.clone(), columns.clone()),
+                Err(_) => (None, None),
+            };
+            let table = match options {
+                Ok((_, true, ..)) => Err("for descriptors use descriptor-addresses --descriptor".to_string()),
+                Ok((Some(xpub), false, path, chain, range, script_type, ..)) => watch_only_addresses(
+                    &xpub,
+                    path.as_deref(),
+                    chain.as_deref(),
+                    range.as_deref().unwrap_or("0-19"),
+                    script_type.as_deref().unwrap_or("auto"),
+                    network,
+                ),
+                Ok(_) => {
+                    println!("Usage: crypto-key-manager addresses --xpub <xpub|ypub|zpub> [--chain 0 | --path <relative path>]");
+                    println!("       [--range 0-19] [--type auto|p2pkh|p2sh-p2wpkh|p2wpkh|p2tr]");
+                    println!("       [--format text|csv|tsv|json] [--columns path,address,pubkey]");
+                    return Ok(());
+                }
+                Err(e) => Err(e),
+            };
+            let result = table.and_then(|mut table| {
+                let format: Format = format.as_deref().unwrap_or("text").parse()?;
+                if let Some(columns) = columns {
+                    table.select_columns(&columns)?;
+                }
+                Ok(table.render(format))
+            });
+
+            match result {
+                Ok(output) => print!("{}", output),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Ok(())
+        }
+        "descriptor-addresses" => {
+            let json = cli::take_flag(&mut args, "--json");
+            let options = (|| -> std::result::Result<_, String> {
+                Ok((
+                    cli::take_option(&mut args, "--descriptor")?,
+                    cli::take_option(&mut args, "--range")?,
+                    cli::take_option(&mut args, "--format")?,
+                ))
+            })();
+            let result = match options {
+                Ok((Some(_), _, Some(_))) if json => Err("--json and --format cannot be combined".to_string()),
+                Ok((Some(descriptor), range, format)) => descriptor_address_table(
+                    &descriptor,
+                    range.as_deref(),
+                    if json { Some("json") } else { format.as_deref() },
+                    network,
+                ),
+                Ok((None, ..)) => {
+                    println!("Usage: crypto-key-manager descriptor-addresses --descriptor '<descriptor>' [--range 0-9]");
+                    println!("       [--format text|csv|tsv|json | --json] [--network testnet]");
+                    return Ok(());
+                }
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(output) => print!("{}", output),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Ok(())
+        }
+        "xpub" => {
+            if args.len() < 4 {
+                println!("Usage: crypto-key-manager xpub <mnemonic> <path> [passphrase] [--network testnet]");
+                return Ok(());
+            }
+            let passphrase = args.get(4).map(|s| s.as_str()).unwrap_or("");
+            let key = derive_from_mnemonic(&args[2], &args[3], passphrase);
+
+            let serialized = ExtendedPublicKey::from_private(&key, network.xpub_version())
+                .map_err(|e| e.to_string())
+                .and_then(|xpub| match coin {
+                    Some(coin) => {
+                        let version = cli::key_version_for_path(coin, &args[3], network, Visibility::Public)?;
+                        xpub.to_string_with_version(version).map_err(|e| e.to_string())
+                    }
+                    None => Ok(xpub.to_string()),
+                });
+            match serialized {
+                Ok(xpub) => println!("{}", xpub),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Ok(())
+        }
+        "address" => {
+            let as_uri = cli::take_flag(&mut args, "--uri");
+            let script_type = match cli::take_option(&mut args, "--type") {
+                Ok(script_type) => script_type,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            let uri_options = (|| -> std::result::Result<_, String> {
+                let amount = cli::take_option(&mut args, "--amount")?;
+                let label = cli::take_option(&mut args, "--label")?;
+                let message = cli::take_option(&mut args, "--message")?;
+                if !as_uri && (amount.is_some() || label.is_some() || message.is_some()) {
+                    return Err("--amount, --label and --message require --uri".to_string());
+                }
+                let amount = amount
+                    .map(|amount| crypto_key_manager::bip21::parse_amount(&amount))
+                    .transpose()
+                    .map_err(|e| e.to_string())?;
+                Ok((amount, label, message))
+            })();
+            let (amount, label, message) = match uri_options {
+                Ok(options) => options,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            let (root, path, network) = match derivation_root(&mut args, false, network_option) {
+                Ok(Some(root)) => root,
+                Ok(None) => {
+                    println!("Usage: crypto-key-manager address <mnemonic> <path> [passphrase] [--network testnet]");
+                    println!("       crypto-key-manager address --xprv <xprv> <relative path> --type p2pkh|p2sh-p2wpkh|p2wpkh|p2tr");
+                    println!("       [--uri [--amount <btc>] [--label <text>] [--message <text>]]");
+                    return Ok(());
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let address = root_script_type(&root, &path, script_type.as_deref()).and_then(|script_type| {
+                root.derive_path(&path)
+                    .and_then(|key| crypto_key_manager::address::address(&key, script_type, network))
+                    .map_err(|e| e.to_string())
+            });
+            match address {
+                Ok(address) if as_uri => {
+                    let options = crypto_key_manager::bip21::Options {
+                        amount,
+                        label: label.as_deref(),
+                        message: message.as_deref(),
+                    };
+                    println!("{}", crypto_key_manager::bip21::uri(&address, options))
+                }
+                Ok(address) => println!("{}", address),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Ok(())
+        }
+        "pubkey" => {
+            let json = cli::take_flag(&mut args, "--json");
+            let uncompressed = cli::take_flag(&mut args, "--uncompressed");
+            let x_only = cli::take_flag(&mut args, "--x-only");
+            // An xprv is used as the root directly; anything else is a mnemonic
+            let (root, path, _network) = match derivation_root(&mut args, true, network_option) {
+                Ok(Some(root)) => root,
+                Ok(None) => {
+                    println!("Usage: crypto-key-manager pubkey <mnemonic|xprv> <path> [passphrase] [--uncompressed | --x-only] [--json]");
+                    println!("       crypto-key-manager pubkey --xprv <xprv> <relative path>");
+                    return Ok(());
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            match root
+                .derive_path(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|key| render_pubkey(&key, uncompressed, x_only, json))
+            {
+                Ok(output) => print!("{}", output),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Ok(())
+        }
+        "recover-pubkey" => {
+            let raw = cli::take_flag(&mut args, "--raw");
+            let options = cli::take_option(&mut args, "--message")
+                .and_then(|message| Ok((message, cli::take_option(&mut args, "--signature")?)));
+            let (message, signature) = match options {
+                Ok((Some(message), Some(signature))) => (message, signature),
+                Ok(_) => {
+                    println!("Usage: crypto-key-manager recover-pubkey --message <text|@file> --signature <base64|hex> [--raw]");
+                    return Ok(());
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            match recover_signer(&message, &signature, raw, network) {
+                Ok(output) => print!("{}", output),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Ok(())
+        }
+        "export" => {
+            let force = cli::take_flag(&mut args, "--force");
+            let options = cli::take_option(&mut args, "--format").and_then(|format| {
+                let account = cli::take_option(&mut args, "--account")?
+                    .map_or(Ok(0), |account| account.parse::<u32>().map_err(|_| format!("Invalid account: {}", account)))?;
+                Ok((format, account, cli::take_option(&mut args, "--out")?))
+            });
+            let (format, account, out) = match options {
+                Ok(options) => options,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            if args.len() < 3 || format.as_deref() != Some("accounts") {
+                println!("Usage: crypto-key-manager export <mnemonic> [passphrase] --format accounts [--account N] [--out <file> [--force]]");
+                return Ok(());
+            }
+            let passphrase = args.get(3).map(|s| s.as_str()).unwrap_or("");
+
+            match crypto_key_manager::seed::generate_master_key_from_mnemonic(&args[2], passphrase)
+                .and_then(|master| crypto_key_manager::export::accounts_json(&master, account, network))
+            {
+                Ok(json) => match out {
+                    Some(path) => {
+                        if let Err(e) = cli::safe_write(Path::new(&path), json.as_bytes(), Sensitivity::Public, force) {
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        }
+                        println!("Wrote {}", path);
+                    }
+                    None => print!("{}", json),
+                },
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Ok(())
+        }
+        "fingerprint" => {
+            if args.len() < 3 {
+                println!("Usage: crypto-key-manager fingerprint <mnemonic> [passphrase]");
+                return Ok(());
+            }
+            let mnemonic_phrase = &args[2];
+            let passphrase = args.get(3).map(|s| s.as_str()).unwrap_or("");
+
+            match mnemonic::validate_mnemonic(mnemonic_phrase)
+                .and_then(|_| crypto_key_manager::seed::master_fingerprint(mnemonic_phrase, passphrase))
+            {
+                Ok(fingerprint) => println!("{}", utils::bytes_to_hex(&fingerprint)),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Ok(())
+        }
+        "decode" => {
+            if args.len() < 3 {
+                println!("Usage: crypto-key-manager decode <string>");
+                return Ok(());
+            }
+
+            match crypto_key_manager::decode::decode_any(&args[2]) {
+                Ok(decoded) => {
+                    println!("Encoding: {:?}", decoded.encoding);
+                    match decoded.checksum_valid {
+                        Some(true) => println!("Checksum: valid"),
+                        Some(false) => println!("Checksum: INVALID"),
+                        None => println!("Checksum: n/a"),
+                    }
+                    println!("Bytes (hex): {}", utils::bytes_to_hex(&decoded.bytes));
+                    for hint in &decoded.hints {
+                        println!("  - {}", hint);
+                    }
+                    if decoded.checksum_valid == Some(false) {
+                        std::process::exit(1);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Ok(())
+        }
+        "psbt" => {
+            let force = cli::take_flag(&mut args, "--force");
+            let options = (|| -> std::result::Result<_, String> {
+                Ok((
+                    cli::take_option(&mut args, "--in")?,
+                    cli::take_option(&mut args, "--out")?,
+                    cli::take_option(&mut args, "--xprv")?,
+                ))
+            })();
+            let is_sign = args.get(2).map(|s| s.as_str()) == Some("sign");
+            let (input, output, xprv) = match options {
+                Ok((Some(input), output, xprv)) if is_sign && (xprv.is_some() || args.len() >= 4) => (input, output, xprv),
+                Ok(_) => {
+                    println!("Usage: crypto-key-manager psbt sign <mnemonic> [passphrase] --in <file> [--out <file> [--force]]");
+                    println!("   or: crypto-key-manager psbt sign --xprv <master xprv> --in <file> [--out <file> [--force]]");
+                    return Ok(());
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let signed = psbt_signing_root(&args, xprv.as_deref()).and_then(|master| {
+                let bytes = std::fs::read(&input).map_err(|e| format!("Cannot read {}: {}", input, e))?;
+                // Bitcoin Core and most wallets exchange PSBTs as base64 text
+                let psbt = match std::str::from_utf8(&bytes) {
+                    Ok(text) if text.trim_start().starts_with("cHNidP8") => Psbt::from_base64(text),
+                    _ => Psbt::from_bytes(&bytes),
+                };
+                let mut psbt = psbt.map_err(|e| e.to_string())?;
+                let report = psbt.sign(&master).map_err(|e| e.to_string())?;
+                Ok((psbt, report))
+            });
+            let (psbt, report) = match signed {
+                Ok(signed) => signed,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            for (index, reason) in &report.skipped {
+                eprintln!("Input {}: skipped ({})", index, reason);
+            }
+            match output {
+                Some(path) => {
+                    let base64 = psbt.to_base64() + "\n";
+                    if let Err(e) = cli::safe_write(Path::new(&path), base64.as_bytes(), Sensitivity::Public, force) {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                    println!("Signed {} of {} inputs; wrote {}", report.signed.len(), psbt.unsigned_tx().inputs.len(), path);
+                }
+                None => println!("{}", psbt.to_base64()),
+            }
+            if report.signed.is_empty() {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        "keypair" => {
+            let allow_non_tty = cli::take_flag(&mut args, "--allow-non-tty");
+            if args.get(2).map(|s| s.as_str()) != Some("new") {
+                println!("Usage: crypto-key-manager keypair new [--coin btc|eth] [--allow-non-tty]");
+                return Ok(());
+            }
+            let coin = match coin_name {
+                Ok(coin) => coin.unwrap_or_else(|| "btc".to_string()),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            if coin != "btc" && coin != "eth" {
+                eprintln!("Error: Unknown coin '{}' for keypair (expected btc or eth)", coin);
+                std::process::exit(1);
+            }
+            if let Err(e) = cli::guard_secret_output(allow_non_tty) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+
+            let keypair = match crypto_key_manager::keypair::generate(&mut entropy) {
+                Ok(keypair) => keypair,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            if coin == "eth" {
+                #[cfg(feature = "ethereum")]
+                {
+                    println!("Private key (hex): 0x{}", utils::bytes_to_hex(keypair.private_key()));
+                    println!("Address:           {}", keypair.ethereum_address()?);
+                }
+                #[cfg(not(feature = "ethereum"))]
+                {
+                    eprintln!("Error: Ethereum keypairs need a build with --features ethereum");
+                    std::process::exit(1);
+                }
+            } else {
+                println!("Private key (WIF): {}", keypair.to_wif(network));
+                println!("Public key:        {}", utils::bytes_to_hex(keypair.public_key()));
+                println!("Address:           {}", keypair.p2wpkh_address(network)?);
+            }
+            Ok(())
+        }
+        "import-key" => {
+            use crypto_key_manager::address::ScriptType;
+            use crypto_key_manager::keypair;
+
+            let options = cli::take_option(&mut args, "--wif")
+                .and_then(|wif| Ok((wif, cli::take_option(&mut args, "--hex")?)));
+            let imported = match options {
+                Ok((Some(wif), None)) => keypair::from_wif(&wif),
+                // Raw keys carry no network or encoding, so use --network and a compressed key
+                Ok((None, Some(hex))) => keypair::from_hex(&hex).map(|keypair| (keypair, network, true)),
+                Ok(_) => {
+                    println!("Usage: crypto-key-manager import-key --wif <wif> | --hex <64 hex chars> [--network testnet]");
+                    return Ok(());
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            let (keypair, network, compressed) = match imported {
+                Ok(imported) => imported,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            eprintln!("Warning: a single key is not an HD wallet; it has no mnemonic or xpub to back up or derive from.");
+            let public_key = if compressed {
+                keypair.public_key().to_vec()
+            } else {
+                ec::pubkey_uncompress(keypair.public_key())?.to_vec()
+            };
+            println!("Network:     {}", network.name());
+            println!("Compressed:  {}", if compressed { "yes" } else { "no" });
+            println!("Public key:  {}", utils::bytes_to_hex(&public_key));
+            for script_type in ScriptType::ALL {
+                // Only P2PKH has an uncompressed form
+                if let Ok((_, address)) = keypair.wif_and_address(script_type, network, compressed) {
+                    println!("{:<12} {}", format!("{}:", script_type.name()), address);
+                }
+            }
+            if !compressed {
+                println!("(segwit and taproot addresses need a compressed key)");
+            }
+            Ok(())
+        }
+        #[cfg(feature = "monero")]
+        "monero-key" => {
+            if args.len() < 3 {
+                println!("Usage: crypto-key-manager monero-key <25-word monero seed>");
+                return Ok(());
+            }
+
+            match crypto_key_manager::monero::seed_to_key(&args[2]) {
+                Ok(key) => println!("Private spend key (hex): {}", utils::bytes_to_hex(&key)),
+                Err(e) => {
+                    eprintln!("✗ Invalid Monero seed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Ok(())
+        }
+        #[cfg(feature = "monero")]
+        "monero-seed" => {
+            if args.len() < 3 {
+                println!("Usage: crypto-key-manager monero-seed <64-hex-char spend key>");
+                return Ok(());
+            }
+
+            let key: [u8; 32] = match utils::hex_to_bytes(&args[2]).map(<[u8; 32]>::try_from) {
+                Ok(Ok(key)) => key,
+                _ => {
+                    eprintln!("Error: spend key must be 32 bytes of hex");
+                    std::process::exit(1);
+                }
+            };
+            println!("{}", crypto_key_manager::monero::key_to_seed(&key));
+            Ok(())
         }
-    }
-        Ok(())
-    }
         "help" | "--help" | "-h" => {
             print_usage();
             Ok(())
@@ -1,12 +1,38 @@
+use crate::entropy::{self, EntropySource, SystemEntropy};
 use crate::error::{KeyManagerError, Result};
 use crate::utils;
+use crate::utils::secret::SecretText;
+use unicode_normalization::UnicodeNormalization;
+use zeroize::Zeroize;
 
 // BIP39 English wordlist (2048 words)
-const WORDLIST: [&str; 2048] = include!("wordlist.txt");
+pub(crate) static WORDLIST: [&str; 2048] = include!("wordlist.txt");
 
 /// Generate a BIP39 mnemonic phrase with the specified word count
 /// Now uses proper SHA256 checksums
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(word_count = word_count)))]
 pub fn generate_mnemonic(word_count: usize) -> Result<String> {
+    #[cfg(test)]
+    if let Some(entropy) = entropy::take_test_entropy() {
+        utils::validate_word_count(word_count)?;
+        if entropy.len() != word_count * 4 / 3 {
+            return Err(KeyManagerError::KeyGenerationError(format!(
+                "Test entropy is {} bytes; {} words need {}",
+                entropy.len(),
+                word_count,
+                word_count * 4 / 3
+            )));
+        }
+        return entropy_to_mnemonic_checked(&entropy);
+    }
+
+    generate_mnemonic_from(word_count, &mut SystemEntropy::new())
+}
+
+/// Generate a BIP39 mnemonic from a specific entropy source
+///
+/// The source must pass `entropy::health_check` before any bytes are used.
+pub fn generate_mnemonic_from(word_count: usize, source: &mut impl EntropySource) -> Result<String> {
     // Validate word count
     utils::validate_word_count(word_count)?;
 
@@ -21,17 +47,86 @@ pub fn generate_mnemonic(word_count: usize) -> Result<String> {
     };
     let entropy_bytes = entropy_bits / 8;
 
-    // Generate random entropy
-    let entropy = generate_entropy(entropy_bytes)?;
+    // Refuse obviously broken sources, then draw fresh entropy
+    entropy::health_check(source)?;
+    let mut entropy = vec![0u8; entropy_bytes];
+    source.fill_bytes(&mut entropy)?;
 
     // Convert entropy to mnemonic with proper SHA256 checksum
-    entropy_to_mnemonic_checked(&entropy)
+    let phrase = entropy_to_mnemonic_checked(&entropy)?;
+
+    // Cheap insurance against a bit-math bug silently dropping words
+    let produced = get_word_count(&phrase);
+    if produced != word_count {
+        return Err(KeyManagerError::KeyGenerationError(format!(
+            "Generated {} words but {} were requested",
+            produced, word_count
+        )));
+    }
+    Ok(phrase)
+}
+
+/// Mnemonic for caller-supplied entropy written as hex
+///
+/// For air-gapped and CI use, where the entropy comes from dice, a hardware
+/// generator or a fixture instead of the OS. The hex must encode 128, 160,
+/// 192, 224 or 256 bits (32 to 64 digits).
+pub fn mnemonic_from_entropy_hex(hex: &str) -> Result<String> {
+    let mut entropy = utils::hex_to_bytes(hex.trim())?;
+    if ![16, 20, 24, 28, 32].contains(&entropy.len()) {
+        let digits = entropy.len() * 2;
+        entropy.zeroize();
+        return Err(KeyManagerError::EncodingError(format!(
+            "Entropy must be 32, 40, 48, 56 or 64 hex digits, got {}",
+            digits
+        )));
+    }
+
+    let phrase = entropy_to_mnemonic_checked(&entropy);
+    entropy.zeroize();
+    phrase
+}
+
+/// Mnemonic phrase that is zeroized when dropped
+///
+/// `Debug` is redacted so the phrase doesn't end up in logs by accident.
+/// With the `locked-memory` feature the phrase is kept in mlock'ed pages.
+pub struct SecretMnemonic(SecretText);
+
+impl SecretMnemonic {
+    /// Take ownership of a phrase
+    pub fn new(phrase: String) -> Self {
+        SecretMnemonic(phrase.into())
+    }
+
+    /// The phrase itself
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Whether the phrase sits in page-locked memory
+    #[cfg(all(test, feature = "locked-memory"))]
+    pub(crate) fn is_locked(&self) -> bool {
+        self.0.is_locked()
+    }
+}
+
+impl From<String> for SecretMnemonic {
+    fn from(phrase: String) -> Self {
+        SecretMnemonic::new(phrase)
+    }
+}
+
+impl std::fmt::Debug for SecretMnemonic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "SecretMnemonic(<{} words>)", get_word_count(self.as_str()))
+    }
 }
 
 /// Validate a BIP39 mnemonic phrase
 /// Now includes proper SHA256 checksum validation
 pub fn validate_mnemonic(mnemonic: &str) -> Result<()> {
-    let words: Vec<&str> = mnemonic.trim().split_whitespace().collect();
+    let words: Vec<&str> = mnemonic.split_whitespace().collect();
     let word_count = words.len();
 
     // Check word count is valid
@@ -98,18 +193,27 @@ pub fn entropy_to_mnemonic_checked(entropy: &[u8]) -> Result<String> {
 }
 
 /// Convert mnemonic to entropy (reverse operation)
+///
+/// Unknown words are reported together (`InvalidWord`, with their 1-based
+/// positions but never the words themselves, which are often near-misses of
+/// the real seed words); a phrase of known words with a bad checksum is
+/// `InvalidMnemonic`. All words are processed before either error is returned.
 pub fn mnemonic_to_entropy(mnemonic: &str) -> Result<Vec<u8>> {
-    let words: Vec<&str> = mnemonic.trim().split_whitespace().collect();
+    let words: Vec<&str> = mnemonic.split_whitespace().collect();
     
     // Validate word count
     utils::validate_word_count(words.len())?;
 
-    // Convert words to indices
+    // Convert words to indices. Every word is looked up even after an
+    // unknown one, so the whole phrase is checked and every bad position
+    // reported; this is not constant time (the lookup is a binary search).
     let mut bits = Vec::new();
-    for word in &words {
-        let index = WORDLIST
-            .binary_search(word)
-            .map_err(|_| KeyManagerError::InvalidMnemonic)?;
+    let mut unknown = Vec::new();
+    for (position, word) in words.iter().enumerate() {
+        let index = WORDLIST.binary_search(word).unwrap_or_else(|_| {
+            unknown.push(position + 1);
+            0
+        });
         
         // Convert index to 11 bits
         for i in (0..11).rev() {
@@ -148,16 +252,40 @@ pub fn mnemonic_to_entropy(mnemonic: &str) -> Result<Vec<u8>> {
 
     // Compare the first checksum_bits of both
     let shift = 8 - checksum_bits;
-    if (calculated_checksum[0] >> shift) != (actual_checksum >> shift) {
+    let checksum_ok = utils::constant_time_eq(&[calculated_checksum[0] >> shift], &[actual_checksum >> shift]);
+
+    if !unknown.is_empty() {
+        entropy.zeroize();
+        let positions: Vec<String> = unknown.iter().map(|position| position.to_string()).collect();
+        let label = if positions.len() == 1 { "position" } else { "positions" };
+        return Err(KeyManagerError::InvalidWord(format!("unknown word at {} {}", label, positions.join(", "))));
+    }
+    if !checksum_ok {
+        entropy.zeroize();
         return Err(KeyManagerError::InvalidMnemonic);
     }
 
     Ok(entropy)
 }
 
+/// Entropy that regenerates `mnemonic` exactly via `entropy_to_mnemonic_checked`
+///
+/// For key ceremonies that record the entropy hex as the canonical backup.
+/// The checksum is verified, and the phrase is re-encoded from the returned
+/// entropy to confirm the round trip (words are compared after collapsing
+/// whitespace).
+pub fn entropy_for_mnemonic(mnemonic: &str) -> Result<Vec<u8>> {
+    let entropy = mnemonic_to_entropy(mnemonic)?;
+    let regenerated = entropy_to_mnemonic_checked(&entropy)?;
+    if !regenerated.split(' ').eq(mnemonic.split_whitespace()) {
+        return Err(KeyManagerError::InvalidMnemonic);
+    }
+    Ok(entropy)
+}
+
 /// Validate mnemonic SHA256 checksum
 pub fn validate_mnemonic_checksum(mnemonic: &str) -> Result<()> {
-    let words: Vec<&str> = mnemonic.trim().split_whitespace().collect();
+    let words: Vec<&str> = mnemonic.split_whitespace().collect();
     
     // Validate word count
     utils::validate_word_count(words.len())?;
@@ -178,9 +306,42 @@ pub fn is_valid_bip39_word(word: &str) -> bool {
     is_valid_word(word)
 }
 
+/// Check whether a mnemonic (or passphrase) is already in Unicode NFKD form
+///
+/// BIP39 hashes the NFKD form of the phrase, so two visually identical inputs
+/// with different encodings (precomposed "é" vs "e" + combining accent) only
+/// produce the same seed once normalized. Input that is not already NFKD is a
+/// sign the backup was transcribed by a tool that re-encoded it.
+pub fn is_nfkd_normalized(mnemonic: &str) -> bool {
+    unicode_normalization::is_nfkd(mnemonic)
+}
+
+/// Convert a mnemonic (or passphrase) to Unicode NFKD form
+pub fn normalize_nfkd(mnemonic: &str) -> String {
+    mnemonic.nfkd().collect()
+}
+
 /// Get word count from mnemonic phrase
 pub fn get_word_count(mnemonic: &str) -> usize {
-    mnemonic.trim().split_whitespace().count()
+    mnemonic.split_whitespace().count()
+}
+
+/// 1-based position of the first word where two phrases differ
+///
+/// `None` if they have the same words (extra whitespace is ignored). If
+/// one phrase is a prefix of the other, the position is the first word
+/// past the shorter one. Words are compared as written; no validation.
+pub fn first_divergence(a: &str, b: &str) -> Option<usize> {
+    let mut a = a.split_whitespace();
+    let mut b = b.split_whitespace();
+    for position in 1.. {
+        match (a.next(), b.next()) {
+            (None, None) => return None,
+            (x, y) if x != y => return Some(position),
+            _ => {}
+        }
+    }
+    unreachable!("a phrase cannot have more than usize::MAX words")
 }
 
 /// Get the size of the BIP39 wordlist
@@ -188,24 +349,114 @@ pub fn wordlist_size() -> usize {
     WORDLIST.len()
 }
 
-// ============================================================================
-// Internal helper functions
-// ============================================================================
+/// Byte-wise XOR of two mnemonics' entropy, for 2-of-2 XOR backups
+///
+/// Splitting a mnemonic `m` into a random share `r` and `xor(m, r)` means
+/// both shares are needed to recover it. This is NOT Shamir secret sharing:
+/// there is no threshold and losing either share loses the wallet. Both
+/// phrases must be valid and encode the same amount of entropy.
+pub fn xor_entropy(a: &str, b: &str) -> Result<Vec<u8>> {
+    let a = mnemonic_to_entropy(a)?;
+    let b = mnemonic_to_entropy(b)?;
+    if a.len() != b.len() {
+        return Err(KeyManagerError::KeyGenerationError(format!(
+            "Cannot XOR mnemonics of different lengths ({} and {} bits of entropy)",
+            a.len() * 8,
+            b.len() * 8
+        )));
+    }
+    Ok(a.iter().zip(&b).map(|(x, y)| x ^ y).collect())
+}
 
-/// Generate cryptographically secure random entropy
-fn generate_entropy(bytes: usize) -> Result<Vec<u8>> {
-    use std::fs::File;
-    use std::io::Read;
+/// Bits of entropy each word of `generate_passphrase` contributes (log2 2048)
+pub const PASSPHRASE_BITS_PER_WORD: usize = 11;
 
-    let mut entropy = vec![0u8; bytes];
-    let mut file = File::open("/dev/urandom")
-        .map_err(|e| KeyManagerError::KeyGenerationError(e.to_string()))?;
-    file.read_exact(&mut entropy)
-        .map_err(|e| KeyManagerError::KeyGenerationError(e.to_string()))?;
+/// Symbols used by `generate_passphrase_chars`, chosen to survive shells and keyboards
+const PASSPHRASE_SYMBOLS: &[u8] = b"!#%+-=?@_~";
 
-    Ok(entropy)
+/// Generate a random BIP39 *passphrase* (the "25th word") of `words` words
+///
+/// Each word is drawn uniformly from the 2048-word BIP39 list, giving
+/// about 11 bits of entropy per word: 6 words are ~66 bits, 8 are ~88. The
+/// result is a passphrase, not a seed phrase; it has no checksum and must be
+/// stored alongside the mnemonic it protects.
+pub fn generate_passphrase(words: usize, separator: char) -> Result<String> {
+    generate_passphrase_from(words, separator, &mut SystemEntropy::new())
+}
+
+/// Generate a BIP39 passphrase from a specific entropy source
+///
+/// The source must pass `entropy::health_check` before any bytes are used.
+pub fn generate_passphrase_from(words: usize, separator: char, source: &mut impl EntropySource) -> Result<String> {
+    if words == 0 {
+        return Err(KeyManagerError::KeyGenerationError(
+            "Passphrase needs at least one word".to_string(),
+        ));
+    }
+
+    entropy::health_check(source)?;
+    let mut random = vec![0u8; words * 2];
+    source.fill_bytes(&mut random)?;
+    // 65536 is a multiple of 2048, so masking 16 random bits is unbiased
+    let phrase = random
+        .chunks_exact(2)
+        .map(|pair| WORDLIST[(u16::from_be_bytes([pair[0], pair[1]]) & 0x07ff) as usize])
+        .collect::<Vec<_>>()
+        .join(&separator.to_string());
+    random.fill(0);
+    Ok(phrase)
+}
+
+/// Generate a random passphrase of `len` ASCII characters
+///
+/// Characters come from lowercase, uppercase, digits and a small symbol set
+/// (72 in total, ~6.2 bits each), and every class appears at least once, so
+/// `len` must be 4 or more.
+pub fn generate_passphrase_chars(len: usize) -> Result<String> {
+    if len < 4 {
+        return Err(KeyManagerError::KeyGenerationError(
+            "Character passphrase needs at least 4 characters (one per class)".to_string(),
+        ));
+    }
+
+    let alphabet: Vec<u8> = (b'a'..=b'z')
+        .chain(b'A'..=b'Z')
+        .chain(b'0'..=b'9')
+        .chain(PASSPHRASE_SYMBOLS.iter().copied())
+        .collect();
+    // Largest multiple of the alphabet size that fits a byte; higher bytes are rejected
+    let limit = 256 - 256 % alphabet.len();
+
+    loop {
+        let mut passphrase = String::with_capacity(len);
+        let mut random = [0u8; 64];
+        while passphrase.len() < len {
+            SystemEntropy::new().fill_bytes(&mut random)?;
+            for &byte in random.iter().filter(|&&byte| (byte as usize) < limit) {
+                if passphrase.len() == len {
+                    break;
+                }
+                passphrase.push(alphabet[byte as usize % alphabet.len()] as char);
+            }
+        }
+        random.fill(0);
+
+        let bytes = passphrase.as_bytes();
+        if bytes.iter().any(u8::is_ascii_lowercase)
+            && bytes.iter().any(u8::is_ascii_uppercase)
+            && bytes.iter().any(u8::is_ascii_digit)
+            && bytes.iter().any(|b| PASSPHRASE_SYMBOLS.contains(b))
+        {
+            return Ok(passphrase);
+        }
+        utils::zeroize_string(&mut passphrase);
+    }
 }
 
+// ============================================================================
+// Internal helper functions
+// ============================================================================
+
 /// Calculate SHA256 checksum of entropy (BIP39 compliant)
 fn calculate_sha256_checksum(entropy: &[u8]) -> Vec<u8> {
     use sha2::{Sha256, Digest};
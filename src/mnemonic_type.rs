@@ -0,0 +1,134 @@
+//! Detect which mnemonic scheme a phrase belongs to (BIP39, Electrum, SLIP39)
+//!
+//! The schemes overlap: Electrum "new" seeds are drawn from the BIP39 English
+//! list, and any 12-word phrase has a 1 in 16 chance of passing the BIP39
+//! checksum. Detection therefore relies on each scheme's own integrity check
+//! (BIP39 checksum, Electrum version prefix, SLIP39 RS1024 checksum) rather
+//! than on word membership alone.
+
+use crate::mnemonic::{self, normalize_nfkd};
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use unicode_normalization::char::is_combining_mark;
+
+// SLIP39 wordlist (1024 words, unique 4-letter prefixes)
+static SLIP39_WORDLIST: [&str; 1024] = include!("slip39_wordlist.txt");
+
+/// Mnemonic scheme a phrase was recognized as
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MnemonicType {
+    /// BIP39 phrase with a valid checksum
+    Bip39,
+    /// Electrum 2.x+ seed (standard, segwit or 2FA version prefix)
+    Electrum,
+    /// One share of a SLIP39 Shamir backup
+    Slip39Share,
+    /// None of the above
+    Unknown,
+}
+
+/// Electrum seed version prefixes (hex of HMAC-SHA512("Seed version", seed))
+const ELECTRUM_PREFIXES: [&str; 4] = [
+    "01",  // standard
+    "100", // segwit
+    "101", // 2fa
+    "102", // 2fa segwit
+];
+
+/// Minimum SLIP39 share length: 4 metadata + 13 value (128-bit secret) + 3 checksum words
+const SLIP39_MIN_WORDS: usize = 20;
+
+/// Classify a mnemonic phrase so it can be routed to the right decoder
+///
+/// Checks run in a fixed priority order and the first match wins:
+///
+/// 1. SLIP39 share: 20+ words from the SLIP39 list with a valid RS1024
+///    checksum. This list shares few words with BIP39 and a random phrase
+///    passes the 30-bit checksum with negligible probability.
+/// 2. BIP39: a valid BIP39 word count, every word in the English list and
+///    a valid checksum.
+/// 3. Electrum: the HMAC-SHA512 of the normalized phrase starts with a
+///    known seed version prefix.
+///
+/// A phrase valid as both BIP39 and Electrum is reported as `Bip39`, the
+/// native format of this crate. Use `is_electrum_seed` to check for that
+/// case explicitly before importing.
+pub fn detect_mnemonic_type(phrase: &str) -> MnemonicType {
+    if is_slip39_share(phrase) {
+        MnemonicType::Slip39Share
+    } else if mnemonic::validate_mnemonic(&normalize_nfkd(phrase).to_lowercase()).is_ok() {
+        MnemonicType::Bip39
+    } else if is_electrum_seed(phrase) {
+        MnemonicType::Electrum
+    } else {
+        MnemonicType::Unknown
+    }
+}
+
+/// Whether the phrase carries an Electrum 2.x+ seed version prefix
+pub fn is_electrum_seed(phrase: &str) -> bool {
+    let normalized = electrum_normalize(phrase);
+    if normalized.is_empty() {
+        return false;
+    }
+
+    let mut mac = Hmac::<Sha512>::new_from_slice(b"Seed version").expect("HMAC accepts any key length");
+    mac.update(normalized.as_bytes());
+    let digest = crate::utils::bytes_to_hex(&mac.finalize().into_bytes()[..2]);
+    ELECTRUM_PREFIXES.iter().any(|prefix| digest.starts_with(prefix))
+}
+
+/// Whether the phrase is a SLIP39 share with a valid RS1024 checksum
+pub fn is_slip39_share(phrase: &str) -> bool {
+    let indices: Option<Vec<u16>> = phrase
+        .split_whitespace()
+        .map(|word| {
+            let word = word.to_lowercase();
+            SLIP39_WORDLIST.iter().position(|w| *w == word).map(|i| i as u16)
+        })
+        .collect();
+
+    match indices {
+        Some(indices) if indices.len() >= SLIP39_MIN_WORDS => {
+            // The extendable flag (bit 15 of the share) selects the customization string
+            let customization: &[u8] = if (indices[1] >> 4) & 1 == 1 {
+                b"shamir_extendable"
+            } else {
+                b"shamir"
+            };
+            rs1024_polymod(customization.iter().map(|&b| b as u16).chain(indices)) == 1
+        }
+        _ => false,
+    }
+}
+
+/// SLIP39 Reed-Solomon checksum over GF(1024)
+fn rs1024_polymod(values: impl Iterator<Item = u16>) -> u32 {
+    const GEN: [u32; 10] = [
+        0xE0E040, 0x1C1C080, 0x3838100, 0x7070200, 0xE0E0009,
+        0x1C0C2412, 0x38086C24, 0x3090FC48, 0x21B1F890, 0x3F3F120,
+    ];
+
+    let mut chk: u32 = 1;
+    for value in values {
+        let b = chk >> 20;
+        chk = ((chk & 0xFFFFF) << 10) ^ value as u32;
+        for (i, generator) in GEN.iter().enumerate() {
+            if (b >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+    chk
+}
+
+/// Electrum's seed normalization: NFKD, lowercase, strip accents, collapse whitespace
+///
+/// Electrum's extra rule for removing spaces between CJK characters is not applied.
+fn electrum_normalize(phrase: &str) -> String {
+    let stripped: String = normalize_nfkd(&phrase.to_lowercase())
+        .chars()
+        .filter(|c| !is_combining_mark(*c))
+        .collect();
+    stripped.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,111 @@
+//! Monero 25-word seeds (legacy "Electrum-style" mnemonic, English list)
+//!
+//! These are NOT BIP39 mnemonics: a Monero seed encodes a 32-byte private
+//! spend key directly (no PBKDF2, no BIP32) using a 1626-word list, 24 data
+//! words and a CRC32-derived checksum word. Keep them away from the BIP39 APIs.
+
+use crate::error::{KeyManagerError, Result};
+
+// Monero English wordlist (1626 words)
+static WORDLIST: [&str; 1626] = include!("monero_wordlist.txt");
+
+/// Number of words in a Monero seed (24 data words + 1 checksum word)
+pub const SEED_WORD_COUNT: usize = 25;
+
+/// Words are identified by their first 3 characters in the English list
+const UNIQUE_PREFIX_LEN: usize = 3;
+
+/// Decode a 25-word Monero seed into its 32-byte private spend key
+pub fn seed_to_key(phrase: &str) -> Result<[u8; 32]> {
+    validate_checksum_word(phrase)?;
+
+    let indices = word_indices(phrase)?;
+    let n = WORDLIST.len() as u64;
+    let mut key = [0u8; 32];
+
+    // Every 3 words encode one little-endian 4-byte group
+    for (group, chunk) in indices[..24].chunks(3).enumerate() {
+        let (w1, w2, w3) = (chunk[0] as u64, chunk[1] as u64, chunk[2] as u64);
+        let value = w1 + n * ((n - w1 + w2) % n) + n * n * ((n - w2 + w3) % n);
+        if value % n != w1 || value > u32::MAX as u64 {
+            return Err(KeyManagerError::InvalidMnemonic);
+        }
+        key[group * 4..group * 4 + 4].copy_from_slice(&(value as u32).to_le_bytes());
+    }
+
+    Ok(key)
+}
+
+/// Encode a 32-byte private spend key as a 25-word Monero seed
+pub fn key_to_seed(key: &[u8; 32]) -> String {
+    let n = WORDLIST.len() as u32;
+    let mut words = Vec::with_capacity(SEED_WORD_COUNT);
+
+    for group in key.chunks(4) {
+        let value = u32::from_le_bytes([group[0], group[1], group[2], group[3]]);
+        let w1 = value % n;
+        let w2 = (value / n + w1) % n;
+        let w3 = (value / n / n + w2) % n;
+        words.push(WORDLIST[w1 as usize]);
+        words.push(WORDLIST[w2 as usize]);
+        words.push(WORDLIST[w3 as usize]);
+    }
+
+    words.push(words[checksum_index(&words)]);
+    words.join(" ")
+}
+
+/// Verify the 25th (checksum) word of a Monero seed
+pub fn validate_checksum_word(phrase: &str) -> Result<()> {
+    let words: Vec<&str> = phrase.split_whitespace().collect();
+    if words.len() != SEED_WORD_COUNT {
+        return Err(KeyManagerError::InvalidWordCount(words.len()));
+    }
+    if let Some(word) = words.iter().find(|w| !is_monero_word(w)) {
+        return Err(KeyManagerError::InvalidWord(word.to_string()));
+    }
+
+    let expected = words[checksum_index(&words[..24])];
+    if prefix(expected) != prefix(words[24]) {
+        return Err(KeyManagerError::InvalidMnemonic);
+    }
+    Ok(())
+}
+
+/// Heuristic: 25 words that all come from the Monero list
+///
+/// Used to point users at the Monero commands when a phrase fails BIP39
+/// validation. Does not check the checksum word.
+pub fn looks_like_monero_seed(phrase: &str) -> bool {
+    let words: Vec<&str> = phrase.split_whitespace().collect();
+    words.len() == SEED_WORD_COUNT && words.iter().all(|w| is_monero_word(w))
+}
+
+/// Check if a word is in the Monero English wordlist
+pub fn is_monero_word(word: &str) -> bool {
+    WORDLIST.binary_search(&word).is_ok()
+}
+
+fn word_indices(phrase: &str) -> Result<Vec<usize>> {
+    phrase
+        .split_whitespace()
+        .map(|word| {
+            WORDLIST
+                .binary_search(&word)
+                .map_err(|_| KeyManagerError::InvalidWord(word.to_string()))
+        })
+        .collect()
+}
+
+fn prefix(word: &str) -> &str {
+    match word.char_indices().nth(UNIQUE_PREFIX_LEN) {
+        Some((end, _)) => &word[..end],
+        None => word,
+    }
+}
+
+/// Index of the checksum word: CRC32 of the concatenated word prefixes
+fn checksum_index(words: &[&str]) -> usize {
+    let trimmed: String = words.iter().map(|w| prefix(w)).collect();
+    crc32fast::hash(trimmed.as_bytes()) as usize % words.len()
+}
@@ -0,0 +1,79 @@
+use crate::xpub::SlipVersion;
+
+/// Bitcoin network, selecting address and key-serialization prefixes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Testnet,
+    /// Local `bitcoind -regtest`: testnet version bytes, `bcrt` bech32 prefix
+    Regtest,
+}
+
+impl Network {
+    /// Lowercase name, as accepted by `--network`
+    pub fn name(self) -> &'static str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Testnet => "testnet",
+            Network::Regtest => "regtest",
+        }
+    }
+
+    /// BIP44 coin type for account paths: 0 on mainnet, 1 on test networks
+    pub fn coin_type(self) -> u32 {
+        match self {
+            Network::Mainnet => 0,
+            Network::Testnet | Network::Regtest => 1,
+        }
+    }
+
+    /// Base58Check version byte for P2PKH addresses
+    pub fn p2pkh_version(self) -> u8 {
+        match self {
+            Network::Mainnet => 0x00,
+            Network::Testnet | Network::Regtest => 0x6f,
+        }
+    }
+
+    /// Base58Check version byte for P2SH addresses
+    pub fn p2sh_version(self) -> u8 {
+        match self {
+            Network::Mainnet => 0x05,
+            Network::Testnet | Network::Regtest => 0xc4,
+        }
+    }
+
+    /// Base58Check version byte for WIF private keys
+    pub fn wif_version(self) -> u8 {
+        match self {
+            Network::Mainnet => 0x80,
+            Network::Testnet | Network::Regtest => 0xef,
+        }
+    }
+
+    /// Human-readable part for bech32 segwit addresses
+    pub fn bech32_hrp(self) -> &'static str {
+        match self {
+            Network::Mainnet => "bc",
+            Network::Testnet => "tb",
+            Network::Regtest => "bcrt",
+        }
+    }
+
+    /// BIP32 version bytes for serialized extended private keys (xprv/tprv)
+    pub fn xprv_version(self) -> [u8; 4] {
+        match self {
+            Network::Mainnet => [0x04, 0x88, 0xAD, 0xE4],
+            Network::Testnet | Network::Regtest => [0x04, 0x35, 0x83, 0x94],
+        }
+    }
+
+    /// Plain BIP32 extended public key version (xpub/tpub)
+    pub fn xpub_version(self) -> SlipVersion {
+        match self {
+            Network::Mainnet => SlipVersion::Xpub,
+            Network::Testnet | Network::Regtest => SlipVersion::Tpub,
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! BIP39 passphrases ("25th word"): secret storage and strength scoring
+//!
+//! The strength score is a rough heuristic for warning users, not an
+//! entropy estimate: it looks at length and how many character classes
+//! appear. It never rejects a passphrase; any string is a valid BIP39
+//! passphrase.
+
+use crate::utils::secret::SecretText;
+use std::fmt;
+
+/// BIP39 passphrase that is zeroized on drop and redacted in `Debug`
+///
+/// Accepted anywhere a passphrase is (`impl AsRef<str>`), so it can be
+/// passed to `mnemonic_to_seed` without copying the plaintext into an
+/// ordinary `String`. With the `locked-memory` feature it is also kept in
+/// mlock'ed pages.
+pub struct SecretPassphrase(SecretText);
+
+impl SecretPassphrase {
+    /// Take ownership of a passphrase
+    pub fn new(passphrase: String) -> Self {
+        SecretPassphrase(passphrase.into())
+    }
+
+    /// The plaintext passphrase
+    pub fn expose_secret(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl From<String> for SecretPassphrase {
+    fn from(passphrase: String) -> Self {
+        SecretPassphrase::new(passphrase)
+    }
+}
+
+impl Default for SecretPassphrase {
+    /// The empty passphrase (the BIP39 default)
+    fn default() -> Self {
+        SecretPassphrase::new(String::new())
+    }
+}
+
+impl AsRef<str> for SecretPassphrase {
+    fn as_ref(&self) -> &str {
+        self.expose_secret()
+    }
+}
+
+impl fmt::Debug for SecretPassphrase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecretPassphrase(<redacted>)")
+    }
+}
+
+/// Coarse passphrase strength, ordered from weakest to strongest
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PassphraseStrength {
+    /// No passphrase (the BIP39 default)
+    Empty,
+    /// Short, single-class or highly repetitive
+    Weak,
+    /// Reasonable length or some character variety
+    Fair,
+    /// Long, or medium length with three or more character classes
+    Strong,
+}
+
+/// Score a passphrase by length and character-class diversity
+///
+/// Classes are lowercase, uppercase, digits, and everything else (symbols,
+/// spaces, non-ASCII). Length is counted in characters, not bytes.
+pub fn passphrase_strength(passphrase: &str) -> PassphraseStrength {
+    if passphrase.is_empty() {
+        return PassphraseStrength::Empty;
+    }
+
+    let length = passphrase.chars().count();
+    let has_lower = passphrase.chars().any(|c| c.is_lowercase());
+    let has_upper = passphrase.chars().any(|c| c.is_uppercase());
+    let has_digit = passphrase.chars().any(|c| c.is_numeric());
+    let has_other = passphrase.chars().any(|c| !c.is_alphanumeric());
+    let classes = [has_lower, has_upper, has_digit, has_other].iter().filter(|&&present| present).count();
+
+    // "aaaaaaaa..." or "abababab..." is long but guessable
+    let mut distinct: Vec<char> = passphrase.chars().collect();
+    distinct.sort_unstable();
+    distinct.dedup();
+    if distinct.len() <= 2 {
+        return PassphraseStrength::Weak;
+    }
+
+    match (length, classes) {
+        (length, _) if length < 8 => PassphraseStrength::Weak,
+        (length, classes) if length >= 20 || (length >= 12 && classes >= 3) => PassphraseStrength::Strong,
+        (length, classes) if length >= 12 || classes >= 2 => PassphraseStrength::Fair,
+        _ => PassphraseStrength::Weak,
+    }
+}
@@ -0,0 +1,357 @@
+//! BIP32 derivation paths and path templates with placeholders
+
+use crate::error::{KeyManagerError, Result};
+use crate::hd_key::HARDENED_OFFSET;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// Parsed BIP32 derivation path: the child indices below the root key
+///
+/// Hardened indices carry the 2^31 offset. Displays as `m/84'/0'/0'/0/5`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DerivationPath {
+    indices: Vec<u32>,
+}
+
+impl DerivationPath {
+    /// Build a path from raw child indices (hardened ones already offset)
+    pub fn from_indices(indices: Vec<u32>) -> Self {
+        DerivationPath { indices }
+    }
+
+    /// Child indices from the root, in derivation order
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    /// Number of derivation steps (0 for "m")
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Whether this is the root path "m"
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Parse a path, treating unmarked components as hardened if asked
+    ///
+    /// Some non-Bitcoin tooling writes "m/44/118/0/0/0" and means every step
+    /// hardened. Explicit `'`/`h` markers are always honoured.
+    pub fn parse_with_default(path: &str, default_hardened: bool) -> Result<Self> {
+        let indices = path_components(path)?
+            .map(|component| parse_component(component, default_hardened))
+            .collect::<Result<Vec<u32>>>()?;
+        Ok(DerivationPath { indices })
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = KeyManagerError;
+
+    fn from_str(path: &str) -> Result<Self> {
+        Self::parse_with_default(path, false)
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m")?;
+        for &index in &self.indices {
+            write!(f, "/{}", format_component(index))?;
+        }
+        Ok(())
+    }
+}
+
+/// Tidy a pasted path for parsing: trim, drop spaces around separators, `\` to `/`
+///
+/// `" m / 44' / 0' "` and `m\44'\0'` (copied from Windows) both become
+/// `m/44'/0'`. Whitespace inside a component is left alone, so "4 4" still
+/// fails to parse instead of silently becoming 44.
+pub fn normalize_path_string(s: &str) -> String {
+    s.replace('\\', "/")
+        .split('/')
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Render raw child indices (e.g. from a PSBT key origin) as "m/44'/0'/0'/0/0"
+///
+/// Indices at or above 2^31 are shown hardened with the offset removed. An
+/// empty slice renders as the root "m".
+pub fn path_from_indices(indices: &[u32]) -> String {
+    DerivationPath::from_indices(indices.to_vec()).to_string()
+}
+
+/// Display form of one child index: "44'" for hardened, "0" otherwise
+pub(crate) fn format_component(index: u32) -> String {
+    if index >= HARDENED_OFFSET {
+        format!("{}'", index - HARDENED_OFFSET)
+    } else {
+        index.to_string()
+    }
+}
+
+/// Split "m/44'/0'" into its non-empty components after the "m/" prefix
+fn path_components(path: &str) -> Result<impl Iterator<Item = &str>> {
+    let path = path.trim();
+
+    // Check if path starts with "m" or "M"
+    let Some(rest) = path.strip_prefix(['m', 'M']) else {
+        return Err(KeyManagerError::InvalidDerivationPath(
+            "Path must start with 'm' or 'M'".to_string(),
+        ));
+    };
+
+    // Just "m" is the root; otherwise require a "/" and at least one character
+    let rest = match rest.strip_prefix('/') {
+        _ if rest.is_empty() => "",
+        Some(components) if !components.is_empty() => components,
+        _ => {
+            return Err(KeyManagerError::InvalidDerivationPath(
+                "Invalid path format".to_string(),
+            ))
+        }
+    };
+
+    Ok(rest.split('/').filter(|component| !component.is_empty()))
+}
+
+/// Parse one path component ("44", "44'", "44h") into a child index
+fn parse_component(component: &str, default_hardened: bool) -> Result<u32> {
+    let (index_str, marked) = split_hardened(component)?;
+    let hardened = marked || default_hardened;
+
+    // Parse index
+    let index: u32 = index_str
+        .parse()
+        .map_err(|_| KeyManagerError::InvalidDerivationPath(
+            format!("Invalid index: {}", index_str)
+        ))?;
+
+    // Apply hardened offset if needed
+    if hardened {
+        index.checked_add(HARDENED_OFFSET)
+            .ok_or_else(|| KeyManagerError::InvalidDerivationPath(
+                "Index overflow".to_string()
+            ))
+    } else {
+        Ok(index)
+    }
+}
+
+/// Strip a trailing hardened marker (' or h), rejecting misplaced markers
+fn split_hardened(component: &str) -> Result<(&str, bool)> {
+    // Check for hardened derivation (ends with ' or h)
+    let (index_str, hardened) = if component.ends_with('\'') || component.ends_with('h') {
+        (&component[..component.len() - 1], true)
+    } else {
+        (component, false)
+    };
+
+    // Give misplaced hardened markers a clearer error than "Invalid index"
+    if hardened && index_str.is_empty() {
+        return Err(KeyManagerError::InvalidDerivationPath(
+            format!("missing index before hardened marker in component '{}'", component)
+        ));
+    }
+    if index_str.starts_with(['\'', 'h']) {
+        return Err(KeyManagerError::InvalidDerivationPath(
+            format!("hardened marker must come after the index in component '{}'", component)
+        ));
+    }
+    if let Some(marker) = index_str.find(['\'', 'h']) {
+        return Err(KeyManagerError::InvalidDerivationPath(format!(
+            "unexpected characters '{}' after hardened marker in component '{}'",
+            &index_str[marker + 1..],
+            component
+        )));
+    }
+
+    Ok((index_str, hardened))
+}
+
+/// One component of a `PathTemplate`
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TemplateComponent {
+    /// Concrete child index (hardened offset applied)
+    Index(u32),
+    /// `{name}` or `{name}'`; `*` is stored under the name `*`
+    Placeholder { name: String, hardened: bool },
+}
+
+/// Derivation path with named placeholders, e.g. `m/84'/0'/{account}'/{change}/*`
+///
+/// Placeholders are `{name}` (letters, digits and `_`). The descriptor-style
+/// wildcard `*` is a placeholder named `*` (see `PathTemplate::WILDCARD`).
+/// A trailing `'` or `h` on a placeholder hardens the substituted value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathTemplate {
+    components: Vec<TemplateComponent>,
+}
+
+impl PathTemplate {
+    /// Name under which the `*` wildcard is filled
+    pub const WILDCARD: &'static str = "*";
+
+    /// Parse a template string
+    pub fn parse(template: &str) -> Result<Self> {
+        let components = path_components(template)?
+            .map(|component| {
+                let (body, hardened) = match component.strip_suffix(['\'', 'h']) {
+                    Some(body) if body == "*" || body.ends_with('}') => (body, true),
+                    _ => (component, false),
+                };
+
+                if body == Self::WILDCARD {
+                    return Ok(TemplateComponent::Placeholder { name: body.to_string(), hardened });
+                }
+                if let Some(name) = body.strip_prefix('{') {
+                    let name = name.strip_suffix('}').ok_or_else(|| {
+                        KeyManagerError::InvalidDerivationPath(format!("unclosed placeholder in component '{}'", component))
+                    })?;
+                    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                        return Err(KeyManagerError::InvalidDerivationPath(format!(
+                            "invalid placeholder name in component '{}'",
+                            component
+                        )));
+                    }
+                    return Ok(TemplateComponent::Placeholder { name: name.to_string(), hardened });
+                }
+                parse_component(component, false).map(TemplateComponent::Index)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(PathTemplate { components })
+    }
+
+    /// Names of the placeholders still in the template, in path order
+    pub fn placeholders(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for component in &self.components {
+            if let TemplateComponent::Placeholder { name, .. } = component {
+                if !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+
+    /// Substitute some placeholders, leaving the others in place
+    ///
+    /// Unknown names are an error so typos don't go unnoticed.
+    pub fn bind(&self, values: &[(&str, u32)]) -> Result<PathTemplate> {
+        for (name, _) in values {
+            if !self.placeholders().contains(name) {
+                return Err(KeyManagerError::InvalidDerivationPath(format!(
+                    "unknown placeholder '{}' (template has: {})",
+                    name,
+                    self.placeholders().join(", ")
+                )));
+            }
+        }
+
+        let components = self
+            .components
+            .iter()
+            .map(|component| match component {
+                TemplateComponent::Placeholder { name, hardened } => {
+                    match values.iter().find(|(n, _)| n == name) {
+                        Some(&(_, value)) => substitute(name, value, *hardened).map(TemplateComponent::Index),
+                        None => Ok(component.clone()),
+                    }
+                }
+                TemplateComponent::Index(_) => Ok(component.clone()),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(PathTemplate { components })
+    }
+
+    /// Substitute every placeholder, producing a concrete path
+    pub fn fill(&self, values: &[(&str, u32)]) -> Result<DerivationPath> {
+        self.bind(values)?.into_path()
+    }
+
+    /// Concrete paths for each value of one placeholder over `range`
+    ///
+    /// `name` must be the only placeholder left; `bind` the others first.
+    pub fn iter_fill<'a>(&'a self, name: &'a str, range: Range<u32>) -> Result<impl Iterator<Item = DerivationPath> + 'a> {
+        let placeholders = self.placeholders();
+        if !placeholders.contains(&name) {
+            return Err(KeyManagerError::InvalidDerivationPath(format!(
+                "unknown placeholder '{}' (template has: {})",
+                name,
+                placeholders.join(", ")
+            )));
+        }
+        if let Some(other) = placeholders.iter().find(|&&other| other != name) {
+            return Err(KeyManagerError::InvalidDerivationPath(format!("unfilled placeholder '{}'", other)));
+        }
+        if range.end > HARDENED_OFFSET {
+            return Err(KeyManagerError::InvalidDerivationPath(format!(
+                "value {} for placeholder '{}' is out of range",
+                range.end - 1,
+                name
+            )));
+        }
+
+        Ok(range.map(move |value| {
+            self.fill(&[(name, value)])
+                .expect("placeholder and range validated above")
+        }))
+    }
+
+    /// Convert a fully bound template into a path
+    fn into_path(self) -> Result<DerivationPath> {
+        let indices = self
+            .components
+            .into_iter()
+            .map(|component| match component {
+                TemplateComponent::Index(index) => Ok(index),
+                TemplateComponent::Placeholder { name, .. } => Err(KeyManagerError::InvalidDerivationPath(
+                    format!("unfilled placeholder '{}'", name),
+                )),
+            })
+            .collect::<Result<Vec<u32>>>()?;
+        Ok(DerivationPath { indices })
+    }
+}
+
+impl fmt::Display for PathTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m")?;
+        for component in &self.components {
+            match component {
+                TemplateComponent::Index(index) if *index >= HARDENED_OFFSET => {
+                    write!(f, "/{}'", index - HARDENED_OFFSET)?
+                }
+                TemplateComponent::Index(index) => write!(f, "/{}", index)?,
+                TemplateComponent::Placeholder { name, hardened } => {
+                    let marker = if *hardened { "'" } else { "" };
+                    if name == Self::WILDCARD {
+                        write!(f, "/*{}", marker)?
+                    } else {
+                        write!(f, "/{{{}}}{}", name, marker)?
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Value for a placeholder, applying the hardened flag after substitution
+fn substitute(name: &str, value: u32, hardened: bool) -> Result<u32> {
+    if value >= HARDENED_OFFSET {
+        return Err(KeyManagerError::InvalidDerivationPath(format!(
+            "value {} for placeholder '{}' is out of range",
+            value, name
+        )));
+    }
+    Ok(if hardened { value + HARDENED_OFFSET } else { value })
+}
@@ -0,0 +1,338 @@
+//! Deterministic OpenPGP keys derived from the BIP32 master key
+//!
+//! The primary key (Ed25519, certify + sign) and the encryption subkey
+//! (Cv25519 ECDH) are each seeded with BIP85-style entropy from their own
+//! hardened path:
+//!
+//! ```text
+//! m/83696968'/807180'/0'   primary Ed25519 signing key
+//! m/83696968'/807180'/1'   Cv25519 encryption subkey
+//! ```
+//!
+//! 807180 spells "PGP" in ASCII decimal, like 83696968 spells "SEED". The
+//! entropy comes from `bip85::bip85_entropy`.
+//!
+//! OpenPGP fingerprints hash the key creation time, so the caller fixes it
+//! explicitly. The same mnemonic, user ID and creation time always produce
+//! byte-identical output, including the self-signatures (Ed25519 signing is
+//! deterministic).
+
+use crate::bip85::bip85_entropy;
+use crate::error::{KeyManagerError, Result};
+use crate::hd_key::ExtendedKey;
+use crate::utils::base64;
+use curve25519_dalek::MontgomeryPoint;
+use ed25519_dalek::{Signer, SigningKey};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+/// BIP85 application number used for OpenPGP keys ("PGP")
+pub const PGP_APPLICATION: u32 = 807180;
+
+// Packet tags (RFC 4880 section 4.3)
+const TAG_SIGNATURE: u8 = 2;
+const TAG_SECRET_KEY: u8 = 5;
+const TAG_SECRET_SUBKEY: u8 = 7;
+const TAG_USER_ID: u8 = 13;
+
+// Algorithm identifiers
+const ALGO_ECDH: u8 = 18;
+const ALGO_EDDSA: u8 = 22;
+const HASH_SHA256: u8 = 8;
+const CIPHER_AES128: u8 = 7;
+
+// Signature types
+const SIG_POSITIVE_CERTIFICATION: u8 = 0x13;
+const SIG_SUBKEY_BINDING: u8 = 0x18;
+
+// Key flags
+const FLAGS_CERTIFY_SIGN: u8 = 0x03;
+const FLAGS_ENCRYPT: u8 = 0x0C;
+
+/// Curve OIDs (without the ASN.1 tag and length)
+const OID_ED25519: [u8; 9] = [0x2B, 0x06, 0x01, 0x04, 0x01, 0xDA, 0x47, 0x0F, 0x01];
+const OID_CURVE25519: [u8; 10] = [0x2B, 0x06, 0x01, 0x04, 0x01, 0x97, 0x55, 0x01, 0x05, 0x01];
+
+/// Prefix marking a native point encoding in EdDSA/ECDH MPIs
+const NATIVE_POINT_PREFIX: u8 = 0x40;
+
+/// Deterministic OpenPGP key: Ed25519 primary plus Cv25519 encryption subkey
+pub struct PgpKey {
+    signing_key: SigningKey,
+    /// Clamped X25519 secret scalar, little-endian as used by the curve
+    encryption_secret: [u8; 32],
+    user_id: String,
+    creation_time: u32,
+}
+
+/// Derive the OpenPGP key for `user_id` (e.g. `"Alice <alice@example.org>"`)
+///
+/// `creation_time` is a Unix timestamp stored in the key and signatures.
+/// Keep it fixed: changing it changes the fingerprint.
+pub fn derive_ed25519_keypair(master: &ExtendedKey, user_id: &str, creation_time: u32) -> Result<PgpKey> {
+    if user_id.trim().is_empty() {
+        return Err(KeyManagerError::EncodingError("OpenPGP user ID must not be empty".to_string()));
+    }
+
+    let mut signing_seed = bip85_entropy(master, PGP_APPLICATION, 0)?;
+    let signing_key = SigningKey::from_bytes(&signing_seed);
+    signing_seed.zeroize();
+
+    let mut encryption_secret = bip85_entropy(master, PGP_APPLICATION, 1)?;
+    clamp(&mut encryption_secret);
+
+    Ok(PgpKey {
+        signing_key,
+        encryption_secret,
+        user_id: user_id.to_string(),
+        creation_time,
+    })
+}
+
+impl PgpKey {
+    /// V4 fingerprint of the primary key
+    pub fn fingerprint(&self) -> [u8; 20] {
+        fingerprint(&self.primary_public_body())
+    }
+
+    /// V4 fingerprint of the encryption subkey
+    pub fn subkey_fingerprint(&self) -> [u8; 20] {
+        fingerprint(&self.subkey_public_body())
+    }
+
+    /// Long key ID: the low 64 bits of the fingerprint
+    pub fn key_id(&self) -> [u8; 8] {
+        let mut key_id = [0u8; 8];
+        key_id.copy_from_slice(&self.fingerprint()[12..]);
+        key_id
+    }
+
+    /// Transferable secret key (RFC 4880 section 11.2) in ASCII armor
+    ///
+    /// Secret key, user ID, self-certification, secret subkey and subkey
+    /// binding signature, unencrypted. Suitable for `gpg --import`.
+    pub fn to_armored_secret_key(&self) -> String {
+        let primary_public = self.primary_public_body();
+        let subkey_public = self.subkey_public_body();
+
+        // Ed25519 secrets are stored as the 32-byte seed
+        let primary_secret = secret_key_body(&primary_public, self.signing_key.as_bytes());
+
+        // Cv25519 secrets are stored big-endian, i.e. byte-reversed
+        let mut reversed = self.encryption_secret;
+        reversed.reverse();
+        let subkey_secret = secret_key_body(&subkey_public, &reversed);
+        reversed.zeroize();
+
+        let mut packets = Vec::new();
+        packets.extend(packet(TAG_SECRET_KEY, &primary_secret));
+        packets.extend(packet(TAG_USER_ID, self.user_id.as_bytes()));
+        packets.extend(packet(TAG_SIGNATURE, &self.self_certification(&primary_public)));
+        packets.extend(packet(TAG_SECRET_SUBKEY, &subkey_secret));
+        packets.extend(packet(TAG_SIGNATURE, &self.subkey_binding(&primary_public, &subkey_public)));
+
+        armor("PGP PRIVATE KEY BLOCK", &packets)
+    }
+
+    /// Public key packet body of the Ed25519 primary key
+    fn primary_public_body(&self) -> Vec<u8> {
+        let public_key = self.signing_key.verifying_key().to_bytes();
+        let mut body = public_body_header(self.creation_time, ALGO_EDDSA, &OID_ED25519);
+        body.extend(native_point_mpi(&public_key));
+        body
+    }
+
+    /// Public key packet body of the Cv25519 subkey, including ECDH KDF parameters
+    fn subkey_public_body(&self) -> Vec<u8> {
+        let public_key = MontgomeryPoint::mul_base_clamped(self.encryption_secret).to_bytes();
+        let mut body = public_body_header(self.creation_time, ALGO_ECDH, &OID_CURVE25519);
+        body.extend(native_point_mpi(&public_key));
+        // KDF: 3 bytes follow, reserved 0x01, SHA-256, AES-128 key wrap
+        body.extend([0x03, 0x01, HASH_SHA256, CIPHER_AES128]);
+        body
+    }
+
+    /// Positive certification binding the user ID to the primary key
+    fn self_certification(&self, primary_public: &[u8]) -> Vec<u8> {
+        let mut hashed = self.common_subpackets();
+        hashed.extend(subpacket(27, &[FLAGS_CERTIFY_SIGN]));
+        hashed.extend(subpacket(11, &[9, 8, 7])); // AES-256, AES-192, AES-128
+        hashed.extend(subpacket(21, &[10, 8])); // SHA-512, SHA-256
+        hashed.extend(subpacket(22, &[2, 3, 1])); // ZLIB, BZip2, ZIP
+        hashed.extend(subpacket(30, &[0x01])); // Modification detection
+
+        let mut signed = key_hash_prefix(primary_public);
+        signed.push(0xB4);
+        signed.extend((self.user_id.len() as u32).to_be_bytes());
+        signed.extend(self.user_id.as_bytes());
+        self.signature(SIG_POSITIVE_CERTIFICATION, &hashed, signed)
+    }
+
+    /// Subkey binding signature over the primary and encryption keys
+    fn subkey_binding(&self, primary_public: &[u8], subkey_public: &[u8]) -> Vec<u8> {
+        let mut hashed = self.common_subpackets();
+        hashed.extend(subpacket(27, &[FLAGS_ENCRYPT]));
+
+        let mut signed = key_hash_prefix(primary_public);
+        signed.extend(key_hash_prefix(subkey_public));
+        self.signature(SIG_SUBKEY_BINDING, &hashed, signed)
+    }
+
+    /// Issuer fingerprint and creation time, hashed into every signature
+    fn common_subpackets(&self) -> Vec<u8> {
+        let mut issuer_fingerprint = vec![4];
+        issuer_fingerprint.extend(self.fingerprint());
+
+        let mut subpackets = subpacket(33, &issuer_fingerprint);
+        subpackets.extend(subpacket(2, &self.creation_time.to_be_bytes()));
+        subpackets
+    }
+
+    /// V4 EdDSA signature packet body over `signed` plus the signature trailer
+    fn signature(&self, signature_type: u8, hashed: &[u8], mut signed: Vec<u8>) -> Vec<u8> {
+        let mut body = vec![4, signature_type, ALGO_EDDSA, HASH_SHA256];
+        body.extend((hashed.len() as u16).to_be_bytes());
+        body.extend(hashed);
+
+        signed.extend(&body);
+        signed.extend([0x04, 0xFF]);
+        signed.extend((body.len() as u32).to_be_bytes());
+        let digest = Sha256::digest(&signed);
+
+        // OpenPGP EdDSA signs the hash digest, not the data itself
+        let signature = self.signing_key.sign(&digest).to_bytes();
+
+        let unhashed = subpacket(16, &self.key_id());
+        body.extend((unhashed.len() as u16).to_be_bytes());
+        body.extend(unhashed);
+        body.extend(&digest[..2]);
+        body.extend(mpi(&signature[..32]));
+        body.extend(mpi(&signature[32..]));
+        body
+    }
+}
+
+impl Drop for PgpKey {
+    fn drop(&mut self) {
+        self.encryption_secret.zeroize();
+    }
+}
+
+/// X25519 scalar clamping (RFC 7748 section 5)
+fn clamp(scalar: &mut [u8; 32]) {
+    scalar[0] &= 248;
+    scalar[31] &= 127;
+    scalar[31] |= 64;
+}
+
+/// Version, creation time, algorithm and curve OID of a v4 key packet
+fn public_body_header(creation_time: u32, algorithm: u8, oid: &[u8]) -> Vec<u8> {
+    let mut body = vec![4];
+    body.extend(creation_time.to_be_bytes());
+    body.push(algorithm);
+    body.push(oid.len() as u8);
+    body.extend(oid);
+    body
+}
+
+/// Append an unencrypted secret MPI and its checksum to a public key body
+fn secret_key_body(public_body: &[u8], secret: &[u8]) -> Vec<u8> {
+    let secret_mpi = mpi(secret);
+    let checksum = secret_mpi.iter().fold(0u16, |sum, &b| sum.wrapping_add(b as u16));
+
+    let mut body = public_body.to_vec();
+    body.push(0); // S2K usage: not encrypted
+    body.extend(&secret_mpi);
+    body.extend(checksum.to_be_bytes());
+    body
+}
+
+/// `0x99 || length || body`, the form in which keys are hashed
+fn key_hash_prefix(public_body: &[u8]) -> Vec<u8> {
+    let mut prefix = vec![0x99];
+    prefix.extend((public_body.len() as u16).to_be_bytes());
+    prefix.extend(public_body);
+    prefix
+}
+
+/// V4 fingerprint: SHA-1 over the hashed form of the public key
+fn fingerprint(public_body: &[u8]) -> [u8; 20] {
+    Sha1::digest(key_hash_prefix(public_body)).into()
+}
+
+/// Multiprecision integer: bit count followed by big-endian bytes without leading zeros
+fn mpi(value: &[u8]) -> Vec<u8> {
+    let start = value.iter().position(|&b| b != 0).unwrap_or(value.len());
+    let value = &value[start..];
+    let bits = value.first().map_or(0, |&b| (value.len() * 8) as u16 - b.leading_zeros() as u16);
+
+    let mut out = bits.to_be_bytes().to_vec();
+    out.extend(value);
+    out
+}
+
+/// MPI of a native curve point: `0x40 || point`
+fn native_point_mpi(point: &[u8; 32]) -> Vec<u8> {
+    let mut prefixed = vec![NATIVE_POINT_PREFIX];
+    prefixed.extend(point);
+    mpi(&prefixed)
+}
+
+/// Signature subpacket with a one-byte length
+fn subpacket(kind: u8, data: &[u8]) -> Vec<u8> {
+    let mut out = vec![(data.len() + 1) as u8, kind];
+    out.extend(data);
+    out
+}
+
+/// New-format packet header plus body
+fn packet(tag: u8, body: &[u8]) -> Vec<u8> {
+    let mut out = vec![0xC0 | tag];
+    match body.len() {
+        len if len < 192 => out.push(len as u8),
+        len if len < 8384 => {
+            let len = len - 192;
+            out.push((len >> 8) as u8 + 192);
+            out.push(len as u8);
+        }
+        len => {
+            out.push(0xFF);
+            out.extend((len as u32).to_be_bytes());
+        }
+    }
+    out.extend(body);
+    out
+}
+
+/// ASCII armor (RFC 4880 section 6.2) with a CRC-24 checksum line
+fn armor(label: &str, data: &[u8]) -> String {
+    let encoded = base64::encode(data);
+    let mut out = format!("-----BEGIN {}-----\n\n", label);
+    for line in encoded.as_bytes().chunks(64) {
+        out.push_str(std::str::from_utf8(line).expect("base64 is ASCII"));
+        out.push('\n');
+    }
+    out.push('=');
+    out.push_str(&base64::encode(&crc24(data).to_be_bytes()[1..]));
+    out.push_str(&format!("\n-----END {}-----\n", label));
+    out
+}
+
+/// CRC-24 as used by OpenPGP armor
+fn crc24(data: &[u8]) -> u32 {
+    const INIT: u32 = 0xB704CE;
+    const POLY: u32 = 0x1864CFB;
+
+    let mut crc = INIT;
+    for &byte in data {
+        crc ^= (byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x1000000 != 0 {
+                crc ^= POLY;
+            }
+        }
+    }
+    crc & 0xFFFFFF
+}
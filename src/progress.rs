@@ -0,0 +1,38 @@
+//! Progress reporting and cancellation for long-running scans
+
+use crate::error::{KeyManagerError, Result};
+use std::ops::ControlFlow;
+
+/// Receives progress updates and decides whether an operation continues
+///
+/// `done` counts units of work (addresses checked, candidates tried);
+/// `total` is known only when the search space is bounded. Returning
+/// `ControlFlow::Break(())` stops the operation with
+/// `KeyManagerError::Cancelled`.
+pub trait Progress {
+    fn report(&self, done: u64, total: Option<u64>) -> ControlFlow<()>;
+}
+
+impl<F: Fn(u64, Option<u64>) -> ControlFlow<()>> Progress for F {
+    fn report(&self, done: u64, total: Option<u64>) -> ControlFlow<()> {
+        self(done, total)
+    }
+}
+
+/// Progress sink that never reports and never cancels
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn report(&self, _done: u64, _total: Option<u64>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+/// Report to `progress`, turning a break into `Cancelled`
+pub(crate) fn checkpoint(progress: &impl Progress, done: u64, total: Option<u64>) -> Result<()> {
+    match progress.report(done, total) {
+        ControlFlow::Continue(()) => Ok(()),
+        ControlFlow::Break(()) => Err(KeyManagerError::Cancelled),
+    }
+}
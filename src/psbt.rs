@@ -0,0 +1,333 @@
+//! BIP174 partially signed Bitcoin transactions
+//!
+//! A PSBT is parsed into its raw key/value maps so that fields we do not
+//! understand survive a sign-and-reserialize round trip. Signing looks for
+//! inputs whose BIP32 derivation entries carry our master fingerprint and
+//! adds a `partial_sig` (P2WPKH, BIP143) or `tap_key_sig` (P2TR key path,
+//! BIP341). Finalizing and extracting the transaction is left to the wallet
+//! that created the PSBT.
+
+pub mod transaction;
+
+use crate::error::{KeyManagerError, Result};
+use crate::hd_key::ExtendedKey;
+use crate::path::DerivationPath;
+use crate::signing;
+use crate::taproot;
+use crate::utils::{base64, hash160};
+use secp256k1::{Keypair, Message, SecretKey};
+use transaction::{write_compact_size, Reader, Transaction, TxOut, SIGHASH_ALL, SIGHASH_DEFAULT};
+
+/// Magic bytes at the start of every PSBT: "psbt" followed by 0xff
+const MAGIC: &[u8; 5] = b"psbt\xff";
+
+const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+const PSBT_GLOBAL_VERSION: u8 = 0xfb;
+const PSBT_IN_WITNESS_UTXO: u8 = 0x01;
+const PSBT_IN_PARTIAL_SIG: u8 = 0x02;
+const PSBT_IN_SIGHASH_TYPE: u8 = 0x03;
+const PSBT_IN_BIP32_DERIVATION: u8 = 0x06;
+const PSBT_IN_FINAL_SCRIPTWITNESS: u8 = 0x08;
+const PSBT_IN_TAP_KEY_SIG: u8 = 0x13;
+const PSBT_IN_TAP_BIP32_DERIVATION: u8 = 0x16;
+const PSBT_IN_TAP_INTERNAL_KEY: u8 = 0x17;
+const PSBT_IN_TAP_MERKLE_ROOT: u8 = 0x18;
+
+/// One key/value map: `(key, value)` pairs in their original order
+type Map = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// A version 0 PSBT
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Psbt {
+    unsigned_tx: Transaction,
+    global: Map,
+    inputs: Vec<Map>,
+    outputs: Vec<Map>,
+}
+
+/// Outcome of `Psbt::sign`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SignReport {
+    /// Indices of inputs that received a signature
+    pub signed: Vec<usize>,
+    /// Inputs left alone, with the reason
+    pub skipped: Vec<(usize, String)>,
+}
+
+impl Psbt {
+    /// Parse a base64-encoded PSBT (the form used by Bitcoin Core's RPCs)
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        Self::from_bytes(&base64::decode(encoded)?)
+    }
+
+    /// Parse a binary PSBT
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if !bytes.starts_with(MAGIC) {
+            return Err(KeyManagerError::EncodingError("Not a PSBT (missing magic bytes)".to_string()));
+        }
+        let mut reader = Reader::new(&bytes[MAGIC.len()..]);
+
+        let global = read_map(&mut reader)?;
+        if let Some(version) = find(&global, &[PSBT_GLOBAL_VERSION]) {
+            if version != [0, 0, 0, 0] {
+                return Err(KeyManagerError::EncodingError(
+                    "Only PSBT version 0 is supported".to_string(),
+                ));
+            }
+        }
+        let unsigned_tx = find(&global, &[PSBT_GLOBAL_UNSIGNED_TX])
+            .ok_or_else(|| KeyManagerError::EncodingError("PSBT has no unsigned transaction".to_string()))
+            .and_then(Transaction::parse)?;
+        if unsigned_tx.inputs.iter().any(|input| !input.script_sig.is_empty()) {
+            return Err(KeyManagerError::EncodingError(
+                "PSBT unsigned transaction has non-empty scriptSigs".to_string(),
+            ));
+        }
+
+        let inputs = (0..unsigned_tx.inputs.len())
+            .map(|_| read_map(&mut reader))
+            .collect::<Result<Vec<_>>>()?;
+        let outputs = (0..unsigned_tx.outputs.len())
+            .map(|_| read_map(&mut reader))
+            .collect::<Result<Vec<_>>>()?;
+        reader.finish()?;
+
+        Ok(Psbt { unsigned_tx, global, inputs, outputs })
+    }
+
+    /// Serialize to base64
+    pub fn to_base64(&self) -> String {
+        base64::encode(&self.to_bytes())
+    }
+
+    /// Serialize to the binary format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        for map in std::iter::once(&self.global).chain(&self.inputs).chain(&self.outputs) {
+            for (key, value) in map {
+                write_compact_size(&mut out, key.len());
+                out.extend(key);
+                write_compact_size(&mut out, value.len());
+                out.extend(value);
+            }
+            out.push(0x00);
+        }
+        out
+    }
+
+    /// The transaction being signed
+    pub fn unsigned_tx(&self) -> &Transaction {
+        &self.unsigned_tx
+    }
+
+    /// Sign every input we hold a key for
+    ///
+    /// An input is signed when one of its BIP32 derivation entries carries
+    /// `master`'s fingerprint, the derived key matches the entry, and the
+    /// spent output is P2WPKH or a P2TR key-path output of that key. Other
+    /// inputs are reported as skipped; only an unusable master key is an error.
+    pub fn sign(&mut self, master: &ExtendedKey) -> Result<SignReport> {
+        let fingerprint = master.fingerprint()?;
+        let spent_outputs: Option<Vec<TxOut>> = self
+            .inputs
+            .iter()
+            .map(|input| find(input, &[PSBT_IN_WITNESS_UTXO]).and_then(|utxo| TxOut::from_bytes(utxo).ok()))
+            .collect();
+
+        let mut report = SignReport::default();
+        for index in 0..self.inputs.len() {
+            match self.sign_input(index, master, &fingerprint, spent_outputs.as_deref()) {
+                Ok(()) => report.signed.push(index),
+                Err(reason) => report.skipped.push((index, reason)),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Sign one input, or explain why it was skipped
+    fn sign_input(
+        &mut self,
+        index: usize,
+        master: &ExtendedKey,
+        fingerprint: &[u8; 4],
+        spent_outputs: Option<&[TxOut]>,
+    ) -> std::result::Result<(), String> {
+        let input = &self.inputs[index];
+        if find(input, &[PSBT_IN_FINAL_SCRIPTWITNESS]).is_some() {
+            return Err("already finalized".to_string());
+        }
+        let utxo = find(input, &[PSBT_IN_WITNESS_UTXO])
+            .ok_or("no witness_utxo (only segwit inputs are supported)")?;
+        let utxo = TxOut::from_bytes(utxo).map_err(|e| format!("invalid witness_utxo: {}", e))?;
+        let sighash_type = match find(input, &[PSBT_IN_SIGHASH_TYPE]) {
+            Some(value) => Some(
+                value
+                    .try_into()
+                    .map(u32::from_le_bytes)
+                    .map_err(|_| "invalid sighash_type".to_string())?,
+            ),
+            None => None,
+        };
+
+        let script = &utxo.script_pubkey;
+        let (key, value) = if script.len() == 22 && script[..2] == [0x00, 0x14] {
+            if sighash_type.is_some_and(|t| t != SIGHASH_ALL as u32) {
+                return Err("only SIGHASH_ALL is supported".to_string());
+            }
+            self.sign_p2wpkh(index, master, fingerprint, &utxo)?
+        } else if script.len() == 34 && script[..2] == [0x51, 0x20] {
+            let sighash_type = match sighash_type {
+                None => SIGHASH_DEFAULT,
+                Some(t) if t == SIGHASH_DEFAULT as u32 || t == SIGHASH_ALL as u32 => t as u8,
+                Some(_) => return Err("only SIGHASH_DEFAULT and SIGHASH_ALL are supported".to_string()),
+            };
+            let spent_outputs = spent_outputs.ok_or("taproot signing needs witness_utxo for every input")?;
+            self.sign_p2tr_key_path(index, master, fingerprint, spent_outputs, sighash_type)?
+        } else {
+            return Err("unsupported script type (expected P2WPKH or P2TR)".to_string());
+        };
+
+        insert(&mut self.inputs[index], key, value);
+        Ok(())
+    }
+
+    /// BIP143 ECDSA signature, returned as the `partial_sig` key and value
+    fn sign_p2wpkh(
+        &self,
+        index: usize,
+        master: &ExtendedKey,
+        fingerprint: &[u8; 4],
+        utxo: &TxOut,
+    ) -> std::result::Result<(Vec<u8>, Vec<u8>), String> {
+        for (key, value) in &self.inputs[index] {
+            if key.len() != 34 || key[0] != PSBT_IN_BIP32_DERIVATION {
+                continue;
+            }
+            let Some(path) = parse_key_origin(value, fingerprint) else { continue };
+            let pubkey = &key[1..];
+            if hash160(pubkey) != utxo.script_pubkey[2..] {
+                continue;
+            }
+
+            let derived = master.derive(&path).map_err(|e| e.to_string())?;
+            if derived.public_key().map_err(|e| e.to_string())? != pubkey {
+                return Err(format!("key at {} does not match the PSBT's pubkey", path));
+            }
+
+            let mut script_code = vec![0x76, 0xa9, 0x14];
+            script_code.extend(&utxo.script_pubkey[2..]);
+            script_code.extend([0x88, 0xac]);
+            let sighash = self
+                .unsigned_tx
+                .segwit_v0_sighash(index, &script_code, utxo.value)
+                .map_err(|e| e.to_string())?;
+
+            let secret_key = SecretKey::from_slice(derived.private_key()).map_err(|e| e.to_string())?;
+            let context = signing::signing_context().map_err(|e| e.to_string())?;
+            let signature = context.sign_ecdsa(&Message::from_digest(sighash), &secret_key);
+            let mut value = signature.serialize_der().to_vec();
+            value.push(SIGHASH_ALL);
+
+            let mut key = vec![PSBT_IN_PARTIAL_SIG];
+            key.extend(pubkey);
+            return Ok((key, value));
+        }
+        Err("no bip32_derivation for our fingerprint matches the output".to_string())
+    }
+
+    /// BIP341 key-path Schnorr signature, returned as the `tap_key_sig` key and value
+    fn sign_p2tr_key_path(
+        &self,
+        index: usize,
+        master: &ExtendedKey,
+        fingerprint: &[u8; 4],
+        spent_outputs: &[TxOut],
+        sighash_type: u8,
+    ) -> std::result::Result<(Vec<u8>, Vec<u8>), String> {
+        let input = &self.inputs[index];
+        let internal_key = find(input, &[PSBT_IN_TAP_INTERNAL_KEY]).ok_or("no tap_internal_key")?;
+        let merkle_root = match find(input, &[PSBT_IN_TAP_MERKLE_ROOT]) {
+            Some(root) => Some(<[u8; 32]>::try_from(root).map_err(|_| "invalid tap_merkle_root".to_string())?),
+            None => None,
+        };
+
+        for (key, value) in input {
+            if key.len() != 33 || key[0] != PSBT_IN_TAP_BIP32_DERIVATION || key[1..] != *internal_key {
+                continue;
+            }
+            // Value: compact_size count, that many 32-byte leaf hashes, then the key origin
+            let mut reader = Reader::new(value);
+            reader
+                .read_compact_size()
+                .and_then(|leaves| reader.read_bytes(leaves.saturating_mul(32)))
+                .map_err(|e| format!("invalid tap_bip32_derivation: {}", e))?;
+            let origin = reader.remaining();
+            let Some(path) = parse_key_origin(origin, fingerprint) else { continue };
+
+            let derived = master.derive(&path).map_err(|e| e.to_string())?;
+            if derived.public_key().map_err(|e| e.to_string())?[1..] != *internal_key {
+                return Err(format!("key at {} does not match the PSBT's internal key", path));
+            }
+            let tweaked = taproot::tweak_keypair(&derived, merkle_root).map_err(|e| e.to_string())?;
+            if tweaked.output_key()[..] != spent_outputs[index].script_pubkey[2..] {
+                return Err("tweaked key does not match the output".to_string());
+            }
+
+            let sighash = self
+                .unsigned_tx
+                .taproot_key_spend_sighash(index, spent_outputs, sighash_type)
+                .map_err(|e| e.to_string())?;
+            let context = signing::signing_context().map_err(|e| e.to_string())?;
+            let keypair = Keypair::from_seckey_slice(&context, tweaked.secret_key()).map_err(|e| e.to_string())?;
+            let signature = context.sign_schnorr_no_aux_rand(&Message::from_digest(sighash), &keypair);
+            let mut value = signature.as_ref().to_vec();
+            if sighash_type != SIGHASH_DEFAULT {
+                value.push(sighash_type);
+            }
+            return Ok((vec![PSBT_IN_TAP_KEY_SIG], value));
+        }
+        Err("no tap_bip32_derivation for our fingerprint matches the internal key".to_string())
+    }
+}
+
+/// Read one key/value map up to its 0x00 separator
+fn read_map(reader: &mut Reader) -> Result<Map> {
+    let mut map: Map = Vec::new();
+    loop {
+        let key = reader.read_var_bytes()?;
+        if key.is_empty() {
+            return Ok(map);
+        }
+        let value = reader.read_var_bytes()?;
+        if map.iter().any(|(existing, _)| existing == key) {
+            return Err(KeyManagerError::EncodingError(format!(
+                "Duplicate PSBT key {}",
+                crate::utils::bytes_to_hex(key)
+            )));
+        }
+        map.push((key.to_vec(), value.to_vec()));
+    }
+}
+
+fn find<'a>(map: &'a Map, key: &[u8]) -> Option<&'a [u8]> {
+    map.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_slice())
+}
+
+fn insert(map: &mut Map, key: Vec<u8>, value: Vec<u8>) {
+    match map.iter_mut().find(|(k, _)| *k == key) {
+        Some(entry) => entry.1 = value,
+        None => map.push((key, value)),
+    }
+}
+
+/// Parse a key origin (fingerprint then little-endian path indices), if it is ours
+fn parse_key_origin(value: &[u8], fingerprint: &[u8; 4]) -> Option<DerivationPath> {
+    if value.len() < 4 || !value.len().is_multiple_of(4) || value[..4] != fingerprint[..] {
+        return None;
+    }
+    let indices = value[4..]
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().expect("4 bytes")))
+        .collect();
+    Some(DerivationPath::from_indices(indices))
+}
@@ -0,0 +1,299 @@
+//! Bitcoin transaction encoding and the segwit signature hashes
+//!
+//! Only what PSBT signing needs: parsing the unsigned transaction, the
+//! BIP143 sighash for segwit v0 and the BIP341 sighash for taproot key-path
+//! spends.
+
+use crate::error::{KeyManagerError, Result};
+use crate::utils::hash::tagged_hash;
+use sha2::{Digest, Sha256};
+
+/// SIGHASH_ALL: commit to every input and output
+pub const SIGHASH_ALL: u8 = 0x01;
+
+/// Taproot SIGHASH_DEFAULT: same commitment as ALL, 64-byte signature
+pub const SIGHASH_DEFAULT: u8 = 0x00;
+
+/// Reference to a previous transaction output
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutPoint {
+    /// Transaction ID in internal byte order
+    pub txid: [u8; 32],
+    pub vout: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxIn {
+    pub previous_output: OutPoint,
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxOut {
+    /// Amount in satoshis
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+/// Transaction without witness data
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transaction {
+    pub version: i32,
+    pub inputs: Vec<TxIn>,
+    pub outputs: Vec<TxOut>,
+    pub lock_time: u32,
+}
+
+impl Transaction {
+    /// Parse a transaction in the legacy (non-witness) serialization
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(bytes);
+        let version = reader.read_u32()? as i32;
+
+        let input_count = reader.read_compact_size()?;
+        if input_count == 0 {
+            return Err(KeyManagerError::EncodingError(
+                "Transaction has no inputs (or uses the witness serialization)".to_string(),
+            ));
+        }
+        let inputs = (0..input_count)
+            .map(|_| {
+                let mut txid = [0u8; 32];
+                txid.copy_from_slice(reader.read_bytes(32)?);
+                let vout = reader.read_u32()?;
+                let script_sig = reader.read_var_bytes()?.to_vec();
+                let sequence = reader.read_u32()?;
+                Ok(TxIn { previous_output: OutPoint { txid, vout }, script_sig, sequence })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let output_count = reader.read_compact_size()?;
+        let outputs = (0..output_count)
+            .map(|_| TxOut::parse(&mut reader))
+            .collect::<Result<Vec<_>>>()?;
+
+        let lock_time = reader.read_u32()?;
+        reader.finish()?;
+
+        Ok(Transaction { version, inputs, outputs, lock_time })
+    }
+
+    /// Legacy serialization (what PSBT stores as the unsigned transaction)
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut out = self.version.to_le_bytes().to_vec();
+        write_compact_size(&mut out, self.inputs.len());
+        for input in &self.inputs {
+            out.extend(input.previous_output.txid);
+            out.extend(input.previous_output.vout.to_le_bytes());
+            write_compact_size(&mut out, input.script_sig.len());
+            out.extend(&input.script_sig);
+            out.extend(input.sequence.to_le_bytes());
+        }
+        write_compact_size(&mut out, self.outputs.len());
+        for output in &self.outputs {
+            output.serialize_into(&mut out);
+        }
+        out.extend(self.lock_time.to_le_bytes());
+        out
+    }
+
+    /// BIP143 signature hash for a segwit v0 input (SIGHASH_ALL)
+    ///
+    /// `script_code` is the BIP143 scriptCode without its length prefix,
+    /// e.g. `OP_DUP OP_HASH160 <hash> OP_EQUALVERIFY OP_CHECKSIG` for P2WPKH.
+    pub fn segwit_v0_sighash(&self, index: usize, script_code: &[u8], value: u64) -> Result<[u8; 32]> {
+        let input = self.input(index)?;
+
+        let mut prevouts = Vec::new();
+        let mut sequences = Vec::new();
+        for input in &self.inputs {
+            prevouts.extend(input.previous_output.txid);
+            prevouts.extend(input.previous_output.vout.to_le_bytes());
+            sequences.extend(input.sequence.to_le_bytes());
+        }
+
+        let mut preimage = self.version.to_le_bytes().to_vec();
+        preimage.extend(double_sha256(&prevouts));
+        preimage.extend(double_sha256(&sequences));
+        preimage.extend(input.previous_output.txid);
+        preimage.extend(input.previous_output.vout.to_le_bytes());
+        write_compact_size(&mut preimage, script_code.len());
+        preimage.extend(script_code);
+        preimage.extend(value.to_le_bytes());
+        preimage.extend(input.sequence.to_le_bytes());
+        preimage.extend(double_sha256(&self.serialize_outputs()));
+        preimage.extend(self.lock_time.to_le_bytes());
+        preimage.extend((SIGHASH_ALL as u32).to_le_bytes());
+        Ok(double_sha256(&preimage))
+    }
+
+    /// BIP341 signature hash for a taproot key-path spend
+    ///
+    /// `spent_outputs` are the outputs every input spends, in input order.
+    /// Supports SIGHASH_DEFAULT and SIGHASH_ALL without an annex.
+    pub fn taproot_key_spend_sighash(&self, index: usize, spent_outputs: &[TxOut], sighash_type: u8) -> Result<[u8; 32]> {
+        self.input(index)?;
+        if spent_outputs.len() != self.inputs.len() {
+            return Err(KeyManagerError::EncodingError(
+                "Taproot sighash needs the spent output of every input".to_string(),
+            ));
+        }
+        if !matches!(sighash_type, SIGHASH_DEFAULT | SIGHASH_ALL) {
+            return Err(KeyManagerError::EncodingError(format!(
+                "Unsupported taproot sighash type 0x{:02x}",
+                sighash_type
+            )));
+        }
+
+        let mut prevouts = Vec::new();
+        let mut sequences = Vec::new();
+        for input in &self.inputs {
+            prevouts.extend(input.previous_output.txid);
+            prevouts.extend(input.previous_output.vout.to_le_bytes());
+            sequences.extend(input.sequence.to_le_bytes());
+        }
+        let mut amounts = Vec::new();
+        let mut script_pubkeys = Vec::new();
+        for output in spent_outputs {
+            amounts.extend(output.value.to_le_bytes());
+            write_compact_size(&mut script_pubkeys, output.script_pubkey.len());
+            script_pubkeys.extend(&output.script_pubkey);
+        }
+
+        let mut message = vec![0x00, sighash_type]; // epoch 0
+        message.extend(self.version.to_le_bytes());
+        message.extend(self.lock_time.to_le_bytes());
+        message.extend(Sha256::digest(&prevouts));
+        message.extend(Sha256::digest(&amounts));
+        message.extend(Sha256::digest(&script_pubkeys));
+        message.extend(Sha256::digest(&sequences));
+        message.extend(Sha256::digest(self.serialize_outputs()));
+        message.push(0x00); // spend type: key path, no annex
+        message.extend((index as u32).to_le_bytes());
+        Ok(tagged_hash("TapSighash", &message))
+    }
+
+    fn input(&self, index: usize) -> Result<&TxIn> {
+        self.inputs.get(index).ok_or_else(|| {
+            KeyManagerError::EncodingError(format!("Input index {} out of range", index))
+        })
+    }
+
+    fn serialize_outputs(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for output in &self.outputs {
+            output.serialize_into(&mut out);
+        }
+        out
+    }
+}
+
+impl TxOut {
+    /// Parse a serialized output (as stored in PSBT_IN_WITNESS_UTXO)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(bytes);
+        let output = TxOut::parse(&mut reader)?;
+        reader.finish()?;
+        Ok(output)
+    }
+
+    fn parse(reader: &mut Reader) -> Result<Self> {
+        let value = u64::from_le_bytes(reader.read_bytes(8)?.try_into().expect("8 bytes"));
+        let script_pubkey = reader.read_var_bytes()?.to_vec();
+        Ok(TxOut { value, script_pubkey })
+    }
+
+    fn serialize_into(&self, out: &mut Vec<u8>) {
+        out.extend(self.value.to_le_bytes());
+        write_compact_size(out, self.script_pubkey.len());
+        out.extend(&self.script_pubkey);
+    }
+}
+
+/// Cursor over a byte slice with Bitcoin's primitive decoders
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes, position: 0 }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.position == self.bytes.len()
+    }
+
+    /// Everything not yet read
+    pub(crate) fn remaining(&self) -> &'a [u8] {
+        &self.bytes[self.position..]
+    }
+
+    pub(crate) fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.position.checked_add(len).filter(|&end| end <= self.bytes.len()).ok_or_else(|| {
+            KeyManagerError::EncodingError("Unexpected end of data".to_string())
+        })?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into().expect("4 bytes")))
+    }
+
+    pub(crate) fn read_compact_size(&mut self) -> Result<usize> {
+        let value = match self.read_bytes(1)?[0] {
+            0xfd => u16::from_le_bytes(self.read_bytes(2)?.try_into().expect("2 bytes")) as u64,
+            0xfe => u32::from_le_bytes(self.read_bytes(4)?.try_into().expect("4 bytes")) as u64,
+            0xff => u64::from_le_bytes(self.read_bytes(8)?.try_into().expect("8 bytes")),
+            small => small as u64,
+        };
+        // Anything larger than the remaining data is malformed; this also caps allocations
+        if value > (self.bytes.len() - self.position) as u64 {
+            return Err(KeyManagerError::EncodingError(format!("Length {} exceeds remaining data", value)));
+        }
+        Ok(value as usize)
+    }
+
+    pub(crate) fn read_var_bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.read_compact_size()?;
+        self.read_bytes(len)
+    }
+
+    pub(crate) fn finish(&self) -> Result<()> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(KeyManagerError::EncodingError(format!(
+                "{} trailing bytes",
+                self.bytes.len() - self.position
+            )))
+        }
+    }
+}
+
+/// Append a Bitcoin CompactSize integer
+pub(crate) fn write_compact_size(out: &mut Vec<u8>, value: usize) {
+    match value {
+        0..=0xfc => out.push(value as u8),
+        0xfd..=0xffff => {
+            out.push(0xfd);
+            out.extend((value as u16).to_le_bytes());
+        }
+        0x10000..=0xffff_ffff => {
+            out.push(0xfe);
+            out.extend((value as u32).to_le_bytes());
+        }
+        _ => {
+            out.push(0xff);
+            out.extend((value as u64).to_le_bytes());
+        }
+    }
+}
+
+fn double_sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(Sha256::digest(data)).into()
+}
@@ -1,34 +1,105 @@
 use crate::error::{KeyManagerError, Result};
-use hmac::Hmac;
+use crate::mnemonic::normalize_nfkd;
+use hmac::{Hmac, Mac};
 use pbkdf2::pbkdf2;
 use sha2::Sha512;
+use zeroize::Zeroize;
+
+/// PBKDF2 iteration count fixed by BIP39
+const BIP39_ROUNDS: u32 = 2048;
 
 /// BIP39 seed generation from mnemonic
 /// 
 /// Takes a BIP39 mnemonic phrase and optional passphrase,
-/// returns a 64-byte seed suitable for BIP32 key generation
-pub fn mnemonic_to_seed(mnemonic: &str, passphrase: &str) -> Result<[u8; 64]> {
-    // Normalize the mnemonic (remove extra whitespace, lowercase)
-    let mnemonic = normalize_mnemonic(mnemonic)?;
-    
-    // Prepare password and salt
-    let password = mnemonic.as_bytes();
-    let salt = format!("mnemonic{}", passphrase);
-    let salt_bytes = salt.as_bytes();
-    
-    // PBKDF2-HMAC-SHA512 with 2048 iterations
-    // This matches BIP39 specification exactly
+/// returns a 64-byte seed suitable for BIP32 key generation.
+/// The passphrase may be a `&str`, a `String` or a `SecretPassphrase`.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+pub fn mnemonic_to_seed(mnemonic: &str, passphrase: impl AsRef<str>) -> Result<[u8; 64]> {
     let mut seed = [0u8; 64];
-    pbkdf2::<Hmac<Sha512>>(password, salt_bytes, 2048, &mut seed);
-    
+    pbkdf2_seed(mnemonic, passphrase.as_ref(), &mut seed)?;
     Ok(seed)
 }
 
+/// Derive arbitrary-length key material from a mnemonic using the BIP39 KDF
+///
+/// Runs the same PBKDF2-HMAC-SHA512 (2048 rounds, salt "mnemonic" + passphrase)
+/// as `mnemonic_to_seed`, but fills `out_len` bytes. Only a 64-byte output is a
+/// BIP39 seed; any other length is NON-BIP39 key material (e.g. a 32-byte
+/// symmetric encryption key) and must not be fed to BIP32 as a wallet seed.
+///
+/// PBKDF2 output blocks are independent, so the first 64 bytes of a longer
+/// output always equal the BIP39 seed for the same input.
+pub fn mnemonic_to_key_material(mnemonic: &str, passphrase: &str, out_len: usize) -> Result<Vec<u8>> {
+    if out_len == 0 {
+        return Err(KeyManagerError::KeyGenerationError(
+            "Key material length must be greater than zero".to_string()
+        ));
+    }
+
+    let mut output = vec![0u8; out_len];
+    pbkdf2_seed(mnemonic, passphrase, &mut output)?;
+    Ok(output)
+}
+
+/// NON-STANDARD seed derivation with extra PBKDF2 rounds
+///
+/// Deliberately slower than BIP39 to make brute-forcing a weak passphrase
+/// more expensive. The output is NOT a BIP39 seed: no other wallet will
+/// derive the same keys from this mnemonic, so the round count must be
+/// backed up alongside the phrase. The salt is
+/// `"mnemonic-hardened{rounds}{passphrase}"`, so the result can never equal
+/// a standard BIP39 seed or a hardened seed with a different round count.
+///
+/// `rounds` must be at least 2048 (the BIP39 count).
+pub fn mnemonic_to_seed_hardened(mnemonic: &str, passphrase: &str, rounds: u32) -> Result<[u8; 64]> {
+    if rounds < BIP39_ROUNDS {
+        return Err(KeyManagerError::KeyGenerationError(format!(
+            "Hardened seed needs at least {} rounds, got {}",
+            BIP39_ROUNDS, rounds
+        )));
+    }
+
+    let salt = format!("mnemonic-hardened{}{}", rounds, normalize_nfkd(passphrase));
+    let mut seed = [0u8; 64];
+    run_pbkdf2(mnemonic, &salt, rounds, &mut seed)?;
+    Ok(seed)
+}
+
+/// Run the BIP39 PBKDF2-HMAC-SHA512 KDF, filling `output` with key material
+fn pbkdf2_seed(mnemonic: &str, passphrase: &str, output: &mut [u8]) -> Result<()> {
+    // BIP39: salt is "mnemonic" + NFKD(passphrase)
+    let mut salt = format!("mnemonic{}", normalize_nfkd(passphrase));
+    let result = run_pbkdf2(mnemonic, &salt, BIP39_ROUNDS, output);
+    salt.zeroize();
+    result
+}
+
+/// PBKDF2-HMAC-SHA512 over the normalized mnemonic with the given salt and rounds
+fn run_pbkdf2(mnemonic: &str, salt: &str, rounds: u32, output: &mut [u8]) -> Result<()> {
+    // Normalize the mnemonic (NFKD, remove extra whitespace, lowercase)
+    let mnemonic = normalize_mnemonic(&normalize_nfkd(mnemonic))?;
+
+    #[cfg(feature = "tracing")]
+    let started = std::time::Instant::now();
+
+    pbkdf2::<Hmac<Sha512>>(mnemonic.as_bytes(), salt.as_bytes(), rounds, output)
+        .map_err(|e| KeyManagerError::KeyGenerationError(e.to_string()))?;
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        iterations = rounds,
+        elapsed_us = started.elapsed().as_micros() as u64,
+        "pbkdf2 complete"
+    );
+    
+    Ok(())
+}
+
 /// Validate and normalize BIP39 mnemonic
 /// 
 /// Validates that the mnemonic has valid word count (12, 15, 18, 21, or 24)
 /// Normalizes whitespace and validates it's not empty
-fn normalize_mnemonic(mnemonic: &str) -> Result<String> {
+pub(crate) fn normalize_mnemonic(mnemonic: &str) -> Result<String> {
     let mnemonic = mnemonic.trim();
     
     // Check not empty
@@ -80,8 +151,90 @@ fn normalize_mnemonic(mnemonic: &str) -> Result<String> {
 /// ```
 pub fn generate_master_key_from_mnemonic(
     mnemonic: &str, 
-    passphrase: &str
+    passphrase: impl AsRef<str>
 ) -> Result<crate::hd_key::ExtendedKey> {
     let seed = mnemonic_to_seed(mnemonic, passphrase)?;
     crate::hd_key::ExtendedKey::from_seed(&seed)
-}
\ No newline at end of file
+}
+
+/// Master keys for many mnemonics sharing one passphrase, in input order
+///
+/// For bulk wallet imports. Each entry succeeds or fails on its own, so one
+/// bad phrase doesn't hide the rest; the secp256k1 context and curve order
+/// are shared across the whole batch.
+pub fn masters_from_mnemonics(
+    mnemonics: &[&str],
+    passphrase: impl AsRef<str>,
+) -> Vec<Result<crate::hd_key::ExtendedKey>> {
+    let passphrase = passphrase.as_ref();
+    mnemonics
+        .iter()
+        .map(|mnemonic| {
+            let mut seed = mnemonic_to_seed(mnemonic, passphrase)?;
+            let master = crate::hd_key::ExtendedKey::from_seed(&seed);
+            seed.zeroize();
+            master
+        })
+        .collect()
+}
+
+/// Compute the BIP32 master key fingerprint for a mnemonic and passphrase
+///
+/// Returns the 4-byte identifier prefix of the master key, as shown by
+/// hardware wallets and used in descriptor key origins (e.g. `[73c5da0a/84'/0'/0']`).
+pub fn master_fingerprint(mnemonic: &str, passphrase: impl AsRef<str>) -> Result<[u8; 4]> {
+    generate_master_key_from_mnemonic(mnemonic, passphrase)?.fingerprint()
+}
+
+/// Every input and stage of the BIP39 KDF, for diffing against another library
+///
+/// Returned by `mnemonic_to_seed_debug`. All fields are secret material and
+/// are wiped on drop; print them only while debugging a test wallet.
+#[derive(Clone, Debug)]
+pub struct SeedDebug {
+    /// Normalized mnemonic, the PBKDF2 password (NFKD, single spaces)
+    pub mnemonic_bytes: Vec<u8>,
+    /// `"mnemonic"` followed by the NFKD passphrase, the PBKDF2 salt
+    pub salt_bytes: Vec<u8>,
+    /// PBKDF2 iteration count (2048)
+    pub iterations: u32,
+    /// First iteration, HMAC-SHA512(password, salt || 00000001)
+    ///
+    /// If this matches but the seed does not, the inputs agree and the
+    /// other library's iteration loop is at fault.
+    pub first_iteration: [u8; 64],
+    /// The seed, equal to `mnemonic_to_seed`'s
+    pub seed: [u8; 64],
+}
+
+impl Drop for SeedDebug {
+    fn drop(&mut self) {
+        self.mnemonic_bytes.zeroize();
+        self.salt_bytes.zeroize();
+        self.first_iteration.zeroize();
+        self.seed.zeroize();
+    }
+}
+
+/// Diagnostic twin of `mnemonic_to_seed` exposing each KDF stage
+///
+/// For "my seeds don't match" reports: compare the normalized mnemonic,
+/// then the salt, then the first iteration against the other
+/// implementation to find where they part ways. Use `mnemonic_to_seed` for
+/// anything else; this one keeps copies of every secret it touches.
+pub fn mnemonic_to_seed_debug(mnemonic: &str, passphrase: impl AsRef<str>) -> Result<SeedDebug> {
+    let mnemonic_bytes = normalize_mnemonic(&normalize_nfkd(mnemonic))?.into_bytes();
+    let salt_bytes = format!("mnemonic{}", normalize_nfkd(passphrase.as_ref())).into_bytes();
+
+    let mut mac = <Hmac<Sha512> as Mac>::new_from_slice(&mnemonic_bytes)
+        .map_err(|e| KeyManagerError::KeyGenerationError(e.to_string()))?;
+    mac.update(&salt_bytes);
+    mac.update(&1u32.to_be_bytes());
+    let first_iteration: [u8; 64] = mac.finalize().into_bytes().into();
+
+    let mut seed = [0u8; 64];
+    pbkdf2::<Hmac<Sha512>>(&mnemonic_bytes, &salt_bytes, BIP39_ROUNDS, &mut seed)
+        .map_err(|e| KeyManagerError::KeyGenerationError(e.to_string()))?;
+
+    Ok(SeedDebug { mnemonic_bytes, salt_bytes, iterations: BIP39_ROUNDS, first_iteration, seed })
+}
@@ -0,0 +1,95 @@
+//! SeedSigner's SeedQR and CompactSeedQR payloads
+//!
+//! Standard SeedQR is the phrase's BIP39 word indices as zero-padded 4-digit
+//! decimals, concatenated (48 digits for 12 words, 96 for 24), meant for a
+//! numeric-mode QR code. CompactSeedQR is the raw entropy (16 or 32 bytes)
+//! for a byte-mode QR code. Only the payloads live here, not QR rendering.
+//! The spec covers 12- and 24-word phrases only.
+
+use crate::error::{KeyManagerError, Result};
+use crate::mnemonic::{self, WORDLIST};
+use zeroize::Zeroize;
+
+/// Digits per word in standard SeedQR
+const DIGITS_PER_WORD: usize = 4;
+
+/// Word counts SeedQR defines
+const WORD_COUNTS: [usize; 2] = [12, 24];
+
+/// Standard SeedQR digit string for a phrase
+pub fn encode(phrase: &str) -> Result<String> {
+    let canonical = canonical_phrase(phrase)?;
+    let digits = canonical
+        .split(' ')
+        .map(|word| {
+            let index = WORDLIST.binary_search(&word).expect("canonical phrase uses wordlist words");
+            format!("{:04}", index)
+        })
+        .collect();
+    Ok(digits)
+}
+
+/// CompactSeedQR bytes (the phrase's entropy) for a phrase
+pub fn encode_compact(phrase: &str) -> Result<Vec<u8>> {
+    let entropy = mnemonic::mnemonic_to_entropy(phrase)?;
+    check_word_count(entropy.len() * 3 / 4)?;
+    Ok(entropy)
+}
+
+/// Phrase from a standard SeedQR digit string, checksum verified
+pub fn decode(digits: &str) -> Result<String> {
+    let digits = digits.trim();
+    if !digits.bytes().all(|b| b.is_ascii_digit()) || !digits.len().is_multiple_of(DIGITS_PER_WORD) {
+        return Err(KeyManagerError::EncodingError(
+            "SeedQR must be a string of 4-digit word indices".to_string(),
+        ));
+    }
+    check_word_count(digits.len() / DIGITS_PER_WORD)?;
+
+    let words = digits
+        .as_bytes()
+        .chunks(DIGITS_PER_WORD)
+        .map(|chunk| {
+            // ASCII digits only, checked above
+            let index: usize = std::str::from_utf8(chunk).expect("ASCII digits").parse().expect("4 digits");
+            WORDLIST.get(index).copied().ok_or_else(|| {
+                KeyManagerError::EncodingError(format!("SeedQR word index {} is out of range (max 2047)", index))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let phrase = words.join(" ");
+
+    mnemonic::validate_mnemonic_checksum(&phrase)?;
+    Ok(phrase)
+}
+
+/// Phrase from CompactSeedQR bytes; the checksum word is recomputed
+pub fn decode_compact(bytes: &[u8]) -> Result<String> {
+    if bytes.len() != 16 && bytes.len() != 32 {
+        return Err(KeyManagerError::EncodingError(format!(
+            "CompactSeedQR must be 16 or 32 bytes, got {}",
+            bytes.len()
+        )));
+    }
+    mnemonic::entropy_to_mnemonic_checked(bytes)
+}
+
+/// The phrase re-rendered from its entropy: lowercase, single-spaced, checksum verified
+fn canonical_phrase(phrase: &str) -> Result<String> {
+    let mut entropy = encode_compact(phrase)?;
+    let canonical = mnemonic::entropy_to_mnemonic_checked(&entropy);
+    entropy.zeroize();
+    canonical
+}
+
+/// Fail unless SeedQR defines a payload for `words`-word phrases
+pub fn check_word_count(words: usize) -> Result<()> {
+    if WORD_COUNTS.contains(&words) {
+        Ok(())
+    } else {
+        Err(KeyManagerError::EncodingError(format!(
+            "SeedQR supports 12- and 24-word phrases, not {} words",
+            words
+        )))
+    }
+}
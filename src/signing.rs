@@ -0,0 +1,286 @@
+use crate::address;
+use crate::entropy::{EntropySource, SystemEntropy};
+use crate::error::{KeyManagerError, Result};
+use crate::hd_key::ExtendedKey;
+use crate::network::Network;
+use crate::path::DerivationPath;
+use crate::utils::base64;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::{All, Message, Secp256k1, SecretKey, SECP256K1};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock, RwLockReadGuard};
+use zeroize::Zeroize;
+
+pub use secp256k1::ecdsa::Signature;
+
+/// Context every signature is made with, blinded by `rerandomize_context`
+///
+/// Kept apart from the global `SECP256K1`, which is immutable and so can
+/// never be re-randomized. Verification and public key work stay on the
+/// global context; only operations that touch a private key need blinding.
+fn signing_context_lock() -> &'static RwLock<Secp256k1<All>> {
+    static CONTEXT: OnceLock<RwLock<Secp256k1<All>>> = OnceLock::new();
+    CONTEXT.get_or_init(|| RwLock::new(Secp256k1::new()))
+}
+
+/// Re-blind the signing context with 32 fresh bytes from the OS generator
+///
+/// libsecp256k1 blinds its precomputed tables with a random scalar so the
+/// timing and power profile of signing does not follow the private key.
+/// Every signing call here already does this first; long-lived signing
+/// servers can also call it on a timer. Concurrent signers wait for it.
+pub fn rerandomize_context() -> Result<()> {
+    let mut seed = [0u8; 32];
+    let filled = SystemEntropy::new().fill_bytes(&mut seed);
+    if filled.is_ok() {
+        signing_context_lock()
+            .write()
+            .map_err(|_| KeyManagerError::Secp256k1Error("Signing context lock poisoned".to_string()))?
+            .seeded_randomize(&seed);
+    }
+    seed.zeroize();
+    filled
+}
+
+/// The signing context, freshly re-randomized
+pub(crate) fn signing_context() -> Result<RwLockReadGuard<'static, Secp256k1<All>>> {
+    rerandomize_context()?;
+    signing_context_lock()
+        .read()
+        .map_err(|_| KeyManagerError::Secp256k1Error("Signing context lock poisoned".to_string()))
+}
+
+/// Sign a single 32-byte digest with the key's private key (RFC 6979 nonce)
+pub fn sign_ecdsa(key: &ExtendedKey, digest: &[u8; 32]) -> Result<Signature> {
+    let secret_key = secret_key(key)?;
+    Ok(signing_context()?.sign_ecdsa(&Message::from_digest(*digest), &secret_key))
+}
+
+/// Sign many digests with one key
+///
+/// The secret key is parsed once and every signature reuses one freshly
+/// re-randomized signing context. Signatures are returned in the same
+/// order as `digests`.
+pub fn sign_batch(key: &ExtendedKey, digests: &[[u8; 32]]) -> Result<Vec<Signature>> {
+    let secret_key = secret_key(key)?;
+    Ok(sign_all(&*signing_context()?, &secret_key, digests))
+}
+
+/// Sign each digest with the key at its own derivation path
+///
+/// Items are grouped by parent path (every index before the last), so keys
+/// that share a parent cost one derivation for the prefix plus one child
+/// step each, however the paths were spelled. Signatures are returned in
+/// the same order as `items`.
+pub fn sign_batch_at_paths(master: &ExtendedKey, items: &[(DerivationPath, [u8; 32])]) -> Result<Vec<Signature>> {
+    let mut parents: HashMap<&[u32], ExtendedKey> = HashMap::new();
+    let mut keys = Vec::with_capacity(items.len());
+
+    for (path, _) in items {
+        let Some((&last, parent_indices)) = path.indices().split_last() else {
+            keys.push(secret_key(master)?);
+            continue;
+        };
+        if !parents.contains_key(parent_indices) {
+            let parent = master.derive(&DerivationPath::from_indices(parent_indices.to_vec()))?;
+            parents.insert(parent_indices, parent);
+        }
+        keys.push(secret_key(&parents[parent_indices].derive_child(last)?)?);
+    }
+
+    Ok(sign_with_keys(&*signing_context()?, &keys, items))
+}
+
+/// Prefix of every `signmessage` digest, including its own length byte (24)
+const MESSAGE_MAGIC: &[u8] = b"\x18Bitcoin Signed Message:\n";
+
+impl ExtendedKey {
+    /// Sign a message the way Bitcoin Core's `signmessage` does
+    ///
+    /// Returns the base64 of a 65-byte recoverable signature whose header
+    /// byte (31-34) marks a compressed key, which verifiers pair with the
+    /// key's P2PKH address.
+    pub fn sign_bitcoin_message(&self, message: &str) -> Result<String> {
+        sign_message_with(&secret_key(self)?, message, true)
+    }
+}
+
+/// `signmessage` signature, with a header byte of 27-30 for an uncompressed key or 31-34 for a compressed one
+pub(crate) fn sign_message_with(secret_key: &SecretKey, message: &str, compressed: bool) -> Result<String> {
+    let digest = bitcoin_message_hash(message);
+    let signature = signing_context()?.sign_ecdsa_recoverable(&Message::from_digest(digest), secret_key);
+    let (recovery_id, compact) = signature.serialize_compact();
+
+    let mut encoded = [0u8; 65];
+    encoded[0] = 27 + if compressed { 4 } else { 0 } + recovery_id.to_i32() as u8;
+    encoded[1..].copy_from_slice(&compact);
+    Ok(base64::encode(&encoded))
+}
+
+/// Check a `signmessage` signature against an address
+///
+/// Accepts Bitcoin Core's P2PKH headers (27-34) and the BIP137 headers used
+/// by hardware wallets for P2SH-P2WPKH (35-38) and P2WPKH (39-42) addresses.
+/// Returns `Ok(false)` if the signature is well-formed but recovers a
+/// different key, and an error if it cannot be decoded at all.
+pub fn verify_bitcoin_message(address: &str, message: &str, signature: &str) -> Result<bool> {
+    let encoded = base64::decode(signature)?;
+    let header = message_signature_header(&encoded)?;
+
+    let recovery_id = RecoveryId::from_i32(((header - 27) % 4) as i32)
+        .map_err(|e| KeyManagerError::Secp256k1Error(e.to_string()))?;
+    let signature = RecoverableSignature::from_compact(&encoded[1..], recovery_id)
+        .map_err(|e| KeyManagerError::Secp256k1Error(format!("Invalid message signature: {}", e)))?;
+    let Ok(public_key) = SECP256K1.recover_ecdsa(&Message::from_digest(bitcoin_message_hash(message)), &signature) else {
+        return Ok(false);
+    };
+
+    let networks = [Network::Mainnet, Network::Testnet, Network::Regtest];
+    let matches = match header {
+        27..=30 => networks
+            .iter()
+            .any(|&network| address::p2pkh_from_pubkey(&public_key.serialize_uncompressed(), network) == address),
+        31..=34 => networks
+            .iter()
+            .any(|&network| address::p2pkh_from_pubkey(&public_key.serialize(), network) == address),
+        35..=38 => networks
+            .iter()
+            .any(|&network| address::p2sh_p2wpkh_from_pubkey(&public_key.serialize(), network) == address),
+        _ => networks.iter().any(|&network| {
+            address::p2wpkh_from_pubkey(&public_key.serialize(), network)
+                .is_ok_and(|candidate| candidate.eq_ignore_ascii_case(address))
+        }),
+    };
+    Ok(matches)
+}
+
+/// Public key recovered from a message signature
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecoveredKey {
+    /// Compressed public key
+    pub public_key: [u8; 33],
+    /// False when the header (27-30) says the signer's address hashes the uncompressed key
+    pub compressed: bool,
+}
+
+/// Recover who signed a `signmessage` / BIP137 signature (`header || r || s`)
+///
+/// Unlike `verify_bitcoin_message` this needs no address: the recovered key
+/// is returned for the caller to map to addresses. High-S signatures are
+/// rejected as malleated, since no standard signer produces them.
+pub fn recover_bitcoin_message_signer(message: &str, signature: &[u8]) -> Result<RecoveredKey> {
+    let header = message_signature_header(signature)?;
+    let public_key = recover(&bitcoin_message_hash(message), &signature[1..], (header - 27) % 4)?;
+    Ok(RecoveredKey { public_key, compressed: header >= 31 })
+}
+
+/// Recover the key behind a raw recoverable signature `r || s || recovery id` over `digest`
+pub fn recover_raw_signer(digest: &[u8; 32], signature: &[u8]) -> Result<[u8; 33]> {
+    if signature.len() != 65 {
+        return Err(KeyManagerError::EncodingError(format!(
+            "Recoverable signature must be 65 bytes, got {}",
+            signature.len()
+        )));
+    }
+    recover(digest, &signature[..64], signature[64])
+}
+
+/// Header byte of a 65-byte message signature, checked to be 27-42
+fn message_signature_header(signature: &[u8]) -> Result<u8> {
+    if signature.len() != 65 {
+        return Err(KeyManagerError::EncodingError(format!(
+            "Message signature must be 65 bytes, got {}",
+            signature.len()
+        )));
+    }
+    let header = signature[0];
+    if !(27..=42).contains(&header) {
+        return Err(KeyManagerError::EncodingError(format!(
+            "Invalid message signature header byte {}",
+            header
+        )));
+    }
+    Ok(header)
+}
+
+/// ECDSA public key recovery from a compact `r || s`, rejecting high S
+fn recover(digest: &[u8; 32], compact: &[u8], recovery_id: u8) -> Result<[u8; 33]> {
+    if recovery_id > 3 {
+        return Err(KeyManagerError::EncodingError(format!(
+            "Invalid recovery id {} (must be 0-3)",
+            recovery_id
+        )));
+    }
+    let signature = Signature::from_compact(compact)
+        .map_err(|e| KeyManagerError::Secp256k1Error(format!("Invalid signature: {}", e)))?;
+    let mut normalized = signature;
+    normalized.normalize_s();
+    if normalized != signature {
+        return Err(KeyManagerError::Secp256k1Error(
+            "Malleated signature: S is in the upper half of the curve order".to_string(),
+        ));
+    }
+
+    let recovery_id = RecoveryId::from_i32(recovery_id.into())
+        .map_err(|e| KeyManagerError::Secp256k1Error(e.to_string()))?;
+    let signature = RecoverableSignature::from_compact(compact, recovery_id)
+        .map_err(|e| KeyManagerError::Secp256k1Error(format!("Invalid signature: {}", e)))?;
+    let public_key = SECP256K1
+        .recover_ecdsa(&Message::from_digest(*digest), &signature)
+        .map_err(|_| {
+            KeyManagerError::Secp256k1Error(format!(
+                "Signature does not recover to a public key with recovery id {}",
+                recovery_id.to_i32()
+            ))
+        })?;
+    Ok(public_key.serialize())
+}
+
+/// `SHA256d(magic || compact_size(len) || message)`, the digest `signmessage` signs
+pub fn bitcoin_message_hash(message: &str) -> [u8; 32] {
+    let mut data = MESSAGE_MAGIC.to_vec();
+    crate::psbt::transaction::write_compact_size(&mut data, message.len());
+    data.extend(message.as_bytes());
+    Sha256::digest(Sha256::digest(&data)).into()
+}
+
+/// Split "m/84'/0'/0'/0/5" into ("m/84'/0'/0'/0", Some("5"))
+fn secret_key(key: &ExtendedKey) -> Result<SecretKey> {
+    SecretKey::from_slice(key.private_key())
+        .map_err(|e| KeyManagerError::Secp256k1Error(format!("Invalid private key: {}", e)))
+}
+
+#[cfg(not(feature = "parallel"))]
+fn sign_all(context: &Secp256k1<All>, secret_key: &SecretKey, digests: &[[u8; 32]]) -> Vec<Signature> {
+    digests
+        .iter()
+        .map(|digest| context.sign_ecdsa(&Message::from_digest(*digest), secret_key))
+        .collect()
+}
+
+#[cfg(feature = "parallel")]
+fn sign_all(context: &Secp256k1<All>, secret_key: &SecretKey, digests: &[[u8; 32]]) -> Vec<Signature> {
+    use rayon::prelude::*;
+    digests
+        .par_iter()
+        .map(|digest| context.sign_ecdsa(&Message::from_digest(*digest), secret_key))
+        .collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+fn sign_with_keys(context: &Secp256k1<All>, keys: &[SecretKey], items: &[(DerivationPath, [u8; 32])]) -> Vec<Signature> {
+    keys.iter()
+        .zip(items)
+        .map(|(key, (_, digest))| context.sign_ecdsa(&Message::from_digest(*digest), key))
+        .collect()
+}
+
+#[cfg(feature = "parallel")]
+fn sign_with_keys(context: &Secp256k1<All>, keys: &[SecretKey], items: &[(DerivationPath, [u8; 32])]) -> Vec<Signature> {
+    use rayon::prelude::*;
+    keys.par_iter()
+        .zip(items.par_iter())
+        .map(|(key, (_, digest))| context.sign_ecdsa(&Message::from_digest(*digest), key))
+        .collect()
+}
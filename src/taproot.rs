@@ -0,0 +1,144 @@
+//! BIP341 taproot tweaks, with or without a script tree
+//!
+//! The output key is `Q = P + t*G` with
+//! `t = tagged_hash("TapTweak", x(P) || merkle_root)`, where `P` is the
+//! internal key with even y. Key-path-only outputs (BIP86) omit the merkle
+//! root. Script trees are committed by hashing leaves with `leaf_hash` and
+//! combining them with `branch_hash` (or `merkle_root` for a simple tree).
+
+use crate::error::{KeyManagerError, Result};
+use crate::hd_key::ExtendedKey;
+use crate::network::Network;
+use crate::psbt::transaction::write_compact_size;
+use crate::signing;
+use crate::utils::bech32;
+use crate::utils::hash::tagged_hash;
+use secp256k1::{Keypair, Scalar, XOnlyPublicKey, SECP256K1};
+use zeroize::Zeroize;
+
+/// Leaf version for BIP342 tapscript
+pub const TAPSCRIPT_LEAF_VERSION: u8 = 0xc0;
+
+/// Tweaked taproot key: the x-only output key and the matching private key
+pub struct TweakedKeypair {
+    output_key: [u8; 32],
+    odd_parity: bool,
+    secret_key: [u8; 32],
+}
+
+impl TweakedKeypair {
+    /// X-only output key committed to in the `OP_1 <key>` script
+    pub fn output_key(&self) -> &[u8; 32] {
+        &self.output_key
+    }
+
+    /// Whether the full output key has odd y (needed in script-path control blocks)
+    pub fn odd_parity(&self) -> bool {
+        self.odd_parity
+    }
+
+    /// Tweaked private key for BIP340 key-path signing
+    pub fn secret_key(&self) -> &[u8; 32] {
+        &self.secret_key
+    }
+
+    /// Segwit v1 address of the output key
+    pub fn address(&self, network: Network) -> Result<String> {
+        bech32::encode_segwit(network.bech32_hrp(), 1, &self.output_key)
+    }
+}
+
+impl Drop for TweakedKeypair {
+    fn drop(&mut self) {
+        self.secret_key.zeroize();
+    }
+}
+
+/// Tweak a private key for key-path spending of a taproot output
+///
+/// If the internal public key has odd y, the private key is negated before
+/// the tweak is added, as BIP341 requires. Pass the tree's merkle root for
+/// outputs with script paths, or `None` for key-path-only outputs.
+pub fn tweak_keypair(internal: &ExtendedKey, merkle_root: Option<[u8; 32]>) -> Result<TweakedKeypair> {
+    tweak_secret_key(internal.private_key(), merkle_root)
+}
+
+/// `tweak_keypair` for a raw 32-byte private key
+///
+/// Both steps multiply by the private key, so they run on the blinded
+/// signing context rather than the global one.
+pub fn tweak_secret_key(internal: &[u8; 32], merkle_root: Option<[u8; 32]>) -> Result<TweakedKeypair> {
+    let context = signing::signing_context()?;
+    let keypair = Keypair::from_seckey_slice(&context, internal)
+        .map_err(|e| KeyManagerError::Secp256k1Error(e.to_string()))?;
+    let (internal_key, _) = keypair.x_only_public_key();
+
+    let tweaked = keypair
+        .add_xonly_tweak(&context, &tap_tweak(&internal_key, merkle_root)?)
+        .map_err(|e| KeyManagerError::Secp256k1Error(e.to_string()))?;
+    let (output_key, parity) = tweaked.x_only_public_key();
+
+    Ok(TweakedKeypair {
+        output_key: output_key.serialize(),
+        odd_parity: parity == secp256k1::Parity::Odd,
+        secret_key: tweaked.secret_bytes(),
+    })
+}
+
+/// Tweak an x-only internal public key, returning the output key and whether its y is odd
+pub fn tweak_public_key(internal_key: &[u8; 32], merkle_root: Option<[u8; 32]>) -> Result<([u8; 32], bool)> {
+    let internal_key = XOnlyPublicKey::from_slice(internal_key)
+        .map_err(|e| KeyManagerError::Secp256k1Error(format!("Invalid x-only public key: {}", e)))?;
+
+    let (output_key, parity) = internal_key
+        .add_tweak(SECP256K1, &tap_tweak(&internal_key, merkle_root)?)
+        .map_err(|e| KeyManagerError::Secp256k1Error(e.to_string()))?;
+    Ok((output_key.serialize(), parity == secp256k1::Parity::Odd))
+}
+
+/// TapLeaf hash of a script: `tagged_hash("TapLeaf", version || compact_size(len) || script)`
+pub fn leaf_hash(script: &[u8], leaf_version: u8) -> [u8; 32] {
+    let mut data = vec![leaf_version & 0xfe];
+    write_compact_size(&mut data, script.len());
+    data.extend(script);
+    tagged_hash("TapLeaf", &data)
+}
+
+/// TapBranch hash of two child nodes (children are sorted first)
+pub fn branch_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(left);
+    data[32..].copy_from_slice(right);
+    tagged_hash("TapBranch", &data)
+}
+
+/// Merkle root of a simple tree built from leaf hashes
+///
+/// Adjacent nodes are paired level by level (`[A, B, C]` becomes
+/// `((A, B), C)`). Trees of any other shape can be built with `branch_hash`.
+/// Returns `None` for an empty list (a key-path-only output).
+pub fn merkle_root(leaves: &[[u8; 32]]) -> Option<[u8; 32]> {
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => branch_hash(a, b),
+                [single] => *single,
+                _ => unreachable!("chunks(2) yields one or two nodes"),
+            })
+            .collect();
+    }
+    level.first().copied()
+}
+
+/// Tweak scalar `t = tagged_hash("TapTweak", x(P) || merkle_root)`
+fn tap_tweak(internal_key: &XOnlyPublicKey, merkle_root: Option<[u8; 32]>) -> Result<Scalar> {
+    let mut data = internal_key.serialize().to_vec();
+    if let Some(root) = merkle_root {
+        data.extend(root);
+    }
+    Scalar::from_be_bytes(tagged_hash("TapTweak", &data))
+        .map_err(|_| KeyManagerError::Secp256k1Error("TapTweak hash exceeds curve order".to_string()))
+}
@@ -1,4 +1,13 @@
 use crate::error::{KeyManagerError, Result};
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+
+pub mod base58;
+pub mod base64;
+pub mod bech32;
+pub mod ec;
+pub mod hash;
+pub(crate) mod secret;
 
 /// Validates that word count is one of the standard BIP39 counts
 pub fn validate_word_count(count: usize) -> Result<()> {
@@ -8,38 +17,76 @@ pub fn validate_word_count(count: usize) -> Result<()> {
     }
 }
 
-/// Validates that a derivation path starts with 'm'
+/// Validates that a derivation path starts with 'm' (private) or 'M' (public)
 pub fn validate_derivation_path_format(path: &str) -> Result<()> {
-    if !path.starts_with('m') {
+    if !path.starts_with(['m', 'M']) {
         return Err(KeyManagerError::InvalidDerivationPath(
-            "Path must start with 'm'".to_string(),
+            "Path must start with 'm' or 'M'".to_string(),
         ));
     }
     Ok(())
 }
 
+/// Lowercase hex digits, indexed by nibble
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Uppercase hex digits, indexed by nibble
+const HEX_DIGITS_UPPER: &[u8; 16] = b"0123456789ABCDEF";
+
 /// Converts hex string to bytes
 pub fn hex_to_bytes(hex_str: &str) -> Result<Vec<u8>> {
-    let cleaned = hex_str.trim_start_matches("0x");
+    let cleaned = hex_str.trim_start_matches("0x").as_bytes();
 
-    if cleaned.len() % 2 != 0 {
+    if !cleaned.len().is_multiple_of(2) {
         return Err(KeyManagerError::EncodingError(
             "Hex string must have even length".to_string(),
         ));
     }
 
-    (0..cleaned.len())
-        .step_by(2)
-        .map(|i| {
-            u8::from_str_radix(&cleaned[i..i + 2], 16)
-                .map_err(|e| KeyManagerError::EncodingError(e.to_string()))
-        })
-        .collect()
+    let mut bytes = Vec::with_capacity(cleaned.len() / 2);
+    for pair in cleaned.chunks_exact(2) {
+        bytes.push((hex_nibble(pair[0])? << 4) | hex_nibble(pair[1])?);
+    }
+    Ok(bytes)
+}
+
+fn hex_nibble(c: u8) -> Result<u8> {
+    match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => Err(KeyManagerError::EncodingError(format!(
+            "Invalid hex character: {:?}",
+            c as char
+        ))),
+    }
 }
 
 /// Converts bytes to hex string
 pub fn bytes_to_hex(bytes: &[u8]) -> String {
-    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    let mut out = String::with_capacity(bytes.len() * 2);
+    write_hex(&mut out, bytes);
+    out
+}
+
+/// Converts bytes to an uppercase hex string (lowercase stays the default)
+pub fn bytes_to_hex_upper(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2);
+    write_hex_digits(&mut out, bytes, HEX_DIGITS_UPPER);
+    out
+}
+
+/// Append the lowercase hex encoding of `bytes` to an existing buffer
+pub fn write_hex(out: &mut String, bytes: &[u8]) {
+    write_hex_digits(out, bytes, HEX_DIGITS);
+}
+
+fn write_hex_digits(out: &mut String, bytes: &[u8], digits: &[u8; 16]) {
+    out.reserve(bytes.len() * 2);
+    for &b in bytes {
+        out.push(digits[(b >> 4) as usize] as char);
+        out.push(digits[(b & 0x0f) as usize] as char);
+    }
 }
 
 /// Validate if string is valid hex (0-9a-fA-F)
@@ -50,7 +97,7 @@ pub fn validate_hex_string(hex: &str) -> Result<()> {
     
     let hex_clean = hex.strip_prefix("0x").unwrap_or(hex);
     
-    if hex_clean.len() % 2 != 0 {
+    if !hex_clean.len().is_multiple_of(2) {
         return Err(KeyManagerError::EncodingError("Odd length hex".to_string()));
     }
     
@@ -71,4 +118,79 @@ pub fn word_count_description(count: usize) -> String {
         24 => "24-word (256-bit entropy)".to_string(),
         _ => format!("{}-word (invalid)", count),
     }
-}
\ No newline at end of file
+}
+
+/// Bits of entropy behind a BIP39 phrase of `word_count` words
+///
+/// The `log2` of the number of possible valid phrases: every word carries 11
+/// bits, but the checksum bits are derived from the rest, so only the
+/// entropy counts (128 for 12 words up to 256 for 24).
+pub fn mnemonic_space_bits(word_count: usize) -> Result<usize> {
+    validate_word_count(word_count)?;
+    Ok(word_count * 11 * 32 / 33)
+}
+
+/// Brute-force strength of a phrase length, against AES key sizes
+///
+/// Anything past 128 bits is headroom: the secp256k1 keys derived from the
+/// phrase only offer about 128-bit security themselves.
+pub fn security_level_description(word_count: usize) -> String {
+    let comparison = match word_count {
+        12 => "~comparable to AES-128",
+        15 => "between AES-128 and AES-192",
+        18 => "~comparable to AES-192",
+        21 => "between AES-192 and AES-256",
+        24 => "~comparable to AES-256",
+        _ => return format!("{}-word (invalid)", word_count),
+    };
+    let bits = word_count * 11 * 32 / 33;
+    format!("{}-bit, {}", bits, comparison)
+}
+
+/// Hash160 = RIPEMD160(SHA256(data)), used for key fingerprints and addresses
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+    let sha256_hash = Sha256::digest(data);
+    let mut hash = [0u8; 20];
+    hash.copy_from_slice(&Ripemd160::digest(sha256_hash));
+    hash
+}
+
+/// Overwrite a string's whole buffer (including spare capacity) with zeros, then clear it
+///
+/// Use this on mnemonics and passphrases held in a `String` once they are no
+/// longer needed; `clear()` alone leaves the bytes in memory.
+pub fn zeroize_string(s: &mut String) {
+    use zeroize::Zeroize;
+    s.zeroize();
+}
+
+/// Compare two byte strings without an early exit on the first difference
+///
+/// The running time depends only on the lengths, not on where (or whether)
+/// the contents differ. Lengths are not secret and are compared directly.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let difference = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    std::hint::black_box(difference) == 0
+}
+
+/// Encode a string as a JSON string literal (with quotes)
+pub fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
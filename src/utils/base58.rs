@@ -0,0 +1,94 @@
+use crate::error::{KeyManagerError, Result};
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use sha2::{Digest, Sha256};
+
+pub(crate) const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Encode bytes as plain Base58 (no checksum)
+pub fn encode(data: &[u8]) -> String {
+    let mut num = BigUint::from_bytes_be(data);
+    let mut encoded = String::new();
+    let base = BigUint::from(58u32);
+    let zero = BigUint::from(0u32);
+
+    while num > zero {
+        let remainder = &num % &base;
+        // Convert remainder to usize (always < 58, so safe)
+        let digit: usize = remainder.to_u64().unwrap_or(0) as usize;
+        encoded.insert(0, BASE58_ALPHABET[digit] as char);
+        num /= &base;
+    }
+
+    // Add leading '1's for leading zero bytes
+    for &byte in data.iter() {
+        if byte == 0 {
+            encoded.insert(0, '1');
+        } else {
+            break;
+        }
+    }
+
+    encoded
+}
+
+/// Decode a plain Base58 string (no checksum verification)
+pub fn decode(encoded: &str) -> Result<Vec<u8>> {
+    let mut num = BigUint::from(0u32);
+    let base = BigUint::from(58u32);
+
+    for c in encoded.chars() {
+        let digit = BASE58_ALPHABET
+            .iter()
+            .position(|&b| b as char == c)
+            .ok_or_else(|| KeyManagerError::EncodingError(
+                format!("Invalid Base58 character: '{}'", c)
+            ))?;
+        num = num * &base + BigUint::from(digit as u32);
+    }
+
+    // Leading '1's encode leading zero bytes
+    let leading_zeros = encoded.chars().take_while(|&c| c == '1').count();
+    let mut decoded = vec![0u8; leading_zeros];
+    if num > BigUint::from(0u32) {
+        decoded.extend_from_slice(&num.to_bytes_be());
+    }
+
+    Ok(decoded)
+}
+
+/// First 4 bytes of SHA256(SHA256(data)), the Base58Check checksum
+pub fn checksum(data: &[u8]) -> [u8; 4] {
+    let hash1 = Sha256::digest(data);
+    let hash2 = Sha256::digest(hash1);
+    let mut checksum = [0u8; 4];
+    checksum.copy_from_slice(&hash2[..4]);
+    checksum
+}
+
+/// Encode bytes as Base58Check (payload || checksum)
+pub fn encode_check(data: &[u8]) -> String {
+    let mut payload = data.to_vec();
+    payload.extend_from_slice(&checksum(data));
+    encode(&payload)
+}
+
+/// Decode a Base58Check string, verifying and stripping the 4-byte checksum
+pub fn decode_check(encoded: &str) -> Result<Vec<u8>> {
+    let mut decoded = decode(encoded)?;
+    if decoded.len() < 4 {
+        return Err(KeyManagerError::EncodingError(
+            "Base58Check payload too short".to_string(),
+        ));
+    }
+
+    let payload_len = decoded.len() - 4;
+    if checksum(&decoded[..payload_len]) != decoded[payload_len..] {
+        return Err(KeyManagerError::EncodingError(
+            "Invalid Base58Check checksum".to_string(),
+        ));
+    }
+
+    decoded.truncate(payload_len);
+    Ok(decoded)
+}
@@ -0,0 +1,52 @@
+use crate::error::{KeyManagerError, Result};
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode bytes as standard (RFC 4648) base64 with `=` padding
+pub fn encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decode standard padded base64, ignoring ASCII whitespace (line breaks)
+pub fn decode(encoded: &str) -> Result<Vec<u8>> {
+    let symbols: Vec<u8> = encoded.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if !symbols.len().is_multiple_of(4) {
+        return Err(KeyManagerError::EncodingError(
+            "Base64 length must be a multiple of 4".to_string(),
+        ));
+    }
+
+    let mut out = Vec::with_capacity(symbols.len() / 4 * 3);
+    let chunk_count = symbols.len() / 4;
+    for (i, chunk) in symbols.chunks_exact(4).enumerate() {
+        let padding = chunk.iter().rev().take_while(|&&b| b == b'=').count();
+        if padding > 2 || (padding > 0 && i + 1 != chunk_count) {
+            return Err(KeyManagerError::EncodingError("Invalid base64 padding".to_string()));
+        }
+
+        let mut n = 0u32;
+        for &symbol in &chunk[..4 - padding] {
+            let value = BASE64_ALPHABET.iter().position(|&c| c == symbol).ok_or_else(|| {
+                KeyManagerError::EncodingError(format!("Invalid base64 character: {:?}", symbol as char))
+            })?;
+            n = n << 6 | value as u32;
+        }
+        n <<= 6 * padding as u32;
+
+        let bytes = n.to_be_bytes();
+        out.extend_from_slice(&bytes[1..4 - padding]);
+    }
+    Ok(out)
+}
@@ -0,0 +1,318 @@
+use crate::error::{KeyManagerError, Result};
+
+/// Bech32 data-part alphabet (BIP173)
+pub(crate) const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+const BECH32_CONST: u32 = 1;
+const BECH32M_CONST: u32 = 0x2bc830a3;
+
+/// Checksum variant: original bech32 (BIP173) or bech32m (BIP350)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Variant {
+    fn constant(self) -> u32 {
+        match self {
+            Variant::Bech32 => BECH32_CONST,
+            Variant::Bech32m => BECH32M_CONST,
+        }
+    }
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut chk: u32 = 1;
+    for &value in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x1ffffff) << 5) ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut expanded: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    expanded.push(0);
+    expanded.extend(hrp.bytes().map(|b| b & 31));
+    expanded
+}
+
+fn create_checksum(hrp: &str, data: &[u8], variant: Variant) -> [u8; 6] {
+    let mut values = hrp_expand(hrp);
+    values.extend_from_slice(data);
+    values.extend_from_slice(&[0u8; 6]);
+    let modulus = polymod(&values) ^ variant.constant();
+
+    let mut checksum = [0u8; 6];
+    for (i, c) in checksum.iter_mut().enumerate() {
+        *c = ((modulus >> (5 * (5 - i))) & 31) as u8;
+    }
+    checksum
+}
+
+/// Determine which checksum variant (if any) a data part verifies under
+///
+/// `data` is the 5-bit data part *including* the trailing 6 checksum values.
+pub fn verify_checksum(hrp: &str, data: &[u8]) -> Option<Variant> {
+    let mut values = hrp_expand(hrp);
+    values.extend_from_slice(data);
+    match polymod(&values) {
+        BECH32_CONST => Some(Variant::Bech32),
+        BECH32M_CONST => Some(Variant::Bech32m),
+        _ => None,
+    }
+}
+
+/// Split a bech32 string into its lowercase HRP and 5-bit data part
+///
+/// Checks structure only (case, separator, charset, lengths); the returned
+/// data still includes the 6 checksum values and is NOT checksum-verified.
+pub fn split(encoded: &str) -> Result<(String, Vec<u8>)> {
+    let has_lower = encoded.chars().any(|c| c.is_ascii_lowercase());
+    let has_upper = encoded.chars().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return Err(KeyManagerError::EncodingError(
+            "Bech32 string has mixed case".to_string(),
+        ));
+    }
+
+    let encoded = encoded.to_ascii_lowercase();
+    let separator = encoded.rfind('1').ok_or_else(|| {
+        KeyManagerError::EncodingError("Bech32 separator '1' not found".to_string())
+    })?;
+
+    let (hrp, data_part) = (&encoded[..separator], &encoded[separator + 1..]);
+    if hrp.is_empty() || hrp.len() > 83 {
+        return Err(KeyManagerError::EncodingError(
+            "Invalid bech32 human-readable part length".to_string(),
+        ));
+    }
+    if !hrp.bytes().all(|b| (33..=126).contains(&b)) {
+        return Err(KeyManagerError::EncodingError(
+            "Invalid character in bech32 human-readable part".to_string(),
+        ));
+    }
+    if data_part.len() < 6 {
+        return Err(KeyManagerError::EncodingError(
+            "Bech32 data part too short".to_string(),
+        ));
+    }
+
+    let data = data_part
+        .bytes()
+        .map(|b| {
+            CHARSET
+                .iter()
+                .position(|&c| c == b)
+                .map(|p| p as u8)
+                .ok_or_else(|| KeyManagerError::EncodingError(
+                    format!("Invalid bech32 character: '{}'", b as char)
+                ))
+        })
+        .collect::<Result<Vec<u8>>>()?;
+
+    Ok((hrp.to_string(), data))
+}
+
+/// Encode an HRP and 5-bit data values as a bech32/bech32m string
+pub fn encode(hrp: &str, data: &[u8], variant: Variant) -> Result<String> {
+    if hrp.is_empty() || hrp.bytes().any(|b| !(33..=126).contains(&b)) {
+        return Err(KeyManagerError::EncodingError(
+            "Invalid bech32 human-readable part".to_string(),
+        ));
+    }
+    if data.iter().any(|&d| d > 31) {
+        return Err(KeyManagerError::EncodingError(
+            "Bech32 data values must be 5-bit".to_string(),
+        ));
+    }
+
+    let hrp = hrp.to_ascii_lowercase();
+    let checksum = create_checksum(&hrp, data, variant);
+
+    let mut encoded = hrp.clone();
+    encoded.push('1');
+    for &d in data.iter().chain(checksum.iter()) {
+        encoded.push(CHARSET[d as usize] as char);
+    }
+    Ok(encoded)
+}
+
+/// Decode and checksum-verify a bech32/bech32m string
+///
+/// Returns the lowercase HRP, the 5-bit data values (checksum stripped) and
+/// the variant the checksum verified under. On a checksum failure that one
+/// substituted character explains, the error names it (see `locate_typo`).
+pub fn decode(encoded: &str) -> Result<(String, Vec<u8>, Variant)> {
+    let (hrp, mut data) = split(encoded)?;
+    let variant = verify_checksum(&hrp, &data).ok_or_else(|| {
+        KeyManagerError::EncodingError(match locate_typo(encoded) {
+            Some(typo) => format!("Invalid bech32 checksum; {}", typo),
+            None => "Invalid bech32 checksum".to_string(),
+        })
+    })?;
+
+    data.truncate(data.len() - 6);
+    Ok((hrp, data, variant))
+}
+
+/// A single substituted character that would make a bech32 checksum valid
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Typo {
+    /// Zero-based character position in the whole string (HRP included)
+    pub position: usize,
+    /// Character found at that position
+    pub found: char,
+    /// Replacement that makes the checksum verify
+    pub suggested: char,
+}
+
+impl std::fmt::Display for Typo {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "possible typo at position {}: did you mean '{}' instead of '{}'?",
+            self.position, self.suggested, self.found
+        )
+    }
+}
+
+/// Find the one character substitution that repairs a bad checksum
+///
+/// Tries every replacement at every data-part position. When the candidate
+/// starts with a witness version (0-16), only the checksum variant that
+/// version requires counts (BIP350: bech32 for v0, bech32m for v1+), so a
+/// "fix" that yields an unspendable address is never suggested. Returns
+/// `None` if the checksum is already valid, if no single substitution fixes
+/// it, or if more than one does (the guess would be ambiguous). Typos in the
+/// HRP are not searched.
+pub fn locate_typo(encoded: &str) -> Option<Typo> {
+    let (hrp, data) = split(encoded).ok()?;
+    if verify_checksum(&hrp, &data).is_some() {
+        return None;
+    }
+
+    let mut candidates = Vec::new();
+    let mut trial = data.clone();
+    for index in 0..data.len() {
+        for value in (0..32u8).filter(|&value| value != data[index]) {
+            trial[index] = value;
+            let verified = match (verify_checksum(&hrp, &trial), trial[0]) {
+                (Some(variant), version @ 0..=16) => variant == segwit_variant(version),
+                (verified, _) => verified.is_some(),
+            };
+            if verified {
+                candidates.push((index, value));
+            }
+        }
+        trial[index] = data[index];
+    }
+
+    let [(index, value)] = candidates[..] else {
+        return None;
+    };
+    let position = hrp.len() + 1 + index;
+    let uppercase = encoded.chars().any(|c| c.is_ascii_uppercase());
+    let in_case = |c: u8| if uppercase { c.to_ascii_uppercase() as char } else { c as char };
+    Some(Typo {
+        position,
+        found: in_case(CHARSET[data[index] as usize]),
+        suggested: in_case(CHARSET[value as usize]),
+    })
+}
+
+/// Regroup bits, e.g. 8-bit bytes to 5-bit bech32 values and back
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max_value: u32 = (1 << to) - 1;
+    let mut converted = Vec::new();
+
+    for &value in data {
+        if (value as u32) >> from != 0 {
+            return Err(KeyManagerError::EncodingError(
+                "Invalid value for bit conversion".to_string(),
+            ));
+        }
+        acc = (acc << from) | value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            converted.push(((acc >> bits) & max_value) as u8);
+        }
+    }
+
+    if pad {
+        if bits > 0 {
+            converted.push(((acc << (to - bits)) & max_value) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max_value) != 0 {
+        return Err(KeyManagerError::EncodingError(
+            "Invalid padding in bit conversion".to_string(),
+        ));
+    }
+
+    Ok(converted)
+}
+
+/// Encode a segwit address (BIP173 for v0, BIP350 bech32m for v1+)
+pub fn encode_segwit(hrp: &str, version: u8, program: &[u8]) -> Result<String> {
+    validate_witness_program(version, program)?;
+
+    let variant = segwit_variant(version);
+    let mut data = vec![version];
+    data.extend(convert_bits(program, 8, 5, true)?);
+    encode(hrp, &data, variant)
+}
+
+/// Decode a segwit address, returning (hrp, witness version, witness program)
+pub fn decode_segwit(address: &str) -> Result<(String, u8, Vec<u8>)> {
+    let (hrp, data, variant) = decode(address)?;
+    if data.is_empty() {
+        return Err(KeyManagerError::EncodingError(
+            "Empty witness data".to_string(),
+        ));
+    }
+
+    let version = data[0];
+    let expected = segwit_variant(version);
+    if variant != expected {
+        return Err(KeyManagerError::EncodingError(
+            format!("Witness version {} requires {:?} checksum", version, expected)
+        ));
+    }
+
+    let program = convert_bits(&data[1..], 5, 8, false)?;
+    validate_witness_program(version, &program)?;
+    Ok((hrp, version, program))
+}
+
+/// Checksum variant a witness version must use (BIP350)
+fn segwit_variant(version: u8) -> Variant {
+    if version == 0 { Variant::Bech32 } else { Variant::Bech32m }
+}
+
+fn validate_witness_program(version: u8, program: &[u8]) -> Result<()> {
+    if version > 16 {
+        return Err(KeyManagerError::EncodingError(
+            format!("Invalid witness version: {}", version)
+        ));
+    }
+    if program.len() < 2 || program.len() > 40 {
+        return Err(KeyManagerError::EncodingError(
+            format!("Invalid witness program length: {}", program.len())
+        ));
+    }
+    if version == 0 && program.len() != 20 && program.len() != 32 {
+        return Err(KeyManagerError::EncodingError(
+            format!("Invalid v0 witness program length: {}", program.len())
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use crate::error::{KeyManagerError, Result};
+use secp256k1::{PublicKey, Scalar, SecretKey, SECP256K1};
+
+/// Parse a serialized (compressed or uncompressed) secp256k1 public key
+fn parse_public_key(bytes: &[u8]) -> Result<PublicKey> {
+    PublicKey::from_slice(bytes)
+        .map_err(|e| KeyManagerError::Secp256k1Error(format!("Invalid public key: {}", e)))
+}
+
+/// Add secp256k1 public key points, returning the compressed sum
+///
+/// Shared primitive for CKDpub and multi-key schemes. Fails on empty input,
+/// any invalid key, or if the sum is the point at infinity (e.g. a key plus
+/// its own negation).
+pub fn combine_public_keys(keys: &[&[u8]]) -> Result<[u8; 33]> {
+    if keys.is_empty() {
+        return Err(KeyManagerError::Secp256k1Error(
+            "No public keys to combine".to_string(),
+        ));
+    }
+
+    let parsed = keys
+        .iter()
+        .map(|key| parse_public_key(key))
+        .collect::<Result<Vec<PublicKey>>>()?;
+    let refs: Vec<&PublicKey> = parsed.iter().collect();
+
+    let sum = PublicKey::combine_keys(&refs).map_err(|e| {
+        KeyManagerError::Secp256k1Error(format!("Public key combination failed: {}", e))
+    })?;
+    Ok(sum.serialize())
+}
+
+/// Add two compressed public keys: `a + b`
+///
+/// Fails if either key is invalid or the sum is the point at infinity.
+pub fn pubkey_add(a: &[u8; 33], b: &[u8; 33]) -> Result<[u8; 33]> {
+    combine_public_keys(&[a, b])
+}
+
+/// Add `scalar * G` to a compressed public key (the public half of BIP32 CKD)
+///
+/// The scalar must be nonzero and below the curve order; a zero tweak would
+/// return the input unchanged, which is never what a protocol intends.
+pub fn pubkey_tweak_add(pubkey: &[u8; 33], scalar: &[u8; 32]) -> Result<[u8; 33]> {
+    if scalar.iter().all(|&b| b == 0) {
+        return Err(KeyManagerError::Secp256k1Error(
+            "Tweak must be nonzero".to_string(),
+        ));
+    }
+    let tweak = Scalar::from_be_bytes(*scalar).map_err(|_| {
+        KeyManagerError::Secp256k1Error("Tweak is not below the curve order".to_string())
+    })?;
+
+    let tweaked = parse_public_key(pubkey)?
+        .add_exp_tweak(SECP256K1, &tweak)
+        .map_err(|e| KeyManagerError::Secp256k1Error(format!("Public key tweak failed: {}", e)))?;
+    Ok(tweaked.serialize())
+}
+
+/// Compressed public key `scalar * G`
+///
+/// The scalar must be a valid secret key (nonzero, below the curve order).
+pub fn pubkey_mul_generator(scalar: &[u8; 32]) -> Result<[u8; 33]> {
+    let secret_key = SecretKey::from_slice(scalar)
+        .map_err(|e| KeyManagerError::Secp256k1Error(format!("Invalid scalar: {}", e)))?;
+    Ok(PublicKey::from_secret_key(SECP256K1, &secret_key).serialize())
+}
+
+/// Negate a compressed public key: `-P` (same x, opposite y parity)
+pub fn pubkey_negate(pubkey: &[u8; 33]) -> Result<[u8; 33]> {
+    Ok(parse_public_key(pubkey)?.negate(SECP256K1).serialize())
+}
+
+/// Uncompressed 65-byte SEC1 encoding (`04 || x || y`) of a compressed public key
+pub fn pubkey_uncompress(pubkey: &[u8; 33]) -> Result<[u8; 65]> {
+    Ok(parse_public_key(pubkey)?.serialize_uncompressed())
+}
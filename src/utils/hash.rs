@@ -0,0 +1,45 @@
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+
+/// Tags with a precomputed `SHA256(tag) || SHA256(tag)` prefix
+const COMMON_TAGS: [&str; 7] = [
+    "TapTweak",
+    "TapLeaf",
+    "TapBranch",
+    "TapSighash",
+    "BIP0340/challenge",
+    "BIP0340/aux",
+    "BIP0340/nonce",
+];
+
+/// BIP340 tagged hash: `SHA256(SHA256(tag) || SHA256(tag) || data)`
+///
+/// Hashers already fed the tag prefix are memoized for the Taproot and
+/// BIP340 tags, so those calls skip the two extra compressions; any other
+/// tag hashes its prefix on each call.
+pub fn tagged_hash(tag: &str, data: &[u8]) -> [u8; 32] {
+    let mut hasher = tagged_hasher(tag);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+/// SHA256 state after absorbing `SHA256(tag) || SHA256(tag)`
+fn tagged_hasher(tag: &str) -> Sha256 {
+    static PREFIXED: OnceLock<Vec<Sha256>> = OnceLock::new();
+
+    match COMMON_TAGS.iter().position(|&common| common == tag) {
+        Some(index) => {
+            let prefixed = PREFIXED.get_or_init(|| COMMON_TAGS.iter().map(|tag| prefixed_hasher(tag)).collect());
+            prefixed[index].clone()
+        }
+        None => prefixed_hasher(tag),
+    }
+}
+
+fn prefixed_hasher(tag: &str) -> Sha256 {
+    let tag = Sha256::digest(tag.as_bytes());
+    let mut hasher = Sha256::new();
+    hasher.update(tag);
+    hasher.update(tag);
+    hasher
+}
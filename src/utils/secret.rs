@@ -0,0 +1,302 @@
+//! Storage for secret bytes (private keys, chain codes, mnemonics)
+//!
+//! Secrets are zeroized when dropped. With the `locked-memory` feature they
+//! also live in their own page-aligned allocation that is locked in RAM
+//! (`mlock` on Unix, `VirtualLock` on Windows) so it cannot be swapped to
+//! disk. If locking fails (usually `RLIMIT_MEMLOCK` or the Windows working
+//! set limit is exhausted, or the platform has neither call) the secret is
+//! kept in ordinary memory. The library never prints: the failure is logged
+//! once as a `tracing` warning when that feature is on, and callers can
+//! check `memory_lock_failed()` themselves.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use zeroize::Zeroize;
+
+/// Fixed-size secret, e.g. a 32-byte private key
+pub(crate) struct SecretArray<const N: usize> {
+    #[cfg(not(feature = "locked-memory"))]
+    bytes: [u8; N],
+    #[cfg(feature = "locked-memory")]
+    bytes: locked::LockedBuffer,
+}
+
+impl<const N: usize> SecretArray<N> {
+    /// Whether the bytes sit in page-locked memory
+    #[cfg(all(test, feature = "locked-memory"))]
+    pub(crate) fn is_locked(&self) -> bool {
+        self.bytes.is_locked()
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for SecretArray<N> {
+    #[cfg(not(feature = "locked-memory"))]
+    fn from(bytes: [u8; N]) -> Self {
+        SecretArray { bytes }
+    }
+
+    #[cfg(feature = "locked-memory")]
+    fn from(mut bytes: [u8; N]) -> Self {
+        let mut buffer = locked::LockedBuffer::new(N);
+        buffer.as_mut_slice().copy_from_slice(&bytes);
+        bytes.zeroize();
+        SecretArray { bytes: buffer }
+    }
+}
+
+impl<const N: usize> Deref for SecretArray<N> {
+    type Target = [u8; N];
+
+    #[cfg(not(feature = "locked-memory"))]
+    fn deref(&self) -> &[u8; N] {
+        &self.bytes
+    }
+
+    #[cfg(feature = "locked-memory")]
+    fn deref(&self) -> &[u8; N] {
+        self.bytes.as_slice().try_into().expect("buffer holds N bytes")
+    }
+}
+
+impl<const N: usize> DerefMut for SecretArray<N> {
+    #[cfg(not(feature = "locked-memory"))]
+    fn deref_mut(&mut self) -> &mut [u8; N] {
+        &mut self.bytes
+    }
+
+    #[cfg(feature = "locked-memory")]
+    fn deref_mut(&mut self) -> &mut [u8; N] {
+        self.bytes.as_mut_slice().try_into().expect("buffer holds N bytes")
+    }
+}
+
+impl<const N: usize> Clone for SecretArray<N> {
+    fn clone(&self) -> Self {
+        SecretArray::from(**self)
+    }
+}
+
+// Never show the bytes, like `SecretMnemonic`
+impl<const N: usize> fmt::Debug for SecretArray<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecretArray<{}>(..)", N)
+    }
+}
+
+// Locked buffers wipe themselves; inline arrays are wiped here
+#[cfg(not(feature = "locked-memory"))]
+impl<const N: usize> Drop for SecretArray<N> {
+    fn drop(&mut self) {
+        self.bytes.zeroize();
+    }
+}
+
+/// Secret UTF-8 text, e.g. a mnemonic phrase
+pub(crate) struct SecretText {
+    #[cfg(not(feature = "locked-memory"))]
+    text: String,
+    #[cfg(feature = "locked-memory")]
+    text: locked::LockedBuffer,
+}
+
+impl SecretText {
+    #[cfg(not(feature = "locked-memory"))]
+    pub(crate) fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    #[cfg(feature = "locked-memory")]
+    pub(crate) fn as_str(&self) -> &str {
+        // Only ever filled from a `String`, so always valid UTF-8
+        std::str::from_utf8(self.text.as_slice()).expect("secret text is UTF-8")
+    }
+
+    /// Whether the text sits in page-locked memory
+    #[cfg(all(test, feature = "locked-memory"))]
+    pub(crate) fn is_locked(&self) -> bool {
+        self.text.is_locked()
+    }
+}
+
+impl From<String> for SecretText {
+    #[cfg(not(feature = "locked-memory"))]
+    fn from(text: String) -> Self {
+        SecretText { text }
+    }
+
+    #[cfg(feature = "locked-memory")]
+    fn from(mut text: String) -> Self {
+        let mut buffer = locked::LockedBuffer::new(text.len());
+        buffer.as_mut_slice().copy_from_slice(text.as_bytes());
+        text.zeroize();
+        SecretText { text: buffer }
+    }
+}
+
+#[cfg(not(feature = "locked-memory"))]
+impl Drop for SecretText {
+    fn drop(&mut self) {
+        self.text.zeroize();
+    }
+}
+
+/// Whether any secret so far could not be locked and was kept in swappable memory
+///
+/// Daemons that must not swap keys can check this after loading them and
+/// refuse to continue, or raise `RLIMIT_MEMLOCK` and restart.
+#[cfg(feature = "locked-memory")]
+pub fn memory_lock_failed() -> bool {
+    locked::LOCK_FAILED.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+#[cfg(feature = "locked-memory")]
+pub(crate) mod locked {
+    use std::alloc::{alloc_zeroed, dealloc, handle_alloc_error, Layout};
+    use std::ptr::NonNull;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::OnceLock;
+    use zeroize::Zeroize;
+
+    /// Set the first time a buffer falls back to ordinary memory
+    pub(super) static LOCK_FAILED: AtomicBool = AtomicBool::new(false);
+
+    #[cfg(test)]
+    thread_local! {
+        static FAIL_LOCKING: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+    }
+
+    /// Make `mlock` report failure on this thread, to exercise the fallback
+    #[cfg(test)]
+    pub(crate) fn set_fail_locking(fail: bool) {
+        FAIL_LOCKING.with(|flag| flag.set(fail));
+    }
+
+    /// Heap buffer on its own pages, locked in RAM when the OS allows it
+    ///
+    /// Each buffer gets whole pages so unlocking one can never unlock the
+    /// page of another (`munlock` is not reference counted).
+    pub(crate) struct LockedBuffer {
+        ptr: NonNull<u8>,
+        len: usize,
+        layout: Layout,
+        locked: bool,
+    }
+
+    // The buffer owns its allocation exclusively, like a Box<[u8]>
+    unsafe impl Send for LockedBuffer {}
+    unsafe impl Sync for LockedBuffer {}
+
+    impl LockedBuffer {
+        /// Zero-filled buffer of `len` bytes
+        pub(crate) fn new(len: usize) -> Self {
+            let page = page_size();
+            let size = len.max(1).div_ceil(page) * page;
+            let layout = Layout::from_size_align(size, page).expect("page-aligned layout");
+            // SAFETY: `layout` has a nonzero size
+            let ptr = NonNull::new(unsafe { alloc_zeroed(layout) }).unwrap_or_else(|| handle_alloc_error(layout));
+
+            let locked = lock(ptr.as_ptr(), size);
+            if !locked {
+                record_lock_failure();
+            }
+            LockedBuffer { ptr, len, layout, locked }
+        }
+
+        #[cfg(test)]
+        pub(crate) fn is_locked(&self) -> bool {
+            self.locked
+        }
+
+        pub(crate) fn as_slice(&self) -> &[u8] {
+            // SAFETY: `ptr` is valid for `layout.size() >= len` initialized bytes
+            unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+        }
+
+        pub(crate) fn as_mut_slice(&mut self) -> &mut [u8] {
+            // SAFETY: as above, and `&mut self` guarantees exclusive access
+            unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+        }
+    }
+
+    impl Drop for LockedBuffer {
+        fn drop(&mut self) {
+            // SAFETY: the whole allocation is initialized and owned by us
+            unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.layout.size()) }.zeroize();
+            if self.locked {
+                unlock(self.ptr.as_ptr(), self.layout.size());
+            }
+            // SAFETY: allocated in `new` with this exact layout
+            unsafe { dealloc(self.ptr.as_ptr(), self.layout) };
+        }
+    }
+
+    fn page_size() -> usize {
+        static PAGE_SIZE: OnceLock<usize> = OnceLock::new();
+        *PAGE_SIZE.get_or_init(|| {
+            #[cfg(unix)]
+            {
+                // SAFETY: sysconf has no preconditions
+                let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+                if size > 0 {
+                    return size as usize;
+                }
+            }
+            4096
+        })
+    }
+
+    fn lock(ptr: *mut u8, size: usize) -> bool {
+        #[cfg(test)]
+        if FAIL_LOCKING.with(|flag| flag.get()) {
+            return false;
+        }
+
+        #[cfg(unix)]
+        {
+            // SAFETY: the range is a live allocation owned by the caller
+            unsafe { libc::mlock(ptr as *const libc::c_void, size) == 0 }
+        }
+        #[cfg(windows)]
+        {
+            // SAFETY: as above; VirtualLock only pins the pages
+            unsafe { windows::VirtualLock(ptr as *const std::ffi::c_void, size) != 0 }
+        }
+        #[cfg(not(any(unix, windows)))]
+        {
+            let _ = (ptr, size);
+            false
+        }
+    }
+
+    fn unlock(ptr: *mut u8, size: usize) {
+        #[cfg(unix)]
+        // SAFETY: the range was locked by `lock` and is still allocated
+        unsafe {
+            libc::munlock(ptr as *const libc::c_void, size);
+        }
+        #[cfg(windows)]
+        // SAFETY: as above
+        unsafe {
+            windows::VirtualUnlock(ptr as *const std::ffi::c_void, size);
+        }
+        #[cfg(not(any(unix, windows)))]
+        let _ = (ptr, size);
+    }
+
+    /// kernel32 page locking, declared here to avoid a bindings dependency
+    #[cfg(windows)]
+    mod windows {
+        #[link(name = "kernel32")]
+        extern "system" {
+            pub(super) fn VirtualLock(address: *const std::ffi::c_void, size: usize) -> i32;
+            pub(super) fn VirtualUnlock(address: *const std::ffi::c_void, size: usize) -> i32;
+        }
+    }
+
+    fn record_lock_failure() {
+        if !LOCK_FAILED.swap(true, Ordering::Relaxed) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("could not lock secret memory (check RLIMIT_MEMLOCK); keys may be swapped to disk");
+        }
+    }
+}
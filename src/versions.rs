@@ -0,0 +1,152 @@
+//! Registry of known 4-byte extended-key version prefixes
+//!
+//! Bitcoin's SLIP-132 prefixes plus the common altcoin ones, so a pasted
+//! key can be named ("this is a Dogecoin public key") even when this crate
+//! cannot use it, and so keys can be serialized for another coin.
+
+use crate::address::ScriptType;
+use crate::error::KeyManagerError;
+use crate::network::Network;
+use crate::utils;
+use std::fmt;
+use Coin::{Bitcoin, Dogecoin, Litecoin};
+use Network::{Mainnet, Testnet};
+use ScriptType::{P2pkh, P2shP2wpkh, P2wpkh};
+use Visibility::{Private, Public};
+
+/// Coin whose extended-key prefixes are registered
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Coin {
+    Bitcoin,
+    Litecoin,
+    Dogecoin,
+}
+
+impl Coin {
+    /// Human-readable coin name
+    pub fn name(self) -> &'static str {
+        match self {
+            Coin::Bitcoin => "Bitcoin",
+            Coin::Litecoin => "Litecoin",
+            Coin::Dogecoin => "Dogecoin",
+        }
+    }
+}
+
+/// Whether a serialized key carries the private or the public key
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Visibility {
+    Private,
+    Public,
+}
+
+/// One registered version prefix
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyVersion {
+    /// Leading characters of the Base58 encoding ("xpub", "Ltpv", ...)
+    pub name: &'static str,
+    /// The 4 version bytes at the start of the serialization
+    pub bytes: [u8; 4],
+    pub coin: Coin,
+    /// Script type wallets should use with the key
+    pub script_type: ScriptType,
+    /// `Mainnet` or `Testnet` (regtest shares the testnet prefixes)
+    pub network: Network,
+    pub visibility: Visibility,
+}
+
+impl fmt::Display for KeyVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self.visibility {
+            Visibility::Private => "private",
+            Visibility::Public => "public",
+        };
+        let network = if self.network == Network::Mainnet { "" } else { " testnet" };
+        write!(
+            f,
+            "{}{} {} key ({}, 0x{})",
+            self.coin.name(),
+            network,
+            kind,
+            self.name,
+            utils::bytes_to_hex(&self.bytes)
+        )
+    }
+}
+
+const fn entry(
+    name: &'static str,
+    bytes: [u8; 4],
+    coin: Coin,
+    script_type: ScriptType,
+    network: Network,
+    visibility: Visibility,
+) -> KeyVersion {
+    KeyVersion { name, bytes, coin, script_type, network, visibility }
+}
+
+/// Every known prefix
+///
+/// Taproot has no prefix of its own; BIP86 accounts use the P2PKH ones.
+pub const VERSIONS: &[KeyVersion] = &[
+    entry("xprv", [0x04, 0x88, 0xAD, 0xE4], Bitcoin, P2pkh, Mainnet, Private),
+    entry("xpub", [0x04, 0x88, 0xB2, 0x1E], Bitcoin, P2pkh, Mainnet, Public),
+    entry("yprv", [0x04, 0x9D, 0x78, 0x78], Bitcoin, P2shP2wpkh, Mainnet, Private),
+    entry("ypub", [0x04, 0x9D, 0x7C, 0xB2], Bitcoin, P2shP2wpkh, Mainnet, Public),
+    entry("zprv", [0x04, 0xB2, 0x43, 0x0C], Bitcoin, P2wpkh, Mainnet, Private),
+    entry("zpub", [0x04, 0xB2, 0x47, 0x46], Bitcoin, P2wpkh, Mainnet, Public),
+    entry("tprv", [0x04, 0x35, 0x83, 0x94], Bitcoin, P2pkh, Testnet, Private),
+    entry("tpub", [0x04, 0x35, 0x87, 0xCF], Bitcoin, P2pkh, Testnet, Public),
+    entry("uprv", [0x04, 0x4A, 0x4E, 0x28], Bitcoin, P2shP2wpkh, Testnet, Private),
+    entry("upub", [0x04, 0x4A, 0x52, 0x62], Bitcoin, P2shP2wpkh, Testnet, Public),
+    entry("vprv", [0x04, 0x5F, 0x18, 0xBC], Bitcoin, P2wpkh, Testnet, Private),
+    entry("vpub", [0x04, 0x5F, 0x1C, 0xF6], Bitcoin, P2wpkh, Testnet, Public),
+    entry("Ltpv", [0x01, 0x9D, 0x9C, 0xFE], Litecoin, P2pkh, Mainnet, Private),
+    entry("Ltub", [0x01, 0x9D, 0xA4, 0x62], Litecoin, P2pkh, Mainnet, Public),
+    entry("Mtpv", [0x01, 0xB2, 0x67, 0x92], Litecoin, P2shP2wpkh, Mainnet, Private),
+    entry("Mtub", [0x01, 0xB2, 0x6E, 0xF6], Litecoin, P2shP2wpkh, Mainnet, Public),
+    entry("ttpv", [0x04, 0x36, 0xEF, 0x7D], Litecoin, P2pkh, Testnet, Private),
+    entry("ttub", [0x04, 0x36, 0xF6, 0xE1], Litecoin, P2pkh, Testnet, Public),
+    entry("dgpv", [0x02, 0xFA, 0xC3, 0x98], Dogecoin, P2pkh, Mainnet, Private),
+    entry("dgub", [0x02, 0xFA, 0xCA, 0xFD], Dogecoin, P2pkh, Mainnet, Public),
+];
+
+/// The prefix with these version bytes
+pub fn by_bytes(bytes: [u8; 4]) -> Option<&'static KeyVersion> {
+    VERSIONS.iter().find(|version| version.bytes == bytes)
+}
+
+/// The prefix with this name ("zpub", "Ltub"); exact case wins, then any case
+pub fn by_name(name: &str) -> Option<&'static KeyVersion> {
+    VERSIONS
+        .iter()
+        .find(|version| version.name == name)
+        .or_else(|| VERSIONS.iter().find(|version| version.name.eq_ignore_ascii_case(name)))
+}
+
+/// The prefix for a coin, script type, network and visibility
+///
+/// Regtest resolves like testnet. Taproot resolves to the P2PKH prefix.
+pub fn lookup(coin: Coin, script_type: ScriptType, network: Network, visibility: Visibility) -> Option<&'static KeyVersion> {
+    let script_type = if script_type == ScriptType::P2tr { P2pkh } else { script_type };
+    let network = if network == Mainnet { Mainnet } else { Testnet };
+    VERSIONS.iter().find(|version| {
+        version.coin == coin
+            && version.script_type == script_type
+            && version.network == network
+            && version.visibility == visibility
+    })
+}
+
+/// Human-readable name for version bytes: the registry entry, or "unknown (0x...)"
+pub fn describe(bytes: [u8; 4]) -> String {
+    match by_bytes(bytes) {
+        Some(version) => version.to_string(),
+        None => format!("unknown (0x{})", utils::bytes_to_hex(&bytes)),
+    }
+}
+
+/// Error for version bytes a parser cannot accept, naming what they are
+pub(crate) fn unsupported(bytes: [u8; 4]) -> KeyManagerError {
+    KeyManagerError::UnsupportedVersionBytes { version: bytes, name: describe(bytes) }
+}
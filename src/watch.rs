@@ -0,0 +1,281 @@
+//! Watch-only wallets: addresses and history scans from public keys alone
+//!
+//! A `WatchOnlyWallet` is built from an account xpub or a descriptor and
+//! keeps nothing but public keys. Extended private keys in a descriptor are
+//! neutered by `descriptor::parse` before the wallet ever sees them, so no
+//! secret can be reached through it. `verify_claim` checks a single
+//! "this is index N of my xpub" claim without a scan.
+
+use crate::address::ScriptType;
+use crate::descriptor::{self, Descriptor, DescriptorKey, Key, KeyOrigin};
+use crate::discovery::GAP_LIMIT;
+use crate::error::{KeyManagerError, Result};
+use crate::hd_key::HARDENED_OFFSET;
+use crate::network::Network;
+use crate::path::DerivationPath;
+use crate::progress::{checkpoint, NoProgress, Progress};
+use crate::utils::{base58, bech32, constant_time_eq};
+use crate::xpub::{ExtendedPublicKey, SlipVersion};
+use std::ops::Range;
+
+/// Receive (0) and change (1) chains
+const CHAINS: [u32; 2] = [0, 1];
+
+/// Public-only view of one account (single-sig or sorted multisig)
+///
+/// The receive and change chain keys are derived once at construction, so
+/// each address costs one public derivation per key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WatchOnlyWallet {
+    /// Descriptors over the chain-level keys, indexed by chain
+    chains: [Descriptor; 2],
+    network: Network,
+}
+
+impl WatchOnlyWallet {
+    /// Wallet for an account-level xpub (or SLIP-132 ypub/zpub/tpub...)
+    ///
+    /// The network follows the key's prefix: mainnet, or testnet for
+    /// tpub/upub/vpub (see `with_network` for regtest).
+    pub fn from_xpub(xpub: &str, script_type: ScriptType) -> Result<Self> {
+        let account: ExtendedPublicKey = xpub.parse()?;
+        let network = if account.version().is_testnet() { Network::Testnet } else { Network::Mainnet };
+        let chain = |chain| -> Result<Descriptor> {
+            let key = DescriptorKey {
+                origin: None,
+                key: Key::Extended { xpub: account.derive_child(chain)?, path: DerivationPath::default(), wildcard: true },
+            };
+            Ok(match script_type {
+                ScriptType::P2pkh => Descriptor::Pkh(key),
+                ScriptType::P2shP2wpkh => Descriptor::ShWpkh(key),
+                ScriptType::P2wpkh => Descriptor::Wpkh(key),
+                ScriptType::P2tr => Descriptor::Tr(key),
+            })
+        };
+        Ok(WatchOnlyWallet { chains: [chain(0)?, chain(1)?], network })
+    }
+
+    /// Wallet for a receive or change descriptor, e.g. `wpkh([73c5da0a/84'/0'/0']xpub.../0/*)`
+    ///
+    /// Every key must be an extended key followed by one chain step and a
+    /// `*` wildcard; the step is replaced to reach the other chain, so the
+    /// `/0/*` and `/1/*` descriptors of an account give the same wallet.
+    pub fn from_descriptor(descriptor: &str) -> Result<Self> {
+        let descriptor = descriptor::parse(descriptor)?;
+        let mut network = Network::Mainnet;
+        let mut chains = [Vec::new(), Vec::new()];
+        for key in descriptor.keys() {
+            let Key::Extended { xpub, path, wildcard: true } = &key.key else {
+                return Err(not_account_key());
+            };
+            let [_chain] = path.indices() else {
+                return Err(not_account_key());
+            };
+            if xpub.version().is_testnet() {
+                network = Network::Testnet;
+            }
+            for (chain, keys) in CHAINS.into_iter().zip(&mut chains) {
+                keys.push(DescriptorKey {
+                    origin: key.origin.as_ref().map(|origin| KeyOrigin {
+                        fingerprint: origin.fingerprint,
+                        path: DerivationPath::from_indices([origin.path.indices(), &[chain]].concat()),
+                    }),
+                    key: Key::Extended { xpub: xpub.derive_child(chain)?, path: DerivationPath::default(), wildcard: true },
+                });
+            }
+        }
+
+        let [receive, change] = chains;
+        Ok(WatchOnlyWallet { chains: [with_keys(&descriptor, receive), with_keys(&descriptor, change)], network })
+    }
+
+    /// The same wallet on `network`; mainnet and test keys cannot be swapped
+    ///
+    /// Mostly for regtest, which shares testnet's key prefixes.
+    pub fn with_network(self, network: Network) -> Result<Self> {
+        if (network == Network::Mainnet) != (self.network == Network::Mainnet) {
+            return Err(KeyManagerError::EncodingError(format!(
+                "Watch-only keys are for {}, not {}",
+                self.network.name(),
+                network.name()
+            )));
+        }
+        Ok(WatchOnlyWallet { network, ..self })
+    }
+
+    /// Network addresses are encoded for
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// Master fingerprint from the key origin, for single-key wallets that have one
+    pub fn fingerprint(&self) -> Option<[u8; 4]> {
+        match self.chains[0].keys() {
+            [key] => key.origin.as_ref().map(|origin| origin.fingerprint),
+            _ => None,
+        }
+    }
+
+    /// Address at `index` on `chain` (0 receive, 1 change)
+    pub fn address(&self, chain: u32, index: u32) -> Result<String> {
+        self.chain(chain)?.address_at(index, self.network)
+    }
+
+    /// Addresses for `range` on `chain`, in order
+    pub fn addresses(&self, chain: u32, range: Range<u32>) -> Result<Vec<String>> {
+        let descriptor = self.chain(chain)?;
+        range.map(|index| descriptor.address_at(index, self.network)).collect()
+    }
+
+    /// Used addresses on both chains as `(chain, index, address)`, receive chain first
+    ///
+    /// Each chain is walked until `GAP_LIMIT` consecutive addresses have no
+    /// history, as `discovery::discover_addresses` does for a single xpub.
+    pub fn scan(&self, has_history: impl Fn(&str) -> bool) -> Result<Vec<(u32, u32, String)>> {
+        self.scan_with_progress(has_history, &NoProgress)
+    }
+
+    /// `scan`, reporting every address checked on either chain
+    ///
+    /// The total is unknown until both gaps are found. Fails with
+    /// `KeyManagerError::Cancelled` if `progress` breaks.
+    pub fn scan_with_progress(
+        &self,
+        has_history: impl Fn(&str) -> bool,
+        progress: &impl Progress,
+    ) -> Result<Vec<(u32, u32, String)>> {
+        let mut used = Vec::new();
+        let mut checked = 0;
+        for chain in CHAINS {
+            let mut gap = 0;
+            let mut index = 0;
+            while gap < GAP_LIMIT && index < HARDENED_OFFSET {
+                let address = self.address(chain, index)?;
+                if has_history(&address) {
+                    used.push((chain, index, address));
+                    gap = 0;
+                } else {
+                    gap += 1;
+                }
+                index += 1;
+                checked += 1;
+                checkpoint(progress, checked, None)?;
+            }
+        }
+        Ok(used)
+    }
+
+    /// First address on `chain` without history, as `(index, address)`
+    pub fn next_unused(&self, chain: u32, has_history: impl Fn(&str) -> bool) -> Result<(u32, String)> {
+        for index in 0..HARDENED_OFFSET {
+            let address = self.address(chain, index)?;
+            if !has_history(&address) {
+                return Ok((index, address));
+            }
+        }
+        Err(KeyManagerError::InvalidDerivationPath(format!("Chain {} has no unused addresses", chain)))
+    }
+
+    fn chain(&self, chain: u32) -> Result<&Descriptor> {
+        self.chains.get(chain as usize).ok_or_else(|| {
+            KeyManagerError::InvalidDerivationPath(format!("Chain must be 0 (receive) or 1 (change), got {}", chain))
+        })
+    }
+}
+
+/// What a claim says is at an xpub's `chain/index`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AddressOrPubkey {
+    /// A P2PKH, P2SH-P2WPKH, P2WPKH or P2TR address
+    Address(String),
+    /// A 33-byte compressed public key
+    Pubkey([u8; 33]),
+}
+
+/// Whether `expected` is what `xpub/chain/index` derives to
+///
+/// One public derivation, no scanning. Addresses may be of any supported
+/// script type, except that a ypub/zpub (upub/vpub) only vouches for its
+/// own type; xpub/tpub accept all of them since legacy and taproot
+/// accounts share that prefix. Key and hash bytes are compared in constant
+/// time. An unparseable address or one for the other network is an error.
+pub fn verify_claim(xpub: &str, chain: u32, index: u32, expected: &AddressOrPubkey) -> Result<bool> {
+    let account: ExtendedPublicKey = xpub.parse()?;
+    let public_key = *account.derive_child(chain)?.derive_child(index)?.public_key();
+
+    match expected {
+        AddressOrPubkey::Pubkey(claimed) => Ok(constant_time_eq(&public_key, claimed)),
+        AddressOrPubkey::Address(address) => {
+            let (script_type, network, claimed) = decode_address(address)?;
+            if (network != Network::Mainnet) != account.version().is_testnet() {
+                return Err(KeyManagerError::EncodingError(format!(
+                    "Address {} is for {}, but the key is for {}",
+                    address,
+                    network.name(),
+                    if account.version().is_testnet() { "testnet" } else { "mainnet" }
+                )));
+            }
+            let allowed = match account.version() {
+                SlipVersion::Xpub | SlipVersion::Tpub => true,
+                version => version.script_type() == script_type,
+            };
+            let (_, _, derived) = decode_address(&script_type.address_from_pubkey(&public_key, network)?)?;
+            Ok(constant_time_eq(&derived, &claimed) & allowed)
+        }
+    }
+}
+
+/// Script type, network and payload (hash or witness program) of an address
+///
+/// Base58 addresses report testnet for the shared testnet/regtest versions,
+/// and every P2SH address is taken to be P2SH-P2WPKH.
+fn decode_address(address: &str) -> Result<(ScriptType, Network, Vec<u8>)> {
+    let unsupported = || KeyManagerError::EncodingError(format!("Unsupported address type: {}", address));
+
+    if let Ok((hrp, version, program)) = bech32::decode_segwit(address) {
+        let network = [Network::Mainnet, Network::Testnet, Network::Regtest]
+            .into_iter()
+            .find(|network| network.bech32_hrp() == hrp)
+            .ok_or_else(|| KeyManagerError::EncodingError(format!("Unknown address prefix '{}'", hrp)))?;
+        let script_type = match (version, program.len()) {
+            (0, 20) => ScriptType::P2wpkh,
+            (1, 32) => ScriptType::P2tr,
+            _ => return Err(unsupported()),
+        };
+        return Ok((script_type, network, program));
+    }
+
+    let payload = base58::decode_check(address)
+        .map_err(|_| KeyManagerError::EncodingError(format!("Not a Bitcoin address: {}", address)))?;
+    let [version, hash @ ..] = payload.as_slice() else {
+        return Err(unsupported());
+    };
+    if hash.len() != 20 {
+        return Err(unsupported());
+    }
+    [Network::Mainnet, Network::Testnet]
+        .into_iter()
+        .find_map(|network| match *version {
+            v if v == network.p2pkh_version() => Some((ScriptType::P2pkh, network, hash.to_vec())),
+            v if v == network.p2sh_version() => Some((ScriptType::P2shP2wpkh, network, hash.to_vec())),
+            _ => None,
+        })
+        .ok_or_else(unsupported)
+}
+
+/// `descriptor` with its keys replaced, in order
+fn with_keys(descriptor: &Descriptor, mut keys: Vec<DescriptorKey>) -> Descriptor {
+    match descriptor {
+        Descriptor::Pkh(_) => Descriptor::Pkh(keys.remove(0)),
+        Descriptor::Wpkh(_) => Descriptor::Wpkh(keys.remove(0)),
+        Descriptor::ShWpkh(_) => Descriptor::ShWpkh(keys.remove(0)),
+        Descriptor::Tr(_) => Descriptor::Tr(keys.remove(0)),
+        Descriptor::WshSortedMulti { threshold, .. } => Descriptor::WshSortedMulti { threshold: *threshold, keys },
+    }
+}
+
+fn not_account_key() -> KeyManagerError {
+    KeyManagerError::EncodingError(
+        "Watch-only descriptors need extended keys ending in /<chain>/* (e.g. xpub.../0/*)".to_string(),
+    )
+}
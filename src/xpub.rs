@@ -0,0 +1,358 @@
+use crate::address::ScriptType;
+use crate::error::{KeyManagerError, Result};
+use crate::hd_key::{ExtendedKey, HARDENED_OFFSET};
+use crate::path::DerivationPath;
+use crate::network::Network;
+use crate::utils::{base58, ec, hash160};
+use crate::versions::{self, KeyVersion, Visibility};
+use hmac::{Hmac, Mac};
+use secp256k1::PublicKey;
+use sha2::Sha512;
+use std::fmt;
+use std::str::FromStr;
+
+/// BIP32 serialization length without the Base58Check checksum
+const SERIALIZED_LEN: usize = 78;
+
+/// Decoded Base58 length including the 4-byte checksum
+const ENCODED_LEN: usize = SERIALIZED_LEN + 4;
+
+/// SLIP-132 version prefixes for serialized extended public keys
+///
+/// The prefix only signals which script type a wallet should use with the
+/// key (`xpub` legacy, `ypub` nested segwit, `zpub` native segwit); the key
+/// material underneath is identical.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SlipVersion {
+    Xpub,
+    Ypub,
+    Zpub,
+    Tpub,
+    Upub,
+    Vpub,
+}
+
+impl SlipVersion {
+    /// The 4 version bytes written at the start of the serialization
+    pub fn bytes(self) -> [u8; 4] {
+        match self {
+            SlipVersion::Xpub => [0x04, 0x88, 0xB2, 0x1E],
+            SlipVersion::Ypub => [0x04, 0x9D, 0x7C, 0xB2],
+            SlipVersion::Zpub => [0x04, 0xB2, 0x47, 0x46],
+            SlipVersion::Tpub => [0x04, 0x35, 0x87, 0xCF],
+            SlipVersion::Upub => [0x04, 0x4A, 0x52, 0x62],
+            SlipVersion::Vpub => [0x04, 0x5F, 0x1C, 0xF6],
+        }
+    }
+
+    /// Look up a version from its 4 serialized bytes
+    pub fn from_bytes(bytes: [u8; 4]) -> Option<Self> {
+        [
+            SlipVersion::Xpub,
+            SlipVersion::Ypub,
+            SlipVersion::Zpub,
+            SlipVersion::Tpub,
+            SlipVersion::Upub,
+            SlipVersion::Vpub,
+        ]
+        .into_iter()
+        .find(|version| version.bytes() == bytes)
+    }
+
+    /// Whether this is a testnet prefix (tpub/upub/vpub)
+    pub fn is_testnet(self) -> bool {
+        matches!(self, SlipVersion::Tpub | SlipVersion::Upub | SlipVersion::Vpub)
+    }
+
+    /// Script type the prefix signals
+    ///
+    /// xpub/tpub map to P2PKH, although BIP86 taproot accounts use them too.
+    pub fn script_type(self) -> ScriptType {
+        match self {
+            SlipVersion::Xpub | SlipVersion::Tpub => ScriptType::P2pkh,
+            SlipVersion::Ypub | SlipVersion::Upub => ScriptType::P2shP2wpkh,
+            SlipVersion::Zpub | SlipVersion::Vpub => ScriptType::P2wpkh,
+        }
+    }
+}
+
+/// BIP32 extended public key (xpub and its SLIP-132 variants)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtendedPublicKey {
+    /// Version prefix the key was serialized with
+    version: SlipVersion,
+    /// Depth in the derivation tree (0 for master)
+    depth: u8,
+    /// Parent key fingerprint (4 bytes)
+    parent_fingerprint: [u8; 4],
+    /// Child index
+    child_index: u32,
+    /// 32-byte chain code
+    chain_code: [u8; 32],
+    /// 33-byte compressed public key
+    public_key: [u8; 33],
+}
+
+impl ExtendedPublicKey {
+    /// Neuter an extended private key into its extended public key
+    pub fn from_private(key: &ExtendedKey, version: SlipVersion) -> Result<Self> {
+        Ok(ExtendedPublicKey {
+            version,
+            depth: key.depth(),
+            parent_fingerprint: key.parent_fingerprint(),
+            child_index: key.child_index(),
+            chain_code: *key.chain_code(),
+            public_key: key.public_key()?,
+        })
+    }
+
+    /// Root-level key from a bare public key and chain code
+    ///
+    /// For key material that arrives outside a BIP32 serialization (e.g. a
+    /// BIP47 payment code); depth, parent fingerprint and index are zero.
+    pub(crate) fn from_parts(public_key: [u8; 33], chain_code: [u8; 32], version: SlipVersion) -> Result<Self> {
+        PublicKey::from_slice(&public_key)
+            .map_err(|e| KeyManagerError::EncodingError(format!("Invalid public key: {}", e)))?;
+        Ok(ExtendedPublicKey {
+            version,
+            depth: 0,
+            parent_fingerprint: [0u8; 4],
+            child_index: 0,
+            chain_code,
+            public_key,
+        })
+    }
+
+    /// Public child key derivation (BIP32 CKDpub) for a non-hardened index
+    ///
+    /// Lets watch-only code walk address chains from an account xpub without
+    /// the private key. Hardened indices are rejected since they need it.
+    pub fn derive_child(&self, index: u32) -> Result<Self> {
+        let depth = self.depth.checked_add(1).ok_or_else(|| {
+            KeyManagerError::InvalidDerivationPath("Maximum derivation depth exceeded".to_string())
+        })?;
+        let (public_key, chain_code) = self.ckd_pub(index)?;
+
+        Ok(ExtendedPublicKey {
+            version: self.version,
+            depth,
+            parent_fingerprint: self.fingerprint(),
+            child_index: index,
+            chain_code,
+            public_key,
+        })
+    }
+
+    /// Compressed public keys for `count` consecutive addresses on one chain
+    ///
+    /// `self` is an account-level key; `change` picks the internal (1) or
+    /// receive (0) chain. The chain node is derived once and only the final
+    /// index varies, which is what address scanning needs.
+    pub fn derive_pub_range(&self, change: bool, start: u32, count: u32) -> Result<Vec<[u8; 33]>> {
+        let end = start
+            .checked_add(count)
+            .filter(|&end| end <= HARDENED_OFFSET)
+            .ok_or_else(|| {
+                KeyManagerError::InvalidDerivationPath(format!(
+                    "Range {}..+{} exceeds the non-hardened index limit",
+                    start, count
+                ))
+            })?;
+
+        let chain = self.derive_child(change as u32)?;
+        (start..end)
+            .map(|index| chain.ckd_pub(index).map(|(public_key, _)| public_key))
+            .collect()
+    }
+
+    /// BIP32 CKDpub: child public key and chain code at a non-hardened index
+    fn ckd_pub(&self, index: u32) -> Result<([u8; 33], [u8; 32])> {
+        if index >= HARDENED_OFFSET {
+            return Err(KeyManagerError::InvalidDerivationPath(format!(
+                "Cannot derive hardened child {}' from a public key",
+                index - HARDENED_OFFSET
+            )));
+        }
+
+        // I = HMAC-SHA512(chain_code, serP(K_par) || ser32(i))
+        let mut mac = Hmac::<Sha512>::new_from_slice(&self.chain_code)
+            .map_err(|_| KeyManagerError::HmacError("HMAC init failed".to_string()))?;
+        mac.update(&self.public_key);
+        mac.update(&index.to_be_bytes());
+        let result = mac.finalize().into_bytes();
+
+        // K_i = point(I_L) + K_par; I_L >= n or an infinite point means the index is invalid
+        let mut tweak = [0u8; 32];
+        tweak.copy_from_slice(&result[..32]);
+        let public_key = ec::pubkey_tweak_add(&self.public_key, &tweak).map_err(|_| {
+            KeyManagerError::KeyGenerationError(format!("Invalid child key at index {}; use the next index", index))
+        })?;
+
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&result[32..]);
+        Ok((public_key, chain_code))
+    }
+
+    /// Derive along a path of non-hardened indices relative to this key
+    pub fn derive(&self, path: &DerivationPath) -> Result<Self> {
+        let mut current = self.clone();
+        for (component_index, &index) in path.indices().iter().enumerate() {
+            current = current.derive_child(index).map_err(|source| KeyManagerError::DerivationFailed {
+                path: path.clone(),
+                component_index,
+                source: Box::new(source),
+            })?;
+        }
+        Ok(current)
+    }
+
+    /// Re-serialize the same key under a different SLIP-132 version prefix
+    ///
+    /// Only the display encoding changes: the public key, chain code and
+    /// derivation metadata are kept as-is, so this cannot make a key "become"
+    /// segwit or legacy. Switching between mainnet and testnet prefixes is
+    /// rejected since that would silently move the key to another network.
+    pub fn reserialize(&self, version: SlipVersion) -> Result<String> {
+        if version.is_testnet() != self.version.is_testnet() {
+            return Err(KeyManagerError::EncodingError(format!(
+                "Cannot reserialize {:?} as {:?}: networks differ",
+                self.version, version
+            )));
+        }
+        Ok(self.serialize_with(version.bytes()))
+    }
+
+    /// Serialize under any registered public-key prefix (e.g. `Ltub` for Litecoin)
+    ///
+    /// Like `reserialize`, only the prefix changes, and a prefix for the
+    /// other network (mainnet vs testnet) is rejected.
+    pub fn to_string_with_version(&self, version: &KeyVersion) -> Result<String> {
+        if version.visibility != Visibility::Public {
+            return Err(versions::unsupported(version.bytes));
+        }
+        if (version.network != Network::Mainnet) != self.version.is_testnet() {
+            return Err(KeyManagerError::EncodingError(format!(
+                "Cannot reserialize {:?} as {}: networks differ",
+                self.version, version.name
+            )));
+        }
+        Ok(self.serialize_with(version.bytes))
+    }
+
+    /// Serialize like `to_string`, then parse the result back as a self-check
+    ///
+    /// Used wherever an account xpub is handed to the user, so a Base58 or
+    /// byte-layout bug surfaces as a `KeyGenerationError` instead of an
+    /// xpub that watch-only wallets would derive different addresses from.
+    /// The public key, chain code and metadata must all survive the trip.
+    pub fn to_xpub_string(&self) -> Result<String> {
+        let serialized = self.to_string();
+        match serialized.parse::<ExtendedPublicKey>() {
+            Ok(parsed) if parsed == *self => Ok(serialized),
+            _ => Err(KeyManagerError::KeyGenerationError(
+                "Extended public key did not survive serialization round trip".to_string(),
+            )),
+        }
+    }
+
+    /// Version prefix this key was serialized with
+    pub fn version(&self) -> SlipVersion {
+        self.version
+    }
+
+    /// 33-byte compressed public key
+    pub fn public_key(&self) -> &[u8; 33] {
+        &self.public_key
+    }
+
+    /// 32-byte chain code
+    pub fn chain_code(&self) -> &[u8; 32] {
+        &self.chain_code
+    }
+
+    /// Depth in the derivation tree
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    /// Fingerprint of the parent key
+    pub fn parent_fingerprint(&self) -> [u8; 4] {
+        self.parent_fingerprint
+    }
+
+    /// Child index of this key
+    pub fn child_index(&self) -> u32 {
+        self.child_index
+    }
+
+    /// This key's own fingerprint (first 4 bytes of Hash160 of the public key)
+    pub fn fingerprint(&self) -> [u8; 4] {
+        let mut fingerprint = [0u8; 4];
+        fingerprint.copy_from_slice(&hash160(&self.public_key)[..4]);
+        fingerprint
+    }
+
+    fn serialize_with(&self, version: [u8; 4]) -> String {
+        let mut data = Vec::with_capacity(SERIALIZED_LEN);
+        data.extend_from_slice(&version);
+        data.push(self.depth);
+        data.extend_from_slice(&self.parent_fingerprint);
+        data.extend_from_slice(&self.child_index.to_be_bytes());
+        data.extend_from_slice(&self.chain_code);
+        data.extend_from_slice(&self.public_key);
+        base58::encode_check(&data)
+    }
+}
+
+impl fmt::Display for ExtendedPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.serialize_with(self.version.bytes()))
+    }
+}
+
+impl FromStr for ExtendedPublicKey {
+    type Err = KeyManagerError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        // Check the length before the checksum so a truncated paste is
+        // reported as such rather than as a checksum failure
+        let raw = base58::decode(s.trim())?;
+        if raw.len() != ENCODED_LEN {
+            return Err(KeyManagerError::EncodingError(format!(
+                "Wrong extended key length: decoded {} bytes, expected {} ({} + 4-byte checksum){}",
+                raw.len(),
+                ENCODED_LEN,
+                SERIALIZED_LEN,
+                if raw.len() < ENCODED_LEN { "; the key looks truncated" } else { "" }
+            )));
+        }
+        let data = base58::decode_check(s.trim())?;
+
+        let mut version_bytes = [0u8; 4];
+        version_bytes.copy_from_slice(&data[..4]);
+        let version = SlipVersion::from_bytes(version_bytes).ok_or_else(|| versions::unsupported(version_bytes))?;
+
+        let mut parent_fingerprint = [0u8; 4];
+        parent_fingerprint.copy_from_slice(&data[5..9]);
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&data[13..45]);
+        let mut public_key = [0u8; 33];
+        public_key.copy_from_slice(&data[45..78]);
+
+        // Reject bytes that are not a valid compressed secp256k1 point
+        if !matches!(public_key[0], 0x02 | 0x03) || PublicKey::from_slice(&public_key).is_err() {
+            return Err(KeyManagerError::EncodingError(
+                "Invalid public key in extended key".to_string(),
+            ));
+        }
+
+        Ok(ExtendedPublicKey {
+            version,
+            depth: data[4],
+            parent_fingerprint,
+            child_index: u32::from_be_bytes([data[9], data[10], data[11], data[12]]),
+            chain_code,
+            public_key,
+        })
+    }
+}
@@ -0,0 +1,773 @@
+// Integration tests for the crypto-key-manager binary
+
+use std::process::{Command, Output};
+
+const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_crypto-key-manager"))
+        .args(args)
+        .output()
+        .expect("Failed to run crypto-key-manager")
+}
+
+#[test]
+fn test_cli_fingerprint_standard_mnemonic() {
+    let output = run(&["fingerprint", TEST_MNEMONIC]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "73c5da0a");
+}
+
+#[test]
+fn test_cli_fingerprint_invalid_mnemonic() {
+    let invalid = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+    let output = run(&["fingerprint", invalid]);
+    assert!(!output.status.success());
+}
+
+/// Minimal RFC 4180 reader: splits rows and honours quoted fields
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', _) => in_quotes = !in_quotes,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            (c, _) => field.push(c),
+        }
+    }
+    rows
+}
+
+#[test]
+fn test_cli_derive_range_csv_matches_json() {
+    let path = "m/84'/0'/0'/0";
+    let csv = run(&["derive", TEST_MNEMONIC, path, "--range", "0-2", "--format", "csv"]);
+    let json = run(&["derive", "--format", "json", TEST_MNEMONIC, path, "--range", "0-2"]);
+    assert!(csv.status.success());
+    assert!(json.status.success());
+
+    let rows = parse_csv(&String::from_utf8_lossy(&csv.stdout));
+    let objects: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    let objects = objects.as_array().unwrap();
+
+    assert_eq!(rows[0], vec!["path", "address", "pubkey"]);
+    assert_eq!(rows.len() - 1, objects.len());
+    for (row, object) in rows[1..].iter().zip(objects) {
+        for (column, cell) in rows[0].iter().zip(row) {
+            assert_eq!(object[column.as_str()].as_str().unwrap(), cell);
+        }
+    }
+    assert_eq!(rows[1][1], "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+    // Paths contain apostrophes but no commas, so they are left unquoted
+    assert_eq!(rows[1][0], "m/84'/0'/0'/0/0");
+}
+
+#[test]
+fn test_cli_derive_range_tsv_columns() {
+    let output = run(&[
+        "derive", TEST_MNEMONIC, "m/44'/0'/0'/0", "--range", "0-1",
+        "--format", "tsv", "--columns", "address,path",
+    ]);
+    assert!(output.status.success());
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "address\tpath");
+    assert_eq!(lines[1], "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA\tm/44'/0'/0'/0/0");
+    assert_eq!(lines.len(), 3);
+
+    let bad = run(&["derive", TEST_MNEMONIC, "m/44'/0'/0'/0", "--range", "0-1", "--columns", "wif"]);
+    assert!(!bad.status.success());
+}
+
+#[test]
+fn test_cli_derive_network_testnet_prints_tprv() {
+    let output = run(&["derive", "--network", "testnet", TEST_MNEMONIC, "m/84'/1'/0'/0/0"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("tprv: tprv8"));
+
+    // The flag is global: before the command, or after the positional arguments
+    let before = run(&["--network", "testnet", "derive", TEST_MNEMONIC, "m/84'/1'/0'/0/0"]);
+    let after = run(&["derive", TEST_MNEMONIC, "m/84'/1'/0'/0/0", "--network", "testnet"]);
+    assert_eq!(before.stdout, output.stdout);
+    assert_eq!(after.stdout, output.stdout);
+
+    let mainnet = run(&["derive", TEST_MNEMONIC, "m/84'/1'/0'/0/0"]);
+    assert!(String::from_utf8_lossy(&mainnet.stdout).starts_with("xprv: xprv"));
+}
+
+#[test]
+fn test_cli_coin_selects_extended_key_prefix() {
+    let ltub = run(&["xpub", TEST_MNEMONIC, "m/44'/2'/0'", "--coin", "ltc"]);
+    assert!(ltub.status.success());
+    assert!(String::from_utf8_lossy(&ltub.stdout).starts_with("Ltub"));
+
+    // The path's purpose picks the script type, as for addresses
+    let mtpv = run(&["derive", TEST_MNEMONIC, "m/49'/2'/0'", "--coin", "litecoin"]);
+    assert!(String::from_utf8_lossy(&mtpv.stdout).starts_with("Mtpv: Mtpv"));
+
+    // Dogecoin has no segwit prefix, so it falls back to dgub
+    let dgub = run(&["--coin", "doge", "xpub", TEST_MNEMONIC, "m/84'/3'/0'"]);
+    assert!(String::from_utf8_lossy(&dgub.stdout).starts_with("dgub"));
+
+    assert!(!run(&["xpub", TEST_MNEMONIC, "m/44'/3'/0'", "--coin", "doge", "--network", "testnet"]).status.success());
+    assert!(!run(&["xpub", TEST_MNEMONIC, "m/0", "--coin", "xmr"]).status.success());
+}
+
+#[test]
+fn test_cli_pubkey_encodings() {
+    // BIP32 test vector 1, chain m/0H/1/2H/2
+    let master = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi";
+    let path = "m/0'/1/2'/2";
+    let compressed = "02e8445082a72f29b75ca48748a914df60622a609cacfce8ed0e35804560741d29";
+
+    let output = run(&["pubkey", master, path]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], format!("pubkey: {}", compressed));
+    assert!(lines[1].starts_with("hash160: d880d7d8"));
+    // Parent fingerprint of the vector's m/0H/1/2H/2/1000000000 key
+    assert_eq!(lines[2], "fingerprint: d880d7d8");
+
+    let x_only = run(&["pubkey", master, path, "--x-only"]);
+    assert!(String::from_utf8_lossy(&x_only.stdout).starts_with(&format!("pubkey: {}\n", &compressed[2..])));
+
+    let json = run(&["pubkey", master, path, "--uncompressed", "--json"]);
+    let value: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    assert_eq!(value["encoding"], "uncompressed");
+    let uncompressed = value["pubkey"].as_str().unwrap();
+    assert_eq!(uncompressed.len(), 130);
+    assert_eq!(&uncompressed[..66], format!("04{}", &compressed[2..]));
+    assert_eq!(value["fingerprint"].as_str().unwrap(), &lines[2]["fingerprint: ".len()..]);
+
+    // Mnemonics work too; the hash160 is the program of BIP84's first
+    // receive address, bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu
+    let bip84 = run(&["pubkey", TEST_MNEMONIC, "m/84'/0'/0'/0/0"]);
+    assert!(String::from_utf8_lossy(&bip84.stdout).starts_with(
+        "pubkey: 0330d54fd0dd420a6e5f8d3624f5f3482cae350f79d5f0753bf5beef9c2d91af3c\n\
+         hash160: c0cebcd6c3d3ca8c75dc5ec62ebe55330ef910e2\n"
+    ));
+    // The encodings are mutually exclusive
+    assert!(!run(&["pubkey", master, path, "--uncompressed", "--x-only"]).status.success());
+}
+
+#[test]
+fn test_cli_address_uri() {
+    let output = run(&["address", TEST_MNEMONIC, "m/84'/0'/0'/0/0", "--uri", "--amount", "0.001", "--label", "invoice 42"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "bitcoin:bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu?amount=0.001&label=invoice%2042\n"
+    );
+
+    assert!(!run(&["address", TEST_MNEMONIC, "m/84'/0'/0'/0/0", "--uri", "--amount", "1e-3"]).status.success());
+    assert!(!run(&["address", TEST_MNEMONIC, "m/84'/0'/0'/0/0", "--amount", "0.001"]).status.success());
+}
+
+#[test]
+fn test_cli_recover_pubkey() {
+    // Bitcoin Core rpc_signmessage.py vector
+    let core = run(&[
+        "recover-pubkey",
+        "--message",
+        "This is just a test message",
+        "--signature",
+        "INbVnW4e6PeRmsv2Qgu8NuopvrVjkcxob+sX8OcZG0SALhWybUjzMLPdAsXI46YZGb0KQTRii+wWIQzRpG/U+S0=",
+        "--network",
+        "testnet",
+    ]);
+    assert!(core.status.success(), "{}", String::from_utf8_lossy(&core.stderr));
+    let stdout = String::from_utf8_lossy(&core.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "pubkey: 03c150061989643d77162902b725409087959f15914649d4f06b6cc3f8c87bb238");
+    assert_eq!(lines[1], "p2pkh: mpLQjfK79b7CCV4VMJWEWAj5Mpx8Up5zxB");
+    assert!(lines[2].starts_with("p2wpkh: tb1q") && lines[3].starts_with("p2tr: tb1p"));
+
+    // Message from a file and the signature as hex
+    let path = std::env::temp_dir().join(format!("ckm-recover-{}.txt", std::process::id()));
+    std::fs::write(&path, "This is just a test message").unwrap();
+    let hex = "20d6d59d6e1ee8f7919acbf6420bbc36ea29beb56391cc686feb17f0e7191b44802e15b26d48f330b3dd02c5c8e3a61919bd0a4134628bec16210cd1a46fd4f92d";
+    let from_file = run(&["recover-pubkey", "--message", &format!("@{}", path.display()), "--signature", hex]);
+    std::fs::remove_file(&path).unwrap();
+    assert!(String::from_utf8_lossy(&from_file.stdout).starts_with(&format!("{}\n", lines[0])));
+
+    let mut bad_header = hex.to_string();
+    bad_header.replace_range(..2, "1a");
+    let failed = run(&["recover-pubkey", "--message", "This is just a test message", "--signature", &bad_header]);
+    assert!(!failed.status.success());
+    assert!(String::from_utf8_lossy(&failed.stderr).contains("header byte 26"));
+}
+
+#[test]
+fn test_cli_xprv_root_matches_full_path_derivation() {
+    let stdout = |output: Output| {
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let account_xprv = |path: &str| {
+        let derived = stdout(run(&["derive", TEST_MNEMONIC, path]));
+        derived.trim().strip_prefix("xprv: ").unwrap().to_string()
+    };
+
+    // Paths are relative to the --xprv key, hardened steps included
+    let bip84 = account_xprv("m/84'/0'/0'");
+    for (relative, full) in [("m/0/5", "m/84'/0'/0'/0/5"), ("m/0'/1", "m/84'/0'/0'/0'/1")] {
+        assert_eq!(stdout(run(&["derive", "--xprv", &bip84, relative])), stdout(run(&["derive", TEST_MNEMONIC, full])));
+        assert_eq!(stdout(run(&["pubkey", "--xprv", &bip84, relative])), stdout(run(&["pubkey", TEST_MNEMONIC, full])));
+    }
+
+    // A relative path has no purpose to infer the address type from
+    assert_eq!(
+        stdout(run(&["address", "--xprv", &bip84, "m/0/0", "--type", "p2wpkh"])),
+        stdout(run(&["address", TEST_MNEMONIC, "m/84'/0'/0'/0/0"]))
+    );
+    assert_eq!(stdout(run(&["address", TEST_MNEMONIC, "m/84'/0'/0'/0/0"])), "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu\n");
+    let untyped = run(&["address", "--xprv", &bip84, "m/0/0"]);
+    assert!(!untyped.status.success());
+    assert!(String::from_utf8_lossy(&untyped.stderr).contains("pass --type"));
+    assert_eq!(
+        stdout(run(&["derive", "--xprv", &bip84, "m/0", "--range", "0-2", "--columns", "address", "--type", "p2wpkh"])),
+        stdout(run(&["derive", TEST_MNEMONIC, "m/84'/0'/0'/0", "--range", "0-2", "--columns", "address"]))
+    );
+    assert!(!run(&["derive", "--xprv", &bip84, "m/0", "--range", "0-2"]).status.success());
+
+    let bip44 = account_xprv("m/44'/0'/0'");
+    assert_eq!(stdout(run(&["address", "--xprv", &bip44, "m/0/0", "--type", "p2pkh"])), "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA\n");
+    assert_eq!(
+        stdout(run(&["derive", "--xprv", &bip44, "m/0", "--range", "0-2", "--columns", "address", "--type", "p2pkh"])),
+        stdout(run(&["derive", TEST_MNEMONIC, "m/44'/0'/0'/0", "--range", "0-2", "--columns", "address"]))
+    );
+
+    // A tprv keeps its network unless --network contradicts it
+    let tprv = stdout(run(&["derive", TEST_MNEMONIC, "m/44'/1'/0'", "--network", "testnet"]));
+    let tprv = tprv.trim().strip_prefix("tprv: ").unwrap();
+    assert_eq!(
+        stdout(run(&["derive", "--xprv", tprv, "m/0/0"])),
+        stdout(run(&["derive", TEST_MNEMONIC, "m/44'/1'/0'/0/0", "--network", "testnet"]))
+    );
+    assert_eq!(
+        stdout(run(&["address", "--xprv", tprv, "m/0/0", "--type", "p2pkh"])),
+        stdout(run(&["address", TEST_MNEMONIC, "m/44'/1'/0'/0/0", "--network", "testnet"]))
+    );
+    assert!(stdout(run(&["address", "--xprv", tprv, "m/0/0", "--type", "p2pkh", "--network", "regtest"])).starts_with(['m', 'n']));
+    let contradicted = run(&["derive", "--xprv", tprv, "m/0/0", "--network", "mainnet"]);
+    assert!(!contradicted.status.success());
+    assert!(String::from_utf8_lossy(&contradicted.stderr).contains("--network is mainnet"));
+    assert!(!run(&["address", "--xprv", &bip44, "m/0/0", "--type", "p2pkh", "--network", "testnet"]).status.success());
+
+    // A root key and a mnemonic are mutually exclusive
+    let mixed = run(&["derive", TEST_MNEMONIC, "m/0/5", "--xprv", &bip84]);
+    assert!(!mixed.status.success());
+    assert!(String::from_utf8_lossy(&mixed.stderr).contains("--xprv cannot be combined"));
+    assert!(!run(&["address", "--xprv", "xprvnotakey", "m/0/0"]).status.success());
+}
+
+#[test]
+fn test_cli_watch_only_addresses_from_zpub() {
+    // BIP84 test vector account zpub
+    let zpub = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+
+    let output = run(&["addresses", "--xpub", zpub, "--chain", "0", "--range", "0-4", "--type", "auto"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with(
+        "path  address  pubkey\n\
+         m/0/0  bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu  0330d54fd0dd420a6e5f8d3624f5f3482cae350f79d5f0753bf5beef9c2d91af3c\n\
+         m/0/1  bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g  03e775fd51f0dfb8cd865d9ff1cca2a158cf651fe997fdc9fee9c1d3b5e995ea77\n"
+    ));
+    assert_eq!(stdout.lines().count(), 6);
+    // The same five addresses and keys as deriving from the seed
+    let watch_only = run(&["addresses", "--xpub", zpub, "--range", "0-4", "--columns", "address,pubkey", "--format", "csv"]);
+    let from_seed = run(&["derive", TEST_MNEMONIC, "m/84'/0'/0'/0", "--range", "0-4", "--columns", "address,pubkey", "--format", "csv"]);
+    assert!(from_seed.status.success(), "{}", String::from_utf8_lossy(&from_seed.stderr));
+    assert_eq!(watch_only.stdout, from_seed.stdout);
+
+    let change = run(&["addresses", "--xpub", zpub, "--chain", "1", "--range", "0", "--columns", "path,address", "--format", "csv"]);
+    assert_eq!(String::from_utf8_lossy(&change.stdout), "path,address\nm/1/0,bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el\n");
+    let legacy = run(&["addresses", "--xpub", zpub, "--path", "m/0", "--range", "0", "--type", "p2pkh", "--columns", "address"]);
+    assert_eq!(String::from_utf8_lossy(&legacy.stdout), "address\n1JaUQDVNRdhfNsVncGkXedaPSM5Gc54Hso\n");
+    assert!(!run(&["addresses", "--xpub", zpub, "--columns", "index"]).status.success());
+
+    let hardened = run(&["addresses", "--xpub", zpub, "--path", "m/0'/1"]);
+    assert!(!hardened.status.success());
+    assert!(String::from_utf8_lossy(&hardened.stderr).contains("Cannot derive hardened child 0' from a public key"));
+    assert!(!run(&["addresses", "--xpub", zpub, "--path", "m/0", "--chain", "1"]).status.success());
+    assert!(!run(&["addresses", "--xpub", zpub, "--network", "testnet"]).status.success());
+}
+
+#[test]
+fn test_cli_descriptor_addresses_from_exported_descriptor() {
+    let descriptor = "wpkh([73c5da0a/84'/0'/0']xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/0/*)#wc3n3van";
+    let output = run(&["descriptor-addresses", "--descriptor", descriptor, "--range", "0-1", "--format", "csv"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "index,address\n0,bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu\n1,bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g\n"
+    );
+
+    assert!(!run(&["descriptor-addresses", "--descriptor", &descriptor.replace("#wc3n3van", "#wc3n3vaq")]).status.success());
+    let fixed = "wpkh(tprv8ZgxMBicQKsPd7Uf69XL1XwhmjHopUGep8GuEiJDZmbQz6o58LninorQAfcKZWARbtRtfnLcJ5MQ2AtHcQJCCRUcMRvmDUjyEmNUWwx8UbK/1/1/0)#t6wfjs64";
+    assert!(!run(&["descriptor-addresses", "--descriptor", fixed, "--range", "0-1"]).status.success());
+
+    // Descriptors have one command; `addresses` points there
+    let redirected = run(&["addresses", "--descriptor", descriptor]);
+    assert!(!redirected.status.success());
+    assert!(String::from_utf8_lossy(&redirected.stderr).contains("descriptor-addresses"));
+}
+
+// Key from Bitcoin Core's rpc_deriveaddresses.py; expected rows are its `deriveaddresses` results on regtest
+const CORE_TPRV: &str = "tprv8ZgxMBicQKsPd7Uf69XL1XwhmjHopUGep8GuEiJDZmbQz6o58LninorQAfcKZWARbtRtfnLcJ5MQ2AtHcQJCCRUcMRvmDUjyEmNUWwx8UbK";
+
+#[test]
+fn test_cli_descriptor_addresses_matches_core() {
+    let ranged = format!("wpkh({}/1/1/*)#kft60nuy", CORE_TPRV);
+    let output = run(&["descriptor-addresses", "--descriptor", &ranged, "--range", "0-2", "--network", "regtest"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.stderr.is_empty());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "index  address\n\
+         0  bcrt1qjqmxmkpmxt80xz4y3746zgt0q3u3ferr34acd5\n\
+         1  bcrt1qhku5rq7jz8ulufe2y6fkcpnlvpsta7rq4442dy\n\
+         2  bcrt1qpgptk2gvshyl0s9lqshsmx932l9ccsv265tvaq\n"
+    );
+    let last = run(&["descriptor-addresses", "--descriptor", &ranged, "--range", "2147483647", "--network", "regtest", "--format", "csv"]);
+    assert_eq!(String::from_utf8_lossy(&last.stdout), "index,address\n2147483647,bcrt1qtzs23vgzpreks5gtygwxf8tv5rldxvvsyfpdkg\n");
+
+    // The combo() fixture's pkh and sh(wpkh) forms, without checksums
+    for (descriptor, address) in [
+        (format!("pkh({}/1/1/0)", CORE_TPRV), "mtfUoUax9L4tzXARpw1oTGxWyoogp52KhJ"),
+        (format!("sh(wpkh({}/1/1/0))", CORE_TPRV), "2NDvEwGfpEqJWfybzpKPHF2XH3jwoQV3D7x"),
+    ] {
+        let output = run(&["descriptor-addresses", "--descriptor", &descriptor, "--network", "regtest", "--json"]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let rows: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(rows, serde_json::json!([{ "index": "0", "address": address }]));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("no checksum"));
+        assert!(stderr.contains(&format!("{}#", descriptor)));
+    }
+}
+
+#[test]
+fn test_cli_descriptor_addresses_lists_multisig_pubkeys() {
+    let a = "02ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f8";
+    let b = "02fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f";
+    let output = run(&["descriptor-addresses", "--descriptor", &format!("wsh(sortedmulti(2,{},{}))", a, b), "--format", "csv"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("index,address,pubkeys\n0,bc1qknwt9mhqpd7hrjrvpqz57zjqk28xlp2h90te6v22en0m3uctnams3pq5ce,\"{},{}\"\n", b, a)
+    );
+
+    let ranged = format!("wpkh({}/1/1/*)", CORE_TPRV);
+    assert!(!run(&["descriptor-addresses", "--descriptor", &ranged, "--json", "--format", "csv"]).status.success());
+    assert!(!run(&["descriptor-addresses", "--descriptor", &format!("{}#kft60nuq", ranged)]).status.success());
+}
+
+#[test]
+fn test_cli_export_accounts_matches_golden_file() {
+    let output = run(&["export", TEST_MNEMONIC, "--format", "accounts"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        include_str!("golden/accounts_standard_mainnet_0.json")
+    );
+
+    let testnet = run(&["export", TEST_MNEMONIC, "--format", "accounts", "--account", "1", "--network", "testnet"]);
+    let value: serde_json::Value = serde_json::from_slice(&testnet.stdout).unwrap();
+    assert_eq!(value["accounts"][0]["path"], "m/44'/1'/1'");
+
+    assert!(!run(&["export", TEST_MNEMONIC, "--format", "accounts", "--account", "x"]).status.success());
+}
+
+#[test]
+fn test_cli_address_and_xpub_follow_network() {
+    let address = run(&["address", TEST_MNEMONIC, "m/84'/1'/0'/0/0", "--network", "testnet"]);
+    assert_eq!(String::from_utf8_lossy(&address.stdout).trim(), "tb1q6rz28mcfaxtmd6v789l9rrlrusdprr9pqcpvkl");
+
+    let address = run(&["address", TEST_MNEMONIC, "m/44'/0'/0'/0/0"]);
+    assert_eq!(String::from_utf8_lossy(&address.stdout).trim(), "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA");
+
+    let xpub = run(&["xpub", TEST_MNEMONIC, "m/44'/0'/0'"]);
+    assert_eq!(
+        String::from_utf8_lossy(&xpub.stdout).trim(),
+        "xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj"
+    );
+    let tpub = run(&["xpub", "--network", "testnet", TEST_MNEMONIC, "m/84'/1'/0'"]);
+    assert!(String::from_utf8_lossy(&tpub.stdout).starts_with("tpub"));
+
+    assert!(!run(&["address", TEST_MNEMONIC, "m/0", "--network", "signet"]).status.success());
+
+    let regtest = run(&["address", TEST_MNEMONIC, "m/84'/1'/0'/0/0", "--network", "regtest"]);
+    assert!(String::from_utf8_lossy(&regtest.stdout).starts_with("bcrt1q"));
+}
+
+#[test]
+fn test_cli_seed_nonstandard_rounds_warns() {
+    let output = run(&["seed", TEST_MNEMONIC, "--nonstandard-rounds", "4096"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("dea9a1422f11979c"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("NON-BIP39"));
+
+    assert!(!run(&["seed", TEST_MNEMONIC, "--nonstandard-rounds", "100"]).status.success());
+}
+
+#[test]
+fn test_cli_wallet_new_json_is_consistent() {
+    use crypto_key_manager::address::{self, ScriptType};
+    use crypto_key_manager::{seed, ExtendedPublicKey, Network};
+
+    let output = run(&["wallet-new", "--json", "--allow-non-tty", "--words", "12"]);
+    assert!(output.status.success());
+    let wallet: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let mnemonic = wallet["mnemonic"].as_str().unwrap();
+    assert_eq!(mnemonic.split_whitespace().count(), 12);
+    let master = seed::generate_master_key_from_mnemonic(mnemonic, "").unwrap();
+    assert_eq!(wallet["fingerprint"].as_str().unwrap(), hex::encode(master.fingerprint().unwrap()));
+
+    let accounts = wallet["accounts"].as_array().unwrap();
+    assert_eq!(accounts.len(), 4);
+    for account in accounts {
+        let script_type = ScriptType::from_purpose(account["purpose"].as_u64().unwrap() as u32).unwrap();
+        let account_key = master.derive_path(account["path"].as_str().unwrap()).unwrap();
+
+        let xpub = ExtendedPublicKey::from_private(&account_key, script_type.slip_version(Network::Mainnet)).unwrap();
+        assert_eq!(account["xpub"].as_str().unwrap(), xpub.to_string());
+
+        for (index, printed) in account["receive_addresses"].as_array().unwrap().iter().enumerate() {
+            let key = account_key.derive_path(&format!("m/0/{}", index)).unwrap();
+            let expected = address::address(&key, script_type, Network::Mainnet).unwrap();
+            assert_eq!(printed.as_str().unwrap(), expected);
+        }
+    }
+}
+
+#[test]
+fn test_cli_wallet_new_refuses_non_tty_stdout() {
+    let output = run(&["wallet-new"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let text = run(&["wallet-new", "--allow-non-tty", "--testnet"]);
+    let stdout = String::from_utf8_lossy(&text.stdout);
+    assert!(stdout.contains(" 1. ") && stdout.contains("24. "));
+    assert!(stdout.contains("tpub") && stdout.contains("tb1p"));
+}
+
+#[test]
+fn test_cli_entropy_device_and_mix() {
+    // 16 zero bytes of entropy, then enough varied bytes for the health check
+    let mut state = 7u32;
+    let mut bytes = vec![0u8; 16];
+    bytes.extend((0..2048).map(|_| {
+        state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        (state >> 16) as u8
+    }));
+    let device = std::env::temp_dir().join(format!("ckm-{}-entropy.rng", std::process::id()));
+    std::fs::write(&device, &bytes).unwrap();
+    let device = device.to_str().unwrap();
+
+    let output = run(&["generate", "--entropy-device", device]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains(TEST_MNEMONIC));
+
+    let mixed = run(&["generate", "--entropy-device", device, "--entropy-mix", "system,device"]);
+    assert!(mixed.status.success(), "{}", String::from_utf8_lossy(&mixed.stderr));
+    assert!(!String::from_utf8_lossy(&mixed.stdout).contains(TEST_MNEMONIC));
+
+    assert!(!run(&["generate", "--entropy-device", "/nonexistent/hwrng"]).status.success());
+    assert!(!run(&["generate", "--entropy-mix", "system,device"]).status.success());
+    assert!(!run(&["generate", "--entropy-mix", "dice"]).status.success());
+    std::fs::remove_file(device).unwrap();
+}
+
+#[test]
+fn test_cli_generate_from_supplied_entropy() {
+    let zeros = "00".repeat(16);
+    let output = run(&["generate", "--entropy", &zeros]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Generated 12-word mnemonic:"));
+    assert!(stdout.contains(TEST_MNEMONIC));
+
+    let words = run(&["generate", "--words", "24", "--entropy", &"7f".repeat(32)]);
+    assert!(String::from_utf8_lossy(&words.stdout).contains("wave sausage worth title"));
+
+    assert!(!run(&["generate", "--words", "24", "--entropy", &zeros]).status.success());
+    assert!(!run(&["generate", "--entropy", "00ff"]).status.success());
+    assert!(!run(&["generate", "--entropy"]).status.success());
+}
+
+#[test]
+fn test_cli_generate_seedqr() {
+    let entropy = "5bbd9d71a8ec7990831aff359d426545";
+    let output = run(&["generate", "--entropy", entropy, "--qr", "--allow-non-tty"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("forum undo fragile fade shy sign arrest garment culture tube off merit"));
+    assert!(stdout.contains("SeedQR: 073318950739065415961602009907670428187212261116\n"));
+
+    let compact = run(&["generate", "--entropy", entropy, "--qr", "--format", "compact-seedqr", "--allow-non-tty"]);
+    assert!(String::from_utf8_lossy(&compact.stdout).contains(&format!("CompactSeedQR (hex): {}\n", entropy)));
+
+    // Secret QR payloads are not written to pipes without --allow-non-tty
+    let guarded = run(&["generate", "--entropy", entropy, "--qr"]);
+    assert!(!guarded.status.success());
+    assert!(guarded.stdout.is_empty());
+    assert!(!run(&["generate", "--qr", "--format", "ur", "--allow-non-tty"]).status.success());
+    // 15-word phrases have no SeedQR form, so nothing is printed at all
+    for args in [
+        &["generate", "--entropy", &"00".repeat(20), "--qr", "--allow-non-tty"][..],
+        &["generate", "--words", "15", "--qr", "--allow-non-tty"][..],
+    ] {
+        let output = run(args);
+        assert!(!output.status.success());
+        assert!(output.stdout.is_empty());
+    }
+    assert!(!run(&["generate", "--entropy", entropy, "--format", "seedqr"]).status.success());
+}
+
+#[test]
+fn test_cli_psbt_sign_writes_signed_psbt() {
+    // Regtest PSBT spending m/84'/1'/0'/0/0 (P2WPKH) and m/86'/1'/0'/0/0 (P2TR)
+    let unsigned = "cHNidP8BAHsCAAAAAhERERERERERERERERERERERERERERERERERERERERERAAAAAAD9////IiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIDAAAAAP////8B8EkCAAAAAAAWABTQxKPvCemXtumeOX5Rj+PkGhGMoQAAAAAAAQEfoIYBAAAAAAAWABTQxKPvCemXtumeOX5Rj+PkGhGMoSIGAuerJTe11J6XAwmq4G6eSfNs4cn+u9ROyODRzKC0+cMZGHPF2gpUAACAAQAAgAAAAIAAAAAAAAAAAAABAStg6gAAAAAAACJRIDuCsrKpGFMV2m+A2l8G0EQNil4UV/qTOHwtkZyG7IeGIRZVNVyoPJc/HZfODjhDyF14kFrxa03FMbxIjlchLSMBFhkAc8XaClYAAIABAACAAAAAgAAAAAAAAAAAARcgVTVcqDyXPx2Xzg44Q8hdeJBa8WtNxTG8SI5XIS0jARYAAA==";
+    let signed = "cHNidP8BAHsCAAAAAhERERERERERERERERERERERERERERERERERERERERERAAAAAAD9////IiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIDAAAAAP////8B8EkCAAAAAAAWABTQxKPvCemXtumeOX5Rj+PkGhGMoQAAAAAAAQEfoIYBAAAAAAAWABTQxKPvCemXtumeOX5Rj+PkGhGMoSIGAuerJTe11J6XAwmq4G6eSfNs4cn+u9ROyODRzKC0+cMZGHPF2gpUAACAAQAAgAAAAIAAAAAAAAAAACICAuerJTe11J6XAwmq4G6eSfNs4cn+u9ROyODRzKC0+cMZSDBFAiEAlyw41IBFZO3edWwmNsGibaneUA0qnUT4mMEJ+Wjh84MCIBVj5cffzor9KLhAY11gaRt7W/laVUByEXEppBmyZBlRAQABAStg6gAAAAAAACJRIDuCsrKpGFMV2m+A2l8G0EQNil4UV/qTOHwtkZyG7IeGIRZVNVyoPJc/HZfODjhDyF14kFrxa03FMbxIjlchLSMBFhkAc8XaClYAAIABAACAAAAAgAAAAAAAAAAAARcgVTVcqDyXPx2Xzg44Q8hdeJBa8WtNxTG8SI5XIS0jARYBE0DGvjKR3srSgyJdnk/h0D23p9cmosruSmcqMsD5xRlDyVk/uP/lDhePB5va4ne8pG+v3p2uMes7KTC7RZt3KpkxAAA=";
+
+    let dir = std::env::temp_dir();
+    let input = dir.join(format!("ckm-{}-unsigned.psbt", std::process::id()));
+    let output = dir.join(format!("ckm-{}-signed.psbt", std::process::id()));
+    std::fs::write(&input, unsigned).unwrap();
+    let _ = std::fs::remove_file(&output);
+
+    let result = run(&["psbt", "sign", TEST_MNEMONIC, "--in", input.to_str().unwrap(), "--out", output.to_str().unwrap()]);
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    assert!(String::from_utf8_lossy(&result.stdout).contains("Signed 2 of 2 inputs"));
+    assert_eq!(std::fs::read_to_string(&output).unwrap().trim(), signed);
+
+    // The signed file is not replaced without --force
+    let again = run(&["psbt", "sign", TEST_MNEMONIC, "--in", input.to_str().unwrap(), "--out", output.to_str().unwrap()]);
+    assert!(!again.status.success());
+    assert!(String::from_utf8_lossy(&again.stderr).contains("already exists"));
+    let forced = run(&["psbt", "sign", TEST_MNEMONIC, "--in", input.to_str().unwrap(), "--out", output.to_str().unwrap(), "--force"]);
+    assert!(forced.status.success(), "{}", String::from_utf8_lossy(&forced.stderr));
+
+    // A different wallet has no keys for these inputs
+    let foreign = run(&["psbt", "sign", TEST_MNEMONIC, "TREZOR", "--in", input.to_str().unwrap()]);
+    assert!(!foreign.status.success());
+    assert!(String::from_utf8_lossy(&foreign.stderr).contains("Input 0: skipped"));
+
+    // The master xprv signs the same way; an account xprv is refused
+    let master = run(&["derive", TEST_MNEMONIC, "m"]);
+    let master = String::from_utf8_lossy(&master.stdout).trim().strip_prefix("xprv: ").unwrap().to_string();
+    let from_xprv = run(&["psbt", "sign", "--xprv", &master, "--in", input.to_str().unwrap()]);
+    assert!(from_xprv.status.success(), "{}", String::from_utf8_lossy(&from_xprv.stderr));
+    assert_eq!(String::from_utf8_lossy(&from_xprv.stdout).trim(), signed);
+    let account = run(&["derive", TEST_MNEMONIC, "m/84'/1'/0'"]);
+    let account = String::from_utf8_lossy(&account.stdout).trim().strip_prefix("xprv: ").unwrap().to_string();
+    let refused = run(&["psbt", "sign", "--xprv", &account, "--in", input.to_str().unwrap()]);
+    assert!(!refused.status.success());
+    assert!(String::from_utf8_lossy(&refused.stderr).contains("must be the master key"));
+    assert!(!run(&["psbt", "sign", TEST_MNEMONIC, "--xprv", &master, "--in", input.to_str().unwrap()]).status.success());
+
+    let _ = std::fs::remove_file(input);
+    let _ = std::fs::remove_file(output);
+}
+
+#[test]
+fn test_cli_generate_passphrase() {
+    // The passphrase is a secret, so pipes need --allow-non-tty
+    assert!(!run(&["generate-passphrase", "--words", "7"]).status.success());
+
+    let output = run(&["generate-passphrase", "--words", "7", "--allow-non-tty"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.lines().next().unwrap().split(' ').count(), 7);
+    assert!(stdout.contains("~77 bits"));
+    assert!(stdout.contains("not a seed phrase"));
+
+    assert!(!run(&["generate-passphrase", "--words", "many", "--allow-non-tty"]).status.success());
+}
+
+#[test]
+fn test_cli_decode_suggests_bech32_typo() {
+    let output = run(&["decode", "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Checksum: INVALID"));
+    assert!(stdout.contains("possible typo at position 41: did you mean '4' instead of '5'?"), "{}", stdout);
+}
+
+#[test]
+fn test_cli_decode_hex_containing_separator() {
+    let output = run(&["decode", "0011223344556677"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("Encoding: Hex\n"), "{}", stdout);
+    assert!(stdout.contains("Bytes (hex): 0011223344556677"));
+}
+
+#[test]
+fn test_cli_children_lists_bip84_receive_addresses() {
+    let output = run(&["children", TEST_MNEMONIC, "m/84'/0'/0'", "0", "3"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "0 bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu\n\
+         1 bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g\n\
+         2 bc1qp59yckz4ae5c4efgw2s5wfyvrz0ala7rgvuz8z\n"
+    );
+
+    // --type overrides the purpose-derived script type
+    let legacy = run(&["children", TEST_MNEMONIC, "m/44'/0'/0'", "0", "1", "--type", "p2pkh"]);
+    assert_eq!(String::from_utf8_lossy(&legacy.stdout), "0 1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA\n");
+
+    // Ranges past the non-hardened limit are rejected
+    let overflow = run(&["children", TEST_MNEMONIC, "m/84'/0'/0'", "2147483647", "2"]);
+    assert!(!overflow.status.success());
+    assert!(!run(&["children", TEST_MNEMONIC, "m/84'/0'/0'", "0", "3", "--type", "p2xyz"]).status.success());
+}
+
+#[test]
+fn test_cli_keypair_new() {
+    let output = run(&["keypair", "new", "--allow-non-tty"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Private key (WIF): K") || stdout.contains("Private key (WIF): L"), "{}", stdout);
+    assert!(stdout.contains("Address:           bc1q"), "{}", stdout);
+
+    let testnet = run(&["--network", "testnet", "keypair", "new", "--allow-non-tty"]);
+    assert!(String::from_utf8_lossy(&testnet.stdout).contains("Address:           tb1q"));
+
+    // Private keys are not written to pipes without --allow-non-tty
+    assert!(!run(&["keypair", "new"]).status.success());
+    assert!(!run(&["keypair", "new", "--coin", "doge", "--allow-non-tty"]).status.success());
+}
+
+#[test]
+fn test_cli_vanity_finds_receive_address() {
+    let output = run(&["vanity", TEST_MNEMONIC, "m/84'/0'/0'", "cr"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "m/84'/0'/0'/0/0 bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu\n");
+
+    let missing = run(&["vanity", TEST_MNEMONIC, "m/84'/0'/0'", "qqqq", "--max-index", "50"]);
+    assert!(!missing.status.success());
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("No receive address up to index 50"));
+    assert!(!run(&["vanity", TEST_MNEMONIC, "m/84'/0'/0'", "b"]).status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_vanity_stops_on_ctrl_c() {
+    use std::process::Stdio;
+    use std::thread::sleep;
+    use std::time::{Duration, Instant};
+
+    // A prefix that will not turn up in any realistic time
+    let mut child = Command::new(env!("CARGO_BIN_EXE_crypto-key-manager"))
+        .args(["vanity", TEST_MNEMONIC, "m/84'/0'/0'", "qqqqqqqqqqqq", "--max-index", "2000000000"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run crypto-key-manager");
+    sleep(Duration::from_millis(1500));
+    let kill = Command::new("kill").args(["-INT", &child.id().to_string()]).status().unwrap();
+    assert!(kill.success());
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while child.try_wait().unwrap().is_none() {
+        if Instant::now() > deadline {
+            child.kill().unwrap();
+            panic!("vanity search kept running after SIGINT");
+        }
+        sleep(Duration::from_millis(20));
+    }
+    let output = child.wait_with_output().unwrap();
+    // Exited through the error path rather than being killed by the signal
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Error: Operation cancelled"));
+    assert!(output.stdout.is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_rejects_non_utf8_argument() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    // Latin-1 "café" in place of a mnemonic word
+    let output = Command::new(env!("CARGO_BIN_EXE_crypto-key-manager"))
+        .arg("validate")
+        .arg(OsStr::from_bytes(b"abandon caf\xe9"))
+        .output()
+        .expect("Failed to run crypto-key-manager");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Error: ") && stderr.contains("Argument 2 is not valid UTF-8"), "{}", stderr);
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_import_key_lists_addresses() {
+    let uncompressed = run(&["import-key", "--wif", "5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ"]);
+    assert!(uncompressed.status.success(), "{}", String::from_utf8_lossy(&uncompressed.stderr));
+    let stdout = String::from_utf8_lossy(&uncompressed.stdout);
+    assert!(stdout.contains("p2pkh:       1GAehh7TsJAHuUAeKZcXf5CnwuGuGgyX2S"), "{}", stdout);
+    assert!(!stdout.contains("p2wpkh:"), "{}", stdout);
+    assert!(String::from_utf8_lossy(&uncompressed.stderr).contains("not an HD wallet"));
+
+    let compressed = run(&["import-key", "--wif", "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617"]);
+    let stdout = String::from_utf8_lossy(&compressed.stdout);
+    assert!(stdout.contains("p2pkh:       1LoVGDgRs9hTfTNJNuXKSpywcbdvwRXpmK"), "{}", stdout);
+    assert!(stdout.contains("p2wpkh:      bc1q"), "{}", stdout);
+
+    // A raw hex key is compressed and follows --network
+    let hex = "0C28FCA386C7A227600B2FE50B7CAE11EC86D3BF1FBE471BE89827E19D72AA1D";
+    let testnet = run(&["import-key", "--hex", hex, "--network", "testnet"]);
+    assert!(String::from_utf8_lossy(&testnet.stdout).contains("p2wpkh:      tb1q"));
+
+    assert!(!run(&["import-key", "--wif", "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98618"]).status.success());
+}
+
+#[test]
+fn test_cli_seed_and_export_write_files() {
+    let dir = std::env::temp_dir();
+    let seed_file = dir.join(format!("ckm-{}-seed.txt", std::process::id()));
+    let export_file = dir.join(format!("ckm-{}-accounts.json", std::process::id()));
+    let _ = std::fs::remove_file(&seed_file);
+    let _ = std::fs::remove_file(&export_file);
+
+    let seed = run(&["seed", TEST_MNEMONIC, "--out", seed_file.to_str().unwrap()]);
+    assert!(seed.status.success(), "{}", String::from_utf8_lossy(&seed.stderr));
+    assert!(!String::from_utf8_lossy(&seed.stdout).contains("5eb00bbddcf069084889a8ab9155568165f5c453"));
+    assert!(std::fs::read_to_string(&seed_file).unwrap().starts_with("5eb00bbddcf069084889a8ab9155568165f5c453"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&seed_file).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+    assert!(!run(&["seed", TEST_MNEMONIC, "--out", seed_file.to_str().unwrap()]).status.success());
+
+    let export = run(&["export", TEST_MNEMONIC, "--format", "accounts", "--out", export_file.to_str().unwrap()]);
+    assert!(export.status.success(), "{}", String::from_utf8_lossy(&export.stderr));
+    let stdout = run(&["export", TEST_MNEMONIC, "--format", "accounts"]);
+    assert_eq!(std::fs::read(&export_file).unwrap(), stdout.stdout);
+
+    let _ = std::fs::remove_file(seed_file);
+    let _ = std::fs::remove_file(export_file);
+}